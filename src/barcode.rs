@@ -0,0 +1,116 @@
+use crate::{event::app_event::AppEvent, model::book::isbn};
+use image::{imageops::FilterType, DynamicImage};
+use log::{info, warn};
+use rxing::BarcodeFormat;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+use uuid::Uuid;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "gif", "tif", "tiff"];
+/// Phone photos are large enough that the decoder can miss the bars, so a
+/// failed attempt is retried on a copy scaled down to this width.
+const RETRY_WIDTH: u32 = 1200;
+
+pub struct ScannedBarcode {
+    pub source: PathBuf,
+    pub isbn: Result<String, String>,
+}
+
+/// Scans a single photo or every photo in a folder on its own thread and
+/// reports back through `AppEvent::BarcodesScanned`.
+pub fn scan_async(path: PathBuf, app_tx: Sender<AppEvent>) {
+    std::thread::spawn(move || {
+        let result = image_paths(&path)
+            .map(|paths| {
+                paths
+                    .into_iter()
+                    .map(|source| {
+                        let isbn = decode_isbn(&source);
+                        if let Err(e) = &isbn {
+                            warn!("No ISBN barcode in {}: {e}", source.display());
+                        }
+                        ScannedBarcode { source, isbn }
+                    })
+                    .collect()
+            })
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()));
+        app_tx.send(AppEvent::BarcodesScanned(result)).ok();
+    });
+}
+
+/// Reads the QR code of a label printed by `bookie labels` on its own thread
+/// and reports back through `AppEvent::LabelScanned`.
+pub fn scan_label_async(path: PathBuf, app_tx: Sender<AppEvent>) {
+    std::thread::spawn(move || {
+        let id = decode_label(&path);
+        if let Err(e) = &id {
+            warn!("No label QR code in {}: {e}", path.display());
+        }
+        app_tx.send(AppEvent::LabelScanned(id)).ok();
+    });
+}
+
+fn image_paths(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return fs::metadata(path).map(|_| vec![path.to_path_buf()]);
+    }
+    let mut paths = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_image(p))
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Reads the EAN-13 barcode printed on the back cover and returns its ISBN-13.
+pub fn decode_isbn(path: &Path) -> Result<String, String> {
+    let img = image::open(path).map_err(|e| e.to_string())?;
+    let text = decode_ean13(&img)
+        .or_else(|| {
+            (img.width() > RETRY_WIDTH)
+                .then(|| img.resize(RETRY_WIDTH, u32::MAX, FilterType::Triangle))
+                .and_then(|smaller| decode_ean13(&smaller))
+        })
+        .ok_or("no barcode found")?;
+    info!("Decoded barcode {text} in {}", path.display());
+    // Bookland EANs are the only ones that encode an ISBN.
+    if !text.starts_with("978") && !text.starts_with("979") {
+        return Err(format!("barcode {text} is not an ISBN"));
+    }
+    isbn::parse(&text).map(|(isbn13, _)| isbn13)
+}
+
+fn decode_ean13(img: &DynamicImage) -> Option<String> {
+    decode(img, BarcodeFormat::EAN_13)
+}
+
+/// Reads the book id from the QR code on a label.
+pub fn decode_label(path: &Path) -> Result<Uuid, String> {
+    let img = image::open(path).map_err(|e| e.to_string())?;
+    let text = decode(&img, BarcodeFormat::QR_CODE)
+        .or_else(|| {
+            (img.width() > RETRY_WIDTH)
+                .then(|| img.resize(RETRY_WIDTH, u32::MAX, FilterType::Triangle))
+                .and_then(|smaller| decode(&smaller, BarcodeFormat::QR_CODE))
+        })
+        .ok_or("no QR code found")?;
+    info!("Decoded QR code {text} in {}", path.display());
+    Uuid::parse_str(text.trim()).map_err(|_| format!("QR code '{text}' is not a bookie label"))
+}
+
+fn decode(img: &DynamicImage, format: BarcodeFormat) -> Option<String> {
+    let luma = img.to_luma8();
+    let (width, height) = luma.dimensions();
+    rxing::helpers::detect_in_luma(luma.into_raw(), width, height, Some(format))
+        .ok()
+        .map(|result| result.getText().to_string())
+}
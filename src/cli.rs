@@ -0,0 +1,130 @@
+use crate::{
+    export::{catalog::CatalogGrouping, columns::Column, ExportFormat},
+    import::ImportFormat,
+};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(version, about = "Terminal book manager")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Index Open Library dump files (editions, works, authors) for offline lookup.
+    IndexDump {
+        /// Dump files, either `.txt.gz` as downloaded or uncompressed.
+        #[arg(required = true)]
+        dumps: Vec<PathBuf>,
+        /// Output directory, defaults to `lookup.offline_index` from the config.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Add the EPUB and PDF files in a directory to the library, or update their links.
+    ScanLibrary {
+        /// Directory scanned recursively.
+        dir: PathBuf,
+    },
+    /// Import the books of a Calibre library.
+    ImportCalibre {
+        /// Calibre library folder.
+        library: PathBuf,
+        /// Read `metadata.db` instead of the `metadata.opf` in every book folder.
+        #[arg(long)]
+        db: bool,
+        /// Only show what would be imported.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import reading progress and highlights from a Kobo or KOReader device folder.
+    ImportDevice {
+        /// Device root or copied folder, or a `KoboReader.sqlite` file.
+        path: PathBuf,
+        /// Only show what would be updated.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import books and reading history from another service's export or a reference manager.
+    Import {
+        #[arg(long, value_enum)]
+        format: ImportFormat,
+        file: PathBuf,
+    },
+    /// Export the library for another service, as CSV or JSON Lines, or through a template.
+    Export {
+        #[arg(long, value_enum, required_unless_present = "template")]
+        format: Option<ExportFormat>,
+        /// Tera template rendered with the books, stats, authors and years.
+        #[arg(long, conflicts_with_all = ["format", "columns"])]
+        template: Option<PathBuf>,
+        /// Only export books whose title or authors contain this text, or with this ISBN.
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Comma-separated columns for csv and jsonl, defaults to all of them.
+        #[arg(short, long, value_enum, value_delimiter = ',')]
+        columns: Vec<Column>,
+        /// Output file, defaults to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write a markdown note per book, with author and year index notes, for Obsidian and similar.
+    ExportMarkdown {
+        /// Vault or folder the notes are written to.
+        dir: PathBuf,
+    },
+    /// Generate a static website with covers, a page per book, yearly pages and stats.
+    Site {
+        /// Output directory, created when missing.
+        outdir: PathBuf,
+    },
+    /// Write a printable PDF catalog with covers, sections and a title index.
+    Catalog {
+        /// PDF file to write.
+        output: PathBuf,
+        #[arg(long, value_enum, default_value = "author")]
+        group_by: CatalogGrouping,
+        /// Only include books whose title or authors contain this text, or with this ISBN.
+        #[arg(short, long)]
+        query: Option<String>,
+    },
+    /// Print sheets of labels with a QR code of the book id, its title and location.
+    Labels {
+        /// An SVG file per sheet, or a single PDF when the name ends in `.pdf`.
+        output: PathBuf,
+        /// Only label books whose title or authors contain this text, or with this ISBN.
+        #[arg(short, long)]
+        query: Option<String>,
+    },
+    /// Write an Atom feed of finished books, newest first.
+    Feed {
+        /// Output file, defaults to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Most entries in the feed, overriding `feed.max_entries` in the config.
+        #[arg(long)]
+        max_entries: Option<usize>,
+    },
+    /// Serve a JSON API for the library, or an OPDS catalog, over HTTP until stopped.
+    Serve {
+        /// Serve an OPDS catalog of the linked ebooks for e-reader apps instead of the API.
+        #[arg(long)]
+        opds: bool,
+        /// Bearer token the API asks for, overriding `server.token` in the config.
+        #[arg(long, conflicts_with = "opds")]
+        token: Option<String>,
+        /// Only allow API requests that read, overriding `server.read_only` in the config.
+        #[arg(long, conflicts_with = "opds")]
+        read_only: bool,
+        /// Web page origin allowed to call the API, overriding `server.allowed_origin`.
+        #[arg(long, conflicts_with = "opds")]
+        allowed_origin: Option<String>,
+        /// Address to listen on; use 0.0.0.0 to reach it from other devices.
+        #[arg(long, default_value = "127.0.0.1")]
+        address: String,
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+    },
+}
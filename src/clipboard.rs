@@ -0,0 +1,10 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::io::{self, Write};
+
+/// Puts the text on the system clipboard with the OSC 52 escape sequence. The
+/// terminal does the copying, so it works over SSH without a clipboard daemon.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
@@ -0,0 +1,82 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+const CONFIG_PATH: &str = "bookie-config.json";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub lookup: LookupConfig,
+    pub feed: FeedConfig,
+    pub server: ServerConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookupConfig {
+    /// Base URL of an Open Library compatible API, e.g. a local mirror or a mock server.
+    pub base_url: String,
+    pub covers_url: String,
+    pub download_covers: bool,
+    /// Directory written by `bookie index-dump`, used for offline title suggestions.
+    pub offline_index: PathBuf,
+}
+
+impl Default for LookupConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://openlibrary.org".to_string(),
+            covers_url: "https://covers.openlibrary.org".to_string(),
+            download_covers: true,
+            offline_index: PathBuf::from("openlibrary-index"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedConfig {
+    pub title: String,
+    pub author: String,
+    /// Where the output of `bookie site` is published; entries link to its pages when set.
+    pub site_url: String,
+    pub max_entries: usize,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            title: "Books I finished".to_string(),
+            author: "bookie".to_string(),
+            site_url: String::new(),
+            max_entries: 50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Bearer token the API asks for. Without one the API only answers
+    /// reads, and only on the computer itself.
+    pub token: String,
+    /// Refuse the API requests that change the library.
+    pub read_only: bool,
+    /// Origin of a web page allowed to call the API, e.g.
+    /// `http://localhost:3000`; empty allows none.
+    pub allowed_origin: String,
+}
+
+impl Config {
+    /// Missing config file means defaults; a broken one is logged and ignored.
+    pub fn load() -> Self {
+        match fs::read_to_string(CONFIG_PATH) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+                error!("Failed to parse {CONFIG_PATH}, using defaults: {e}");
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+}
@@ -1,8 +1,13 @@
+use crate::{barcode::ScannedBarcode, metadata::Metadata};
 use ratatui::crossterm::event::KeyEvent;
 use ratatui_image::thread::ResizeResponse;
+use uuid::Uuid;
 
 pub enum AppEvent {
     Key(KeyEvent),
     CoverReady(ResizeResponse),
+    MetadataReady(Result<Metadata, String>),
+    BarcodesScanned(Result<Vec<ScannedBarcode>, String>),
+    LabelScanned(Result<Uuid, String>),
     Resize,
 }
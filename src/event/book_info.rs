@@ -9,10 +9,18 @@ pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
         (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
             Some(Message::CancelForm)
         }
+        (KeyCode::Char('l'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::LookupMetadata)
+        }
+        (KeyCode::Char('x'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::StopReview)
+        }
         (KeyCode::Enter, _) => Some(Message::SubmitForm),
         (KeyCode::Char(c), _) => Some(Message::InsertChar(c)),
         (KeyCode::Left, _) => Some(Message::FormLeft),
         (KeyCode::Right, _) => Some(Message::FormRight),
+        (KeyCode::Down, _) => Some(Message::NextSuggestion),
+        (KeyCode::Up, _) => Some(Message::PreviousSuggestion),
         (KeyCode::Backspace, _) => Some(Message::DeleteChar),
         _ => None,
     }
@@ -1,4 +1,4 @@
-use crate::event::Message;
+use crate::{event::Message, model::status::Prompt};
 use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
 
 pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
@@ -10,6 +10,15 @@ pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
         (KeyCode::Char('a'), _) => Some(Message::AddBook),
         (KeyCode::Char('e'), _) => Some(Message::EditBook),
         (KeyCode::Char('d'), _) => Some(Message::ConfirmDeleteBook),
+        (KeyCode::Char('p'), _) => Some(Message::TogglePrivate),
+        (KeyCode::Char('c'), _) => Some(Message::ChooseCitationStyle),
+        (KeyCode::Char('t'), _) => Some(Message::CycleTypeFilter),
+        (KeyCode::Char('r'), _) => Some(Message::OpenReader),
+        (KeyCode::Char('/'), _) => Some(Message::OpenPrompt(Prompt::Search)),
+        (KeyCode::Char('b'), _) => Some(Message::OpenPrompt(Prompt::BarcodeImage)),
+        (KeyCode::Char('o'), _) => Some(Message::OpenPrompt(Prompt::LabelImage)),
+        (KeyCode::Char('s'), _) => Some(Message::OpenPrompt(Prompt::CardPath)),
+        (KeyCode::Char('i'), _) => Some(Message::OpenPrompt(Prompt::ImportFile)),
         _ => None,
     }
 }
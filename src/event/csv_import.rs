@@ -0,0 +1,24 @@
+use crate::{event::Message, model::status::Prompt};
+use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
+
+pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::CloseCsvImport)
+        }
+        (KeyCode::Char('q') | KeyCode::Esc, _) => Some(Message::CloseCsvImport),
+        (KeyCode::Right, _) => Some(Message::CsvNextColumn),
+        (KeyCode::Left, _) => Some(Message::CsvPreviousColumn),
+        (KeyCode::Down, _) => Some(Message::CsvNextField),
+        (KeyCode::Up, _) => Some(Message::CsvPreviousField),
+        (KeyCode::Char('d'), _) => Some(Message::CsvCycleDelimiter),
+        (KeyCode::Char('f'), _) => Some(Message::CsvCycleDateFormat),
+        (KeyCode::Char('s'), _) => Some(Message::CsvCycleListSeparator),
+        (KeyCode::Char('p'), _) => Some(Message::CsvNextPreset),
+        (KeyCode::Char('w'), _) => Some(Message::OpenPrompt(Prompt::PresetName)),
+        (KeyCode::PageDown, _) => Some(Message::CsvProblemsDown),
+        (KeyCode::PageUp, _) => Some(Message::CsvProblemsUp),
+        (KeyCode::Enter, _) => Some(Message::SubmitCsvImport),
+        _ => None,
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{event::Message, model::status::Prompt};
+use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
+
+pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::CloseListImport)
+        }
+        (KeyCode::Char('q') | KeyCode::Esc, _) => Some(Message::CloseListImport),
+        (KeyCode::Down, _) => Some(Message::NextCandidate),
+        (KeyCode::Up, _) => Some(Message::PreviousCandidate),
+        (KeyCode::Char('a'), _) => Some(Message::AcceptCandidate),
+        (KeyCode::Char('x'), _) => Some(Message::RejectCandidate),
+        (KeyCode::Char(' '), _) => Some(Message::ToggleCandidate),
+        (KeyCode::Char('e'), _) => Some(Message::OpenPrompt(Prompt::EditCandidate)),
+        (KeyCode::Enter, _) => Some(Message::SubmitListImport),
+        _ => None,
+    }
+}
@@ -1,11 +1,15 @@
 pub mod app_event;
 mod book_info;
 mod book_table;
+mod csv_import;
+mod list_import;
+mod prompt;
+mod reader;
 mod status;
 
 use crate::{
     event::app_event::AppEvent,
-    model::{focus::Focus, Model},
+    model::{book::citation::CitationStyle, focus::Focus, status::Prompt, Model},
 };
 use ratatui::crossterm::event::{self, Event};
 use std::sync::mpsc::Sender;
@@ -15,11 +19,56 @@ pub enum Message {
     Quit,
     NextBook,
     PreviousBook,
+    CycleTypeFilter,
     AddBook,
     EditBook,
     DeleteBook,
     ConfirmDeleteBook,
+    TogglePrivate,
+    ChooseCitationStyle,
+    CopyCitation(CitationStyle),
     CancelConfirm,
+    // Reader messages
+    OpenReader,
+    CloseReader,
+    ReaderDown,
+    ReaderUp,
+    ReaderPageDown,
+    ReaderPageUp,
+    ReaderNextChapter,
+    ReaderPreviousChapter,
+    ToggleToc,
+    JumpToTocEntry,
+    FinishBook,
+    // CSV import messages
+    CloseCsvImport,
+    CsvNextColumn,
+    CsvPreviousColumn,
+    CsvNextField,
+    CsvPreviousField,
+    CsvCycleDelimiter,
+    CsvCycleDateFormat,
+    CsvCycleListSeparator,
+    CsvNextPreset,
+    CsvProblemsDown,
+    CsvProblemsUp,
+    SubmitCsvImport,
+    // Plain-text list import messages
+    CloseListImport,
+    NextCandidate,
+    PreviousCandidate,
+    AcceptCandidate,
+    RejectCandidate,
+    ToggleCandidate,
+    SubmitListImport,
+    // Status bar prompt messages
+    OpenPrompt(Prompt),
+    CancelPrompt,
+    PromptInsertChar(char),
+    PromptDeleteChar,
+    PromptLeft,
+    PromptRight,
+    SubmitPrompt,
     // Form messages
     CancelForm,
     InsertChar(char),
@@ -28,7 +77,11 @@ pub enum Message {
     FormLeft,
     NextFormField,
     PreviousFormField,
+    NextSuggestion,
+    PreviousSuggestion,
     SubmitForm,
+    LookupMetadata,
+    StopReview,
 }
 
 pub fn spawn_input_thread(tx: Sender<AppEvent>) {
@@ -51,6 +104,10 @@ pub fn handle_key(model: &Model, key: event::KeyEvent) -> Option<Message> {
     match model.focus {
         Focus::Table => book_table::handle_key(key),
         Focus::Info => book_info::handle_key(key),
-        Focus::Status => status::handle_key(key),
+        Focus::Status => status::handle_key(&model.status.mode, key),
+        Focus::Prompt => prompt::handle_key(key),
+        Focus::Reader => reader::handle_key(key),
+        Focus::CsvImport => csv_import::handle_key(key),
+        Focus::ListImport => list_import::handle_key(key),
     }
 }
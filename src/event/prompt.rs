@@ -0,0 +1,17 @@
+use crate::event::Message;
+use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
+
+pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Esc, _) => Some(Message::CancelPrompt),
+        (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::CancelPrompt)
+        }
+        (KeyCode::Enter, _) => Some(Message::SubmitPrompt),
+        (KeyCode::Char(c), _) => Some(Message::PromptInsertChar(c)),
+        (KeyCode::Left, _) => Some(Message::PromptLeft),
+        (KeyCode::Right, _) => Some(Message::PromptRight),
+        (KeyCode::Backspace, _) => Some(Message::PromptDeleteChar),
+        _ => None,
+    }
+}
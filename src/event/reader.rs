@@ -0,0 +1,20 @@
+use crate::event::Message;
+use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
+
+pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::CloseReader)
+        }
+        (KeyCode::Char('q') | KeyCode::Esc, _) => Some(Message::CloseReader),
+        (KeyCode::Down | KeyCode::Char('j'), _) => Some(Message::ReaderDown),
+        (KeyCode::Up | KeyCode::Char('k'), _) => Some(Message::ReaderUp),
+        (KeyCode::PageDown | KeyCode::Char(' '), _) => Some(Message::ReaderPageDown),
+        (KeyCode::PageUp, _) => Some(Message::ReaderPageUp),
+        (KeyCode::Right | KeyCode::Char('n'), _) => Some(Message::ReaderNextChapter),
+        (KeyCode::Left | KeyCode::Char('p'), _) => Some(Message::ReaderPreviousChapter),
+        (KeyCode::Char('t'), _) => Some(Message::ToggleToc),
+        (KeyCode::Enter, _) => Some(Message::JumpToTocEntry),
+        _ => None,
+    }
+}
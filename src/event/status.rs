@@ -1,9 +1,18 @@
-use crate::event::Message;
+use crate::{
+    event::Message,
+    model::{book::citation::CitationStyle, status::StatusMode},
+};
 use ratatui::crossterm::event::{self, KeyCode, KeyModifiers};
 
-pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
+pub fn handle_key(mode: &StatusMode, key: event::KeyEvent) -> Option<Message> {
+    if let StatusMode::ChooseCitationStyle = mode {
+        return handle_citation_key(key);
+    }
     match (key.code, key.modifiers) {
-        (KeyCode::Char('y'), _) => Some(Message::DeleteBook),
+        (KeyCode::Char('y'), _) => match mode {
+            StatusMode::ConfirmFinishBook => Some(Message::FinishBook),
+            _ => Some(Message::DeleteBook),
+        },
         (KeyCode::Char('n'), _) => Some(Message::CancelConfirm),
         (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
             Some(Message::CancelConfirm)
@@ -11,3 +20,17 @@ pub fn handle_key(key: event::KeyEvent) -> Option<Message> {
         _ => None,
     }
 }
+
+fn handle_citation_key(key: event::KeyEvent) -> Option<Message> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('c'), mods) if mods.contains(KeyModifiers::CONTROL) => {
+            Some(Message::CancelConfirm)
+        }
+        (KeyCode::Char('a'), _) => Some(Message::CopyCitation(CitationStyle::Apa)),
+        (KeyCode::Char('m'), _) => Some(Message::CopyCitation(CitationStyle::Mla)),
+        (KeyCode::Char('c'), _) => Some(Message::CopyCitation(CitationStyle::Chicago)),
+        (KeyCode::Char('s'), _) => Some(Message::CopyCitation(CitationStyle::Short)),
+        (KeyCode::Esc | KeyCode::Char('q'), _) => Some(Message::CancelConfirm),
+        _ => None,
+    }
+}
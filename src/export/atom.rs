@@ -0,0 +1,150 @@
+use crate::{
+    config::FeedConfig,
+    export::site::{escape, slug},
+    model::book::Book,
+};
+use chrono::{Datelike, NaiveDate};
+use std::io::{self, Write};
+use uuid::Uuid;
+
+/// Id of feeds without a site URL, so readers still recognise the feed.
+const FEED_ID: &str = "urn:uuid:5b0f3a4e-8c1d-4e57-9a62-3f1d2c7b9e40";
+/// Highlights are cut to this many characters in entries.
+const EXCERPT_LENGTH: usize = 280;
+
+/// An Atom feed with an entry for every finish date, newest first. Entries
+/// link to the pages of `bookie site` when the site URL is configured.
+/// Private books are left out.
+pub fn write(books: &[Book], config: &FeedConfig, mut output: impl Write) -> io::Result<()> {
+    let mut finished = books
+        .iter()
+        .filter(|b| !b.private)
+        .flat_map(|b| b.finished_at.iter().map(move |d| (*d, b)))
+        .collect::<Vec<_>>();
+    finished.sort_by(|(a, _), (b, _)| b.cmp(a));
+    finished.truncate(config.max_entries);
+
+    let base_url = config.site_url.trim_end_matches('/');
+    let feed_id = if base_url.is_empty() {
+        FEED_ID.to_string()
+    } else {
+        format!("{base_url}/feed.xml")
+    };
+    let updated = finished
+        .first()
+        .map(|(date, _)| timestamp(*date))
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string());
+
+    writeln!(output, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(output, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+    writeln!(output, "  <title>{}</title>", escape(&config.title))?;
+    writeln!(output, "  <id>{}</id>", escape(&feed_id))?;
+    writeln!(output, "  <updated>{updated}</updated>")?;
+    writeln!(
+        output,
+        "  <author><name>{}</name></author>",
+        escape(&config.author)
+    )?;
+    if !base_url.is_empty() {
+        writeln!(
+            output,
+            r#"  <link rel="self" href="{}"/>"#,
+            escape(&feed_id)
+        )?;
+        writeln!(
+            output,
+            r#"  <link rel="alternate" href="{}/"/>"#,
+            escape(base_url)
+        )?;
+    }
+    for (date, book) in finished {
+        write_entry(&mut output, book, date, base_url)?;
+    }
+    writeln!(output, "</feed>")?;
+    output.flush()?;
+    Ok(())
+}
+
+fn write_entry(
+    output: &mut impl Write,
+    book: &Book,
+    date: NaiveDate,
+    base_url: &str,
+) -> io::Result<()> {
+    let mut title = format!("Finished {}", book.title);
+    if !book.authors.is_empty() {
+        title.push_str(&format!(" by {}", book.authors.join(", ")));
+    }
+    writeln!(output, "  <entry>")?;
+    writeln!(output, "    <title>{}</title>", escape(&title))?;
+    writeln!(output, "    <id>urn:uuid:{}</id>", entry_id(book, date))?;
+    writeln!(output, "    <updated>{}</updated>", timestamp(date))?;
+
+    let cover = (!base_url.is_empty() && book.cover_path.as_ref().is_some_and(|p| p.is_file()))
+        .then(|| format!("{base_url}/covers/{}.jpg", slug(book)));
+    if !base_url.is_empty() {
+        writeln!(
+            output,
+            r#"    <link rel="alternate" href="{}"/>"#,
+            escape(&format!("{base_url}/books/{}.html", slug(book)))
+        )?;
+    }
+    if let Some(cover) = &cover {
+        writeln!(
+            output,
+            r#"    <link rel="enclosure" type="image/jpeg" href="{}"/>"#,
+            escape(cover)
+        )?;
+    }
+
+    let mut content = vec![];
+    if let Some(cover) = &cover {
+        content.push(format!(
+            r#"<p><img src="{}" alt="Cover of {}"></p>"#,
+            escape(cover),
+            escape(&book.title)
+        ));
+    }
+    if book.rating > 0 {
+        content.push(format!(
+            "<p>Rating: {} ({}/5)</p>",
+            "★".repeat(book.rating as usize),
+            book.rating
+        ));
+    }
+    if let Some(highlight) = book.highlights.first() {
+        content.push(format!(
+            "<blockquote>{}</blockquote>",
+            escape(&excerpt(&highlight.text))
+        ));
+    }
+    if !content.is_empty() {
+        writeln!(
+            output,
+            r#"    <content type="html">{}</content>"#,
+            escape(&content.join(""))
+        )?;
+    }
+    writeln!(output, "  </entry>")?;
+    Ok(())
+}
+
+/// The same id every time the feed is written: the book's id with the day
+/// of the finish date mixed into its last bits.
+fn entry_id(book: &Book, date: NaiveDate) -> Uuid {
+    Uuid::from_u128(book.id.as_u128() ^ date.num_days_from_ce() as u128)
+}
+
+fn timestamp(date: NaiveDate) -> String {
+    format!("{date}T00:00:00Z")
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= EXCERPT_LENGTH {
+        return text;
+    }
+    let cut = text.chars().take(EXCERPT_LENGTH).collect::<String>();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{cut}…")
+}
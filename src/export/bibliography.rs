@@ -0,0 +1,292 @@
+use crate::model::book::{author_name::AuthorName, item_type::ItemType, Book};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+};
+
+/// `@book{herbert1965dune, ...}` entries with unique citation keys.
+pub fn write_bibtex(books: &[Book], mut output: impl Write) -> io::Result<()> {
+    let keys = citation_keys(books);
+    for (book, key) in books.iter().zip(keys) {
+        let entry_type = match book.item_type {
+            ItemType::Book | ItemType::Comic => "book",
+            ItemType::Magazine | ItemType::Paper => "article",
+        };
+        writeln!(output, "@{entry_type}{{{key},")?;
+        for (field, value) in fields(book) {
+            let value = match field {
+                "author" => value
+                    .split('\n')
+                    .map(escape_bibtex)
+                    .collect::<Vec<_>>()
+                    .join(" and "),
+                _ => escape_bibtex(&value),
+            };
+            writeln!(output, "  {field} = {{{value}}},")?;
+        }
+        writeln!(output, "}}\n")?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// A CSL-JSON array, as Zotero and pandoc read it.
+pub fn write_csl_json(books: &[Book], mut output: impl Write) -> io::Result<()> {
+    let items = books
+        .iter()
+        .zip(citation_keys(books))
+        .map(|(book, key)| {
+            let mut item = Map::new();
+            item.insert("id".to_string(), json!(key));
+            item.insert(
+                "type".to_string(),
+                json!(match book.item_type {
+                    ItemType::Book | ItemType::Comic => "book",
+                    ItemType::Magazine => "article-magazine",
+                    ItemType::Paper => "article-journal",
+                }),
+            );
+            item.insert("title".to_string(), json!(book.title));
+            let authors = book
+                .author_names()
+                .into_iter()
+                .map(|name| {
+                    let mut author = Map::new();
+                    author.insert("family".to_string(), json!(name.family));
+                    if !name.given.is_empty() {
+                        author.insert("given".to_string(), json!(name.given));
+                    }
+                    if let Some(suffix) = name.suffix {
+                        author.insert("suffix".to_string(), json!(suffix));
+                    }
+                    Value::Object(author)
+                })
+                .collect::<Vec<_>>();
+            if !authors.is_empty() {
+                item.insert("author".to_string(), Value::Array(authors));
+            }
+            if book.year > 0 {
+                item.insert("issued".to_string(), json!({"date-parts": [[book.year]]}));
+            }
+            let optional = [
+                ("publisher", book.publisher.as_ref()),
+                ("ISBN", book.identifiers.isbn()),
+                ("DOI", book.doi.as_ref()),
+                ("container-title", book.venue.as_ref()),
+            ];
+            for (key, value) in optional {
+                if let Some(value) = value {
+                    item.insert(key.to_string(), json!(value));
+                }
+            }
+            Value::Object(item)
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut output, &items)?;
+    writeln!(output)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// RIS records, one tag per line, as EndNote and most reference managers import them.
+pub fn write_ris(books: &[Book], mut output: impl Write) -> io::Result<()> {
+    for book in books {
+        let entry_type = match book.item_type {
+            ItemType::Book | ItemType::Comic => "BOOK",
+            ItemType::Magazine => "MGZN",
+            ItemType::Paper => "JOUR",
+        };
+        writeln!(output, "TY  - {entry_type}")?;
+        for (field, value) in fields(book) {
+            let tag = match field {
+                "title" => "TI",
+                "author" => "AU",
+                "year" => "PY",
+                "publisher" => "PB",
+                "isbn" => "SN",
+                "doi" => "DO",
+                "journal" => "T2",
+                _ => continue,
+            };
+            for value in value.split('\n') {
+                writeln!(output, "{tag}  - {value}")?;
+            }
+        }
+        writeln!(output, "ER  - \n")?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// The fields every format shares, named as in BibTeX. Authors are inverted
+/// and separated by line breaks.
+fn fields(book: &Book) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("title", book.title.clone())];
+    if !book.authors.is_empty() {
+        let authors = book
+            .author_names()
+            .iter()
+            .map(AuthorName::inverted)
+            .collect::<Vec<_>>();
+        fields.push(("author", authors.join("\n")));
+    }
+    if book.year > 0 {
+        fields.push(("year", book.year.to_string()));
+    }
+    if let Some(publisher) = &book.publisher {
+        fields.push(("publisher", publisher.clone()));
+    }
+    if let Some(isbn) = book.identifiers.isbn() {
+        fields.push(("isbn", isbn.clone()));
+    }
+    if let Some(doi) = &book.doi {
+        fields.push(("doi", doi.clone()));
+    }
+    if let Some(venue) = &book.venue {
+        fields.push(("journal", venue.clone()));
+    }
+    fields
+}
+
+/// First author's family name, year and first title word, with a letter
+/// added when two books would get the same key.
+fn citation_keys(books: &[Book]) -> Vec<String> {
+    let mut used = HashSet::new();
+    books
+        .iter()
+        .map(|book| {
+            let family = book
+                .author_names()
+                .first()
+                .map(|a| a.family.clone())
+                .unwrap_or_default();
+            let word = book
+                .title
+                .split_whitespace()
+                .find(|w| !["the", "a", "an"].contains(&w.to_lowercase().as_str()))
+                .unwrap_or_default();
+            let year = if book.year > 0 {
+                book.year.to_string()
+            } else {
+                String::new()
+            };
+            let base = format!("{}{year}{}", key_part(&family), key_part(word));
+            let base = if base.is_empty() {
+                "untitled".to_string()
+            } else {
+                base
+            };
+            let key = std::iter::once(base.clone())
+                .chain(('a'..='z').map(|c| format!("{base}{c}")))
+                .chain((1..).map(|n| format!("{base}-{n}")))
+                .find(|k| !used.contains(k))
+                .unwrap_or(base);
+            used.insert(key.clone());
+            key
+        })
+        .collect()
+}
+
+/// Lowercase ASCII letters and digits, with accents dropped from common letters.
+fn key_part(text: &str) -> String {
+    let mut key = String::new();
+    for c in text.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            key.push(c);
+            continue;
+        }
+        let folded = match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+            'æ' => "ae",
+            'ç' | 'č' | 'ć' => "c",
+            'è' | 'é' | 'ê' | 'ë' | 'ě' => "e",
+            'ì' | 'í' | 'î' | 'ï' | 'ı' => "i",
+            'ñ' | 'ń' | 'ň' => "n",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+            'œ' => "oe",
+            'ř' => "r",
+            'ß' => "ss",
+            'š' | 'ş' => "s",
+            'ù' | 'ú' | 'û' | 'ü' => "u",
+            'ý' | 'ÿ' => "y",
+            'ž' => "z",
+            'ł' => "l",
+            _ => "",
+        };
+        key.push_str(folded);
+    }
+    key
+}
+
+/// Escapes the characters LaTeX treats specially.
+fn escape_bibtex(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn book(title: &str, authors: &[&str], year: u16) -> Book {
+        Book {
+            title: title.to_string(),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            year,
+            ..Book::default()
+        }
+    }
+
+    #[rstest]
+    #[case(book("Dune", &["Frank Herbert"], 1965), "herbert1965dune")]
+    #[case(book("The Dispossessed", &["Ursula K. Le Guin"], 1974), "leguin1974dispossessed")]
+    #[case(book("Cien años de soledad", &["Gabriel García Márquez"], 1967), "marquez1967cien")]
+    #[case(book("Ænima", &[], 0), "aenima")]
+    #[case(book("The", &[], 0), "untitled")]
+    fn builds_keys(#[case] book: Book, #[case] key: &str) {
+        assert_eq!(citation_keys(&[book]), [key]);
+    }
+
+    #[test]
+    fn adds_letters_to_colliding_keys() {
+        let dune = book("Dune", &["Frank Herbert"], 1965);
+        let keys = citation_keys(&[
+            dune.clone(),
+            book("Dune Messiah", &["Frank Herbert"], 1965),
+            dune.clone(),
+            book("Dune", &["Brian Herbert"], 1965),
+        ]);
+        assert_eq!(
+            keys,
+            [
+                "herbert1965dune",
+                "herbert1965dunea",
+                "herbert1965duneb",
+                "herbert1965dunec"
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_keys_after_the_alphabet() {
+        let dune = book("Dune", &["Frank Herbert"], 1965);
+        let keys = citation_keys(&vec![dune; 28]);
+        assert_eq!(keys[26], "herbert1965dunez");
+        assert_eq!(keys[27], "herbert1965dune-1");
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 28);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{export::text, model::book::Book};
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use image::{imageops, imageops::FilterType, ImageFormat, Rgba, RgbaImage};
+use std::{io, path::Path};
+
+const REGULAR: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+const OBLIQUE: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Oblique.ttf");
+
+/// The size chat apps and social sites use for link previews.
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 630;
+const MARGIN: u32 = 60;
+const COVER_WIDTH: u32 = 340;
+const COVER_HEIGHT: u32 = HEIGHT - 2 * MARGIN;
+const TEXT_X: f32 = (MARGIN * 2 + COVER_WIDTH) as f32;
+const TEXT_WIDTH: f32 = WIDTH as f32 - TEXT_X - MARGIN as f32;
+const EXCERPT_LENGTH: usize = 220;
+
+const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const DIM: Rgba<u8> = Rgba([255, 255, 255, 170]);
+const STAR: Rgba<u8> = Rgba([255, 196, 61, 255]);
+const EMPTY_STAR: Rgba<u8> = Rgba([255, 255, 255, 70]);
+/// Background when there is no cover to take the colour from.
+const DEFAULT_TINT: [u8; 3] = [52, 61, 110];
+
+struct Fonts<'a> {
+    regular: FontRef<'a>,
+    bold: FontRef<'a>,
+    oblique: FontRef<'a>,
+}
+
+impl Fonts<'static> {
+    fn load() -> io::Result<Self> {
+        let load = |data| FontRef::try_from_slice(data).map_err(io::Error::other);
+        Ok(Self {
+            regular: load(REGULAR)?,
+            bold: load(BOLD)?,
+            oblique: load(OBLIQUE)?,
+        })
+    }
+}
+
+/// Writes a PNG card with the cover, title, authors, rating, last finish
+/// date and an excerpt of the book's first note or highlight. The
+/// background is tinted with the colour of the cover.
+pub fn write(book: &Book, path: &Path) -> io::Result<()> {
+    let fonts = Fonts::load()?;
+    let cover = book
+        .cover_path
+        .as_ref()
+        .filter(|p| p.is_file())
+        .and_then(|p| image::open(p).ok());
+    let tint = cover.as_ref().map_or(DEFAULT_TINT, |cover| {
+        let average = cover.resize_exact(1, 1, FilterType::Triangle).to_rgb8();
+        average.get_pixel(0, 0).0
+    });
+    let mut card = background(tint);
+
+    match &cover {
+        Some(cover) => {
+            let cover = cover
+                .resize(COVER_WIDTH, COVER_HEIGHT, FilterType::CatmullRom)
+                .to_rgba8();
+            let x = MARGIN + (COVER_WIDTH - cover.width()) / 2;
+            let y = MARGIN + (COVER_HEIGHT - cover.height()) / 2;
+            fill_rect(
+                &mut card,
+                x + 10,
+                y + 10,
+                cover.width(),
+                cover.height(),
+                Rgba([0, 0, 0, 90]),
+            );
+            imageops::overlay(&mut card, &cover, x as i64, y as i64);
+        }
+        None => {
+            let (width, height) = (COVER_WIDTH * 5 / 6, COVER_HEIGHT * 5 / 6);
+            let x = MARGIN + (COVER_WIDTH - width) / 2;
+            let y = MARGIN + (COVER_HEIGHT - height) / 2;
+            fill_rect(&mut card, x, y, width, height, Rgba([255, 255, 255, 40]));
+            let initial = book.title.chars().next().unwrap_or(' ').to_string();
+            let size = 160.0;
+            let initial_width = text_width(&fonts.bold, size, &initial);
+            draw_text(
+                &mut card,
+                &fonts.bold,
+                size,
+                x as f32 + (width as f32 - initial_width) / 2.0,
+                (y + height / 2) as f32 + size * 0.36,
+                &initial,
+                DIM,
+            );
+        }
+    }
+
+    let mut y = MARGIN as f32 + 50.0;
+    for line in text::wrap(&book.title, TEXT_WIDTH, 3, |t| {
+        text_width(&fonts.bold, 52.0, t)
+    }) {
+        draw_text(&mut card, &fonts.bold, 52.0, TEXT_X, y, &line, TEXT);
+        y += 62.0;
+    }
+    if !book.authors.is_empty() {
+        let authors = text::truncate(&book.authors.join(", "), TEXT_WIDTH, |t| {
+            text_width(&fonts.regular, 30.0, t)
+        });
+        draw_text(
+            &mut card,
+            &fonts.regular,
+            30.0,
+            TEXT_X,
+            y + 4.0,
+            &authors,
+            DIM,
+        );
+        y += 44.0;
+    }
+
+    y += 30.0;
+    if book.rating > 0 {
+        let mut x = TEXT_X;
+        for n in 0..5 {
+            let color = if n < book.rating { STAR } else { EMPTY_STAR };
+            x += draw_text(&mut card, &fonts.regular, 44.0, x, y + 10.0, "★", color) + 6.0;
+        }
+        y += 56.0;
+    }
+    if let Some(date) = book.finished_at.iter().max() {
+        let finished = format!("Finished {}", date.format("%-d %B %Y"));
+        draw_text(&mut card, &fonts.regular, 26.0, TEXT_X, y, &finished, DIM);
+        y += 40.0;
+    }
+
+    if let Some(excerpt) = excerpt(book) {
+        y += 24.0;
+        let lines = ((HEIGHT - MARGIN) as f32 - y) as usize / 36 + 1;
+        for line in text::wrap(&excerpt, TEXT_WIDTH, lines.min(5), |t| {
+            text_width(&fonts.oblique, 24.0, t)
+        }) {
+            draw_text(&mut card, &fonts.oblique, 24.0, TEXT_X, y, &line, TEXT);
+            y += 36.0;
+        }
+    }
+
+    card.save_with_format(path, ImageFormat::Png)
+        .map_err(io::Error::other)
+}
+
+/// A diagonal gradient from a darkened tint to an even darker one, so white
+/// text stays readable on light covers.
+fn background(tint: [u8; 3]) -> RgbaImage {
+    let shade = |channel: u8, amount: f32| (channel as f32 * amount) as u8;
+    RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let t = (x + y) as f32 / (WIDTH + HEIGHT) as f32;
+        let amount = 0.55 - 0.35 * t;
+        Rgba([
+            shade(tint[0], amount),
+            shade(tint[1], amount),
+            shade(tint[2], amount),
+            255,
+        ])
+    })
+}
+
+/// The reader's own note on the first highlight that has one, otherwise the
+/// first highlighted passage in quotes.
+fn excerpt(book: &Book) -> Option<String> {
+    let text = match book.highlights.iter().find_map(|h| h.note.as_ref()) {
+        Some(note) => note.clone(),
+        None => format!("“{}”", book.highlights.first()?.text.trim()),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= EXCERPT_LENGTH {
+        return Some(text);
+    }
+    let cut = text.chars().take(EXCERPT_LENGTH).collect::<String>();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    Some(format!("{cut}…"))
+}
+
+fn fill_rect(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            blend(image, px, py, color, 1.0);
+        }
+    }
+}
+
+fn blend(image: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>, coverage: f32) {
+    let alpha = coverage.clamp(0.0, 1.0) * color[3] as f32 / 255.0;
+    let pixel = image.get_pixel_mut(x, y);
+    for channel in 0..3 {
+        let mixed = pixel[channel] as f32 * (1.0 - alpha) + color[channel] as f32 * alpha;
+        pixel[channel] = mixed.round() as u8;
+    }
+}
+
+/// Draws a line of text with its baseline at y and returns its width.
+fn draw_text(
+    image: &mut RgbaImage,
+    font: &FontRef,
+    size: f32,
+    x: f32,
+    y: f32,
+    text: &str,
+    color: Rgba<u8>,
+) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut caret = x;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(size, point(caret, y));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i32 + gx as i32;
+            let py = bounds.min.y as i32 + gy as i32;
+            if px >= 0 && py >= 0 && (px as u32) < image.width() && (py as u32) < image.height() {
+                blend(image, px as u32, py as u32, color, coverage);
+            }
+        });
+    }
+    caret - x
+}
+
+fn text_width(font: &FontRef, size: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
@@ -0,0 +1,649 @@
+use crate::{
+    export::{site::status, text},
+    model::book::{reading_status::ReadingStatus, Book},
+};
+use ab_glyph::{Font, FontRef};
+use chrono::Local;
+use clap::ValueEnum;
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType};
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Document, Object, ObjectId, Stream, StringFormat,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// A4 in points.
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const FOOTER_Y: f32 = 30.0;
+const COVER_WIDTH: f32 = 40.0;
+const COVER_HEIGHT: f32 = 60.0;
+const ENTRY_HEIGHT: f32 = 70.0;
+const TEXT_X: f32 = MARGIN + COVER_WIDTH + 14.0;
+const SECTION_HEIGHT: f32 = 34.0;
+const INDEX_LINE_HEIGHT: f32 = 15.0;
+/// Thumbnails are scaled to three pixels per point, enough for print.
+const THUMBNAIL_WIDTH: u32 = 120;
+const THUMBNAIL_HEIGHT: u32 = 180;
+
+pub const REGULAR: &str = "F1";
+pub const BOLD: &str = "F2";
+const REGULAR_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CatalogGrouping {
+    /// A section per first author, by family name.
+    Author,
+    /// A section per reading status.
+    Shelf,
+}
+
+#[derive(Default)]
+pub struct CatalogReport {
+    pub pages: usize,
+    pub books: usize,
+    /// Covers that could not be read, with the reason.
+    pub failed_covers: Vec<(PathBuf, String)>,
+}
+
+/// Writes a printable PDF catalog: every book with its cover, title,
+/// authors, year, rating and status in sections, followed by a title index
+/// pointing to the pages.
+pub fn write(books: &[Book], grouping: CatalogGrouping, path: &Path) -> io::Result<CatalogReport> {
+    let mut report = CatalogReport {
+        books: books.len(),
+        ..CatalogReport::default()
+    };
+    let mut document = Document::with_version("1.5");
+    let mut pages = Pages::default();
+
+    pages.new_page();
+    pages.text(BOLD, 20.0, MARGIN, pages.y - 20.0, "Library catalog");
+    pages.gray(0.4);
+    let subtitle = format!(
+        "{} items, grouped by {}, printed {}",
+        books.len(),
+        match grouping {
+            CatalogGrouping::Author => "author",
+            CatalogGrouping::Shelf => "shelf",
+        },
+        Local::now().date_naive()
+    );
+    pages.text(REGULAR, 10.0, MARGIN, pages.y - 36.0, &subtitle);
+    pages.gray(0.0);
+    pages.y -= 56.0;
+
+    let mut images = vec![];
+    let mut index = vec![];
+    for (section, books) in sections(books, grouping) {
+        pages.section(&section, false);
+        for book in books {
+            if pages.y - ENTRY_HEIGHT < MARGIN {
+                pages.new_page();
+                pages.section(&section, true);
+            }
+            let image = match cover(&mut document, book) {
+                Ok(image) => image,
+                Err(e) => {
+                    if let Some(path) = &book.cover_path {
+                        report.failed_covers.push((path.clone(), e));
+                    }
+                    None
+                }
+            };
+            pages.entry(book, image.as_ref());
+            images.extend(image);
+            index.push((book, pages.number()));
+        }
+    }
+
+    pages.new_page();
+    pages.text(BOLD, 16.0, MARGIN, pages.y - 16.0, "Title index");
+    pages.y -= 34.0;
+    index.sort_by_key(|(book, _)| book.title_normalized());
+    for (book, page) in index {
+        if pages.y - INDEX_LINE_HEIGHT < MARGIN {
+            pages.new_page();
+        }
+        pages.index_line(&book.title, page);
+    }
+
+    report.pages = pages.pages.len();
+    save(document, pages, &images, path)?;
+    Ok(report)
+}
+
+/// Books in print order: sections sorted by name, or in shelf order, with
+/// their books sorted by title.
+fn sections(books: &[Book], grouping: CatalogGrouping) -> Vec<(String, Vec<&Book>)> {
+    let mut sections = BTreeMap::<(String, String), Vec<&Book>>::new();
+    for book in books {
+        let key = match grouping {
+            CatalogGrouping::Author => match book.author_names().first() {
+                Some(name) => (name.family.to_lowercase(), name.inverted()),
+                None => ("~".to_string(), "Unknown author".to_string()),
+            },
+            CatalogGrouping::Shelf => {
+                let order = match book.reading_status {
+                    ReadingStatus::Reading => "0",
+                    ReadingStatus::ToRead => "1",
+                    ReadingStatus::Read => "2",
+                };
+                (order.to_string(), status(book).to_string())
+            }
+        };
+        sections.entry(key).or_default().push(book);
+    }
+    sections
+        .into_iter()
+        .map(|((_, heading), mut books)| {
+            books.sort_by_key(|b| b.title_normalized());
+            (heading, books)
+        })
+        .collect()
+}
+
+/// An embedded cover image.
+struct Cover {
+    name: String,
+    id: ObjectId,
+    width: f32,
+    height: f32,
+}
+
+/// Adds the cover as a JPEG image, or returns None when the book has no cover.
+fn cover(document: &mut Document, book: &Book) -> Result<Option<Cover>, String> {
+    let Some(source) = book.cover_path.as_ref().filter(|p| p.is_file()) else {
+        return Ok(None);
+    };
+    let image = image::open(source)
+        .map_err(|e| e.to_string())?
+        .resize(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT, FilterType::Triangle)
+        .to_rgb8();
+    let mut jpeg = vec![];
+    JpegEncoder::new_with_quality(&mut jpeg, 85)
+        .encode_image(&image)
+        .map_err(|e| e.to_string())?;
+    let stream = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => image.width() as i64,
+            "Height" => image.height() as i64,
+            "ColorSpace" => "DeviceRGB",
+            "BitsPerComponent" => 8,
+            "Filter" => "DCTDecode",
+        },
+        jpeg,
+    )
+    .with_compression(false);
+    let id = document.add_object(stream);
+    Ok(Some(Cover {
+        name: format!("Im{}", id.0),
+        id,
+        width: image.width() as f32,
+        height: image.height() as f32,
+    }))
+}
+
+fn save(document: Document, pages: Pages, images: &[Cover], path: &Path) -> io::Result<()> {
+    let mut x_objects = lopdf::Dictionary::new();
+    for image in images {
+        x_objects.set(image.name.as_bytes(), image.id);
+    }
+    let count = pages.pages.len();
+    let pages = pages
+        .pages
+        .into_iter()
+        .enumerate()
+        .map(|(number, mut operations)| {
+            let footer = format!("Page {} of {count}", number + 1);
+            let width = text_width(&footer, REGULAR, 9.0);
+            operations.push(Operation::new("g", vec![0.4.into()]));
+            push_text(
+                &mut operations,
+                REGULAR,
+                9.0,
+                (PAGE_WIDTH - width) / 2.0,
+                FOOTER_Y,
+                &footer,
+            );
+            operations
+        })
+        .collect();
+    save_pages(document, pages, x_objects, path)
+}
+
+/// Writes A4 pages that draw text with `REGULAR` and `BOLD` and the given images.
+pub fn save_pages(
+    mut document: Document,
+    pages: Vec<Vec<Operation>>,
+    x_objects: lopdf::Dictionary,
+    path: &Path,
+) -> io::Result<()> {
+    let regular = embed_font(&mut document, REGULAR, "DejaVuSans", &pages)?;
+    let bold = embed_font(&mut document, BOLD, "DejaVuSans-Bold", &pages)?;
+    let resources = document.add_object(dictionary! {
+        "Font" => dictionary! {
+            REGULAR => regular,
+            BOLD => bold,
+        },
+        "XObject" => x_objects,
+    });
+
+    let pages_id = document.new_object_id();
+    let count = pages.len();
+    let mut kids = vec![];
+    for operations in pages {
+        let content = Content { operations }.encode().map_err(io::Error::other)?;
+        let content = document.add_object(Stream::new(dictionary! {}, content));
+        kids.push(Object::Reference(document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content,
+            "Resources" => resources,
+        })));
+    }
+    document.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count as i64,
+            "MediaBox" => vec![0.into(), 0.into(), PAGE_WIDTH.into(), PAGE_HEIGHT.into()],
+        }),
+    );
+    let catalog = document.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    document.trailer.set("Root", catalog);
+    document.compress();
+    document.save(path)?;
+    Ok(())
+}
+
+/// The content of every page and where the next line goes on the last one.
+#[derive(Default)]
+struct Pages {
+    pages: Vec<Vec<Operation>>,
+    y: f32,
+}
+
+impl Pages {
+    fn new_page(&mut self) {
+        self.pages.push(vec![]);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn number(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn operations(&mut self) -> &mut Vec<Operation> {
+        self.pages.last_mut().expect("a page was started")
+    }
+
+    fn text(&mut self, font: &str, size: f32, x: f32, y: f32, text: &str) {
+        push_text(self.operations(), font, size, x, y, text);
+    }
+
+    fn gray(&mut self, level: f32) {
+        self.operations()
+            .push(Operation::new("g", vec![level.into()]));
+    }
+
+    /// A heading with a rule below it, on a new page when no entry would fit
+    /// below it.
+    fn section(&mut self, heading: &str, continued: bool) {
+        if self.y - SECTION_HEIGHT - ENTRY_HEIGHT < MARGIN {
+            self.new_page();
+        }
+        let heading = if continued {
+            format!("{heading} (continued)")
+        } else {
+            heading.to_string()
+        };
+        let heading = truncate(&heading, BOLD, 13.0, PAGE_WIDTH - 2.0 * MARGIN);
+        let y = self.y - 16.0;
+        self.text(BOLD, 13.0, MARGIN, y, &heading);
+        let rule = y - 6.0;
+        self.operations().extend([
+            Operation::new("G", vec![0.6.into()]),
+            Operation::new("w", vec![0.5.into()]),
+            Operation::new("m", vec![MARGIN.into(), rule.into()]),
+            Operation::new("l", vec![(PAGE_WIDTH - MARGIN).into(), rule.into()]),
+            Operation::new("S", vec![]),
+        ]);
+        self.y -= SECTION_HEIGHT;
+    }
+
+    fn entry(&mut self, book: &Book, image: Option<&Cover>) {
+        let top = self.y;
+        let cover_y = top - COVER_HEIGHT;
+        match image {
+            // Fitted into the cover box, centred horizontally and at the top.
+            Some(image) => {
+                let scale = (COVER_WIDTH / image.width).min(COVER_HEIGHT / image.height);
+                let (width, height) = (image.width * scale, image.height * scale);
+                self.operations().extend([
+                    Operation::new("q", vec![]),
+                    Operation::new(
+                        "cm",
+                        vec![
+                            width.into(),
+                            0.into(),
+                            0.into(),
+                            height.into(),
+                            (MARGIN + (COVER_WIDTH - width) / 2.0).into(),
+                            (top - height).into(),
+                        ],
+                    ),
+                    Operation::new("Do", vec![Object::Name(image.name.clone().into_bytes())]),
+                    Operation::new("Q", vec![]),
+                ]);
+            }
+            None => self.operations().extend([
+                Operation::new("G", vec![0.75.into()]),
+                Operation::new("w", vec![0.5.into()]),
+                Operation::new(
+                    "re",
+                    vec![
+                        MARGIN.into(),
+                        cover_y.into(),
+                        COVER_WIDTH.into(),
+                        COVER_HEIGHT.into(),
+                    ],
+                ),
+                Operation::new("S", vec![]),
+            ]),
+        }
+
+        let width = PAGE_WIDTH - MARGIN - TEXT_X;
+        let title = truncate(&book.title, BOLD, 11.0, width);
+        self.text(BOLD, 11.0, TEXT_X, top - 12.0, &title);
+        if !book.authors.is_empty() {
+            let authors = truncate(&book.authors.join(", "), REGULAR, 10.0, width);
+            self.text(REGULAR, 10.0, TEXT_X, top - 27.0, &authors);
+        }
+        let mut details = vec![];
+        if book.year > 0 {
+            details.push(book.year.to_string());
+        }
+        details.push(match book.finished_at.last() {
+            Some(date) => format!("{}, finished {date}", status(book)),
+            None => status(book).to_string(),
+        });
+        if book.rating > 0 {
+            details.push(format!("rated {}/5", book.rating));
+        }
+        if let Some(isbn) = book.identifiers.isbn() {
+            details.push(format!("ISBN {isbn}"));
+        }
+        if let Some(location) = &book.location {
+            details.push(location.clone());
+        }
+        let details = truncate(&details.join("  ·  "), REGULAR, 9.0, width);
+        self.gray(0.4);
+        self.text(REGULAR, 9.0, TEXT_X, top - 42.0, &details);
+        self.gray(0.0);
+        self.y -= ENTRY_HEIGHT;
+    }
+
+    /// "Title ........ 12"
+    fn index_line(&mut self, title: &str, page: usize) {
+        let y = self.y - 10.0;
+        let number = page.to_string();
+        let number_width = text_width(&number, REGULAR, 10.0);
+        let title = truncate(title, REGULAR, 10.0, PAGE_WIDTH - 2.0 * MARGIN - 60.0);
+        let title_width = text_width(&title, REGULAR, 10.0);
+        let dot_width = text_width(".", REGULAR, 10.0);
+        let gap = PAGE_WIDTH - 2.0 * MARGIN - title_width - number_width - 2.0 * dot_width;
+        let dots = ".".repeat((gap / dot_width).max(0.0) as usize);
+        self.text(REGULAR, 10.0, MARGIN, y, &title);
+        self.gray(0.6);
+        self.text(REGULAR, 10.0, MARGIN + title_width + dot_width, y, &dots);
+        self.gray(0.0);
+        self.text(
+            REGULAR,
+            10.0,
+            PAGE_WIDTH - MARGIN - number_width,
+            y,
+            &number,
+        );
+        self.y -= INDEX_LINE_HEIGHT;
+    }
+}
+
+pub fn push_text(
+    operations: &mut Vec<Operation>,
+    font: &str,
+    size: f32,
+    x: f32,
+    y: f32,
+    text: &str,
+) {
+    operations.extend([
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec![font.into(), size.into()]),
+        Operation::new("Td", vec![x.into(), y.into()]),
+        Operation::new(
+            "Tj",
+            vec![Object::String(
+                glyph_ids(text, font),
+                StringFormat::Hexadecimal,
+            )],
+        ),
+        Operation::new("ET", vec![]),
+    ]);
+}
+
+/// Shortens the text with an ellipsis to fit the width.
+pub fn truncate(text: &str, font: &str, size: f32, width: f32) -> String {
+    text::truncate(text, width, |t| text_width(t, font, size))
+}
+
+/// Width in points, from the advance widths of the embedded font.
+pub fn text_width(text: &str, font: &str, size: f32) -> f32 {
+    let face = load_font(font);
+    let units = text
+        .chars()
+        .map(|c| face.h_advance_unscaled(face.glyph_id(c)))
+        .sum::<f32>();
+    units * size / face.units_per_em().unwrap_or(1000.0)
+}
+
+/// The bundled DejaVu Sans behind a font resource name.
+fn load_font(font: &str) -> FontRef<'static> {
+    let data = if font == BOLD {
+        BOLD_FONT
+    } else {
+        REGULAR_FONT
+    };
+    FontRef::try_from_slice(data).expect("the bundled fonts are valid")
+}
+
+/// The text as the two-byte glyph ids the embedded fonts are addressed with.
+fn glyph_ids(text: &str, font: &str) -> Vec<u8> {
+    let face = load_font(font);
+    text.chars()
+        .flat_map(|c| face.glyph_id(c).0.to_be_bytes())
+        .collect()
+}
+
+/// Embeds a bundled font as a TrueType CID font. Widths and the map back to
+/// Unicode, which viewers use to copy and search text, cover the glyphs the
+/// pages use.
+fn embed_font(
+    document: &mut Document,
+    font: &str,
+    name: &str,
+    pages: &[Vec<Operation>],
+) -> io::Result<ObjectId> {
+    let data = if font == BOLD {
+        BOLD_FONT
+    } else {
+        REGULAR_FONT
+    };
+    let face = load_font(font);
+    let scale = 1000.0 / face.units_per_em().unwrap_or(1000.0);
+    let glyphs = used_glyphs(pages, font);
+    let characters = face
+        .codepoint_ids()
+        .map(|(id, c)| (id.0, c))
+        .collect::<HashMap<_, _>>();
+
+    let widths = glyphs
+        .iter()
+        .flat_map(|id| {
+            let width = face.h_advance_unscaled(ab_glyph::GlyphId(*id)) * scale;
+            [
+                Object::Integer(*id as i64),
+                Object::Array(vec![Object::Integer(width.round() as i64)]),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let mut to_unicode = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let mapped = glyphs
+        .iter()
+        .filter_map(|id| Some((*id, *characters.get(id)?)))
+        .collect::<Vec<_>>();
+    // A bfchar block holds at most 100 entries.
+    for chunk in mapped.chunks(100) {
+        to_unicode.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (id, c) in chunk {
+            let utf16 = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|unit| format!("{unit:04X}"))
+                .collect::<String>();
+            to_unicode.push_str(&format!("<{id:04X}> <{utf16}>\n"));
+        }
+        to_unicode.push_str("endbfchar\n");
+    }
+    to_unicode.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+
+    let [x_min, y_min, x_max, y_max] =
+        bounding_box(data).ok_or_else(|| io::Error::other(format!("{name} has no head table")))?;
+    let font_file = document.add_object(Stream::new(
+        dictionary! { "Length1" => data.len() as i64 },
+        data.to_vec(),
+    ));
+    let descriptor = document.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => name,
+        // Nonsymbolic: the glyphs are those of the Latin character set and more.
+        "Flags" => 32,
+        "FontBBox" => [x_min, y_min, x_max, y_max]
+            .into_iter()
+            .map(|v| Object::Integer((v as f32 * scale).round() as i64))
+            .collect::<Vec<_>>(),
+        "ItalicAngle" => 0,
+        "Ascent" => (face.ascent_unscaled() * scale).round() as i64,
+        "Descent" => (face.descent_unscaled() * scale).round() as i64,
+        "CapHeight" => (face.ascent_unscaled() * scale).round() as i64,
+        "StemV" => if font == BOLD { 120 } else { 80 },
+        "FontFile2" => font_file,
+    });
+    let cid_font = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "CIDFontType2",
+        "BaseFont" => name,
+        "CIDSystemInfo" => dictionary! {
+            "Registry" => Object::string_literal("Adobe"),
+            "Ordering" => Object::string_literal("Identity"),
+            "Supplement" => 0,
+        },
+        "FontDescriptor" => descriptor,
+        "W" => widths,
+        "CIDToGIDMap" => "Identity",
+    });
+    let to_unicode = document.add_object(Stream::new(dictionary! {}, to_unicode.into_bytes()));
+    Ok(document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type0",
+        "BaseFont" => name,
+        "Encoding" => "Identity-H",
+        "DescendantFonts" => vec![cid_font.into()],
+        "ToUnicode" => to_unicode,
+    }))
+}
+
+/// The glyph ids shown in `font` on any of the pages.
+fn used_glyphs(pages: &[Vec<Operation>], font: &str) -> BTreeSet<u16> {
+    let mut glyphs = BTreeSet::new();
+    for operations in pages {
+        let mut current = None;
+        for operation in operations {
+            match (operation.operator.as_str(), operation.operands.first()) {
+                ("Tf", Some(Object::Name(name))) => current = Some(name.clone()),
+                ("Tj", Some(Object::String(bytes, _)))
+                    if current.as_deref() == Some(font.as_bytes()) =>
+                {
+                    glyphs.extend(
+                        bytes
+                            .chunks_exact(2)
+                            .map(|pair| u16::from_be_bytes([pair[0], pair[1]])),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    glyphs
+}
+
+/// xMin, yMin, xMax and yMax of all glyphs, from the font's `head` table.
+fn bounding_box(data: &[u8]) -> Option<[i16; 4]> {
+    let read_u16 = |at: usize| Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let read_u32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let tables = read_u16(4)? as usize;
+    let head = (0..tables)
+        .map(|i| 12 + i * 16)
+        .find(|record| data.get(*record..record + 4) == Some(b"head"))?;
+    let offset = read_u32(head + 8)? as usize;
+    Some([36, 38, 40, 42].map(|field| read_u16(offset + field).unwrap_or_default() as i16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embeds_fonts_that_map_back_to_unicode() {
+        let text = "Čapek — Ἰλιάς, Война и мир…";
+        let mut operations = vec![];
+        push_text(&mut operations, REGULAR, 12.0, MARGIN, 700.0, text);
+        push_text(&mut operations, BOLD, 12.0, MARGIN, 680.0, "Straße");
+        let path = std::env::temp_dir().join(format!("bookie-{}.pdf", uuid::Uuid::new_v4()));
+        save_pages(
+            Document::with_version("1.5"),
+            vec![operations],
+            lopdf::Dictionary::new(),
+            &path,
+        )
+        .unwrap();
+        let document = Document::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let extracted = document.extract_text(&[1]).unwrap();
+        assert!(extracted.contains(text), "{extracted}");
+        assert!(extracted.contains("Straße"), "{extracted}");
+    }
+
+    #[test]
+    fn measures_characters_outside_latin_1() {
+        assert!(text_width("Война", REGULAR, 10.0) > text_width("Во", REGULAR, 10.0));
+        assert!(text_width("Dune", BOLD, 10.0) > text_width("Dune", REGULAR, 10.0));
+    }
+}
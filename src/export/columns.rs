@@ -0,0 +1,207 @@
+use crate::{export::csv_error, model::book::Book};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+/// Joins list columns such as authors, tags and finish dates in CSV cells.
+pub const LIST_SEPARATOR: &str = "; ";
+
+/// A field of the flat export. The names are the CSV headers and JSON keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum Column {
+    /// Stable id of the entry in bookie.
+    Id,
+    /// Book, Comic, Magazine or Paper.
+    Type,
+    Title,
+    /// All authors in order.
+    Authors,
+    /// Calibre's sort name for all the authors, joined with " & ", such as
+    /// "Pratchett, Terry & Gaiman, Neil".
+    AuthorSort,
+    /// Publication year, empty when unknown.
+    Year,
+    /// Number of pages, empty when unknown.
+    Pages,
+    /// Comic volume.
+    Volume,
+    /// Comic issue.
+    Issue,
+    /// Magazine issue date.
+    IssueDate,
+    /// DOI of a paper.
+    Doi,
+    /// Journal or conference of a paper.
+    Venue,
+    #[value(name = "isbn_10")]
+    Isbn10,
+    #[value(name = "isbn_13")]
+    Isbn13,
+    Asin,
+    OpenlibraryId,
+    GoodreadsId,
+    Language,
+    Series,
+    SeriesIndex,
+    Tags,
+    Publisher,
+    /// Path of the linked ebook.
+    File,
+    /// Percentage read in the ebook or on an e-reader.
+    Progress,
+    /// Time spent reading as tracked by an e-reader.
+    ReadingSeconds,
+    /// Number of imported highlights.
+    Highlights,
+    /// Where the physical copy is kept.
+    Location,
+    /// Whether the book is left out of the generated site.
+    Private,
+    /// ToRead, Reading or Read.
+    Status,
+    /// Every date the book was started, oldest first.
+    StartedAt,
+    /// Every date the book was finished, oldest first.
+    FinishedAt,
+    /// 0 to 5.
+    Rating,
+    CoverPath,
+}
+
+impl Column {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Type => "type",
+            Column::Title => "title",
+            Column::Authors => "authors",
+            Column::AuthorSort => "author_sort",
+            Column::Year => "year",
+            Column::Pages => "pages",
+            Column::Volume => "volume",
+            Column::Issue => "issue",
+            Column::IssueDate => "issue_date",
+            Column::Doi => "doi",
+            Column::Venue => "venue",
+            Column::Isbn10 => "isbn_10",
+            Column::Isbn13 => "isbn_13",
+            Column::Asin => "asin",
+            Column::OpenlibraryId => "openlibrary_id",
+            Column::GoodreadsId => "goodreads_id",
+            Column::Language => "language",
+            Column::Series => "series",
+            Column::SeriesIndex => "series_index",
+            Column::Tags => "tags",
+            Column::Publisher => "publisher",
+            Column::File => "file",
+            Column::Progress => "progress",
+            Column::ReadingSeconds => "reading_seconds",
+            Column::Highlights => "highlights",
+            Column::Location => "location",
+            Column::Private => "private",
+            Column::Status => "status",
+            Column::StartedAt => "started_at",
+            Column::FinishedAt => "finished_at",
+            Column::Rating => "rating",
+            Column::CoverPath => "cover_path",
+        }
+    }
+
+    /// The field as JSON: lists stay arrays and unknown values are null.
+    pub fn value(&self, book: &Book) -> Value {
+        let ids = &book.identifiers;
+        match self {
+            Column::Id => json!(book.id.to_string()),
+            Column::Type => json!(format!("{:?}", book.item_type)),
+            Column::Title => json!(book.title),
+            Column::Authors => json!(book.authors),
+            Column::AuthorSort => json!(book.author_sort),
+            Column::Year => non_zero(book.year),
+            Column::Pages => non_zero(book.pages),
+            Column::Volume => json!(book.volume),
+            Column::Issue => json!(book.issue),
+            Column::IssueDate => json!(book.issue_date.map(|d| d.to_string())),
+            Column::Doi => json!(book.doi),
+            Column::Venue => json!(book.venue),
+            Column::Isbn10 => json!(ids.isbn_10),
+            Column::Isbn13 => json!(ids.isbn_13),
+            Column::Asin => json!(ids.asin),
+            Column::OpenlibraryId => json!(ids.openlibrary_id),
+            Column::GoodreadsId => json!(ids.goodreads_id),
+            Column::Language => json!(book.language),
+            Column::Series => json!(book.series),
+            Column::SeriesIndex => json!(book.series_index),
+            Column::Tags => json!(book.tags),
+            Column::Publisher => json!(book.publisher),
+            Column::File => json!(book.file.as_ref().map(|f| f.path.display().to_string())),
+            Column::Progress => json!(book.position.as_ref().map(|p| p.progress)),
+            Column::ReadingSeconds => json!(book.reading_seconds),
+            Column::Highlights => json!(book.highlights.len()),
+            Column::Location => json!(book.location),
+            Column::Private => json!(book.private),
+            Column::Status => json!(format!("{:?}", book.reading_status)),
+            Column::StartedAt => json!(book
+                .started_at
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()),
+            Column::FinishedAt => json!(book
+                .finished_at
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()),
+            Column::Rating => json!(book.rating),
+            Column::CoverPath => json!(book.cover_path.as_ref().map(|p| p.display().to_string())),
+        }
+    }
+
+    /// The field as a CSV cell.
+    pub fn cell(&self, book: &Book) -> String {
+        match self.value(book) {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            Value::Array(values) => values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string).unwrap_or(v.to_string()))
+                .collect::<Vec<_>>()
+                .join(LIST_SEPARATOR),
+            other => other.to_string(),
+        }
+    }
+}
+
+pub fn write_csv(books: &[Book], columns: &[Column], output: impl Write) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(output);
+    writer
+        .write_record(columns.iter().map(Column::name))
+        .map_err(csv_error)?;
+    for book in books {
+        writer
+            .write_record(columns.iter().map(|c| c.cell(book)))
+            .map_err(csv_error)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// One JSON object per line, with the columns as keys in the given order.
+pub fn write_jsonl(books: &[Book], columns: &[Column], mut output: impl Write) -> io::Result<()> {
+    for book in books {
+        let fields = columns
+            .iter()
+            .map(|c| format!("{}:{}", json!(c.name()), c.value(book)))
+            .collect::<Vec<_>>();
+        writeln!(output, "{{{}}}", fields.join(","))?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn non_zero(value: u16) -> Value {
+    if value == 0 {
+        Value::Null
+    } else {
+        json!(value)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    export::csv_error,
+    model::book::{reading_status::ReadingStatus, Book},
+};
+use std::io::{self, Write};
+
+/// The columns of Goodreads' own export, which its importer reads back.
+const HEADER: &[&str] = &[
+    "Book Id",
+    "Title",
+    "Author",
+    "Additional Authors",
+    "ISBN",
+    "ISBN13",
+    "My Rating",
+    "Publisher",
+    "Number of Pages",
+    "Year Published",
+    "Date Read",
+    "Bookshelves",
+    "Exclusive Shelf",
+    "Read Count",
+];
+
+pub fn write(books: &[Book], output: impl Write) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(output);
+    writer.write_record(HEADER).map_err(csv_error)?;
+    for book in books {
+        let shelf = match book.reading_status {
+            ReadingStatus::ToRead => "to-read",
+            ReadingStatus::Reading => "currently-reading",
+            ReadingStatus::Read => "read",
+        };
+        let mut shelves = book.tags.clone();
+        shelves.push(shelf.to_string());
+        writer
+            .write_record([
+                book.identifiers.goodreads_id.clone().unwrap_or_default(),
+                book.title.clone(),
+                book.authors.first().cloned().unwrap_or_default(),
+                book.authors
+                    .iter()
+                    .skip(1)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                book.identifiers.isbn_10.clone().unwrap_or_default(),
+                book.identifiers.isbn_13.clone().unwrap_or_default(),
+                book.rating.to_string(),
+                book.publisher.clone().unwrap_or_default(),
+                number(book.pages),
+                number(book.year),
+                book.finished_at
+                    .last()
+                    .map(|d| d.format("%Y/%m/%d").to_string())
+                    .unwrap_or_default(),
+                shelves.join(", "),
+                shelf.to_string(),
+                book.finished_at.len().to_string(),
+            ])
+            .map_err(csv_error)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Unknown numbers are stored as 0 but left empty in the export.
+fn number(value: u16) -> String {
+    if value == 0 {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,107 @@
+use crate::model::book::Book;
+use chrono::{Days, NaiveDate, Utc};
+use std::io::{self, Write};
+
+/// Longest content line allowed by RFC 5545, in bytes.
+const LINE_LENGTH: usize = 75;
+
+/// An iCalendar file with an all-day event for every finished read, spanning
+/// from the day it was started when that is known. The UID of an event is
+/// made of the book id and the finish date, so importing a newer file into a
+/// calendar updates the events instead of adding them again.
+pub fn write(books: &[Book], mut output: impl Write) -> io::Result<()> {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//bookie//Reading history//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Reading".to_string(),
+    ];
+    for book in books {
+        for (started, finished) in book.reads() {
+            lines.extend(event(book, started, finished, &stamp));
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(output, "{}\r\n", fold(&line))?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn event(book: &Book, started: Option<NaiveDate>, finished: NaiveDate, stamp: &str) -> Vec<String> {
+    let mut summary = format!("Read {}", book.title);
+    if !book.authors.is_empty() {
+        summary.push_str(&format!(" by {}", book.authors.join(", ")));
+    }
+    let mut description = vec![];
+    if book.rating > 0 {
+        description.push(format!("Rating: {}/5", book.rating));
+    }
+    if book.pages > 0 {
+        description.push(format!("{} pages", book.pages));
+    }
+    if let Some(started) = started {
+        let days = (finished - started).num_days() + 1;
+        description.push(format!("Read in {days} days"));
+    }
+    let end = finished.checked_add_days(Days::new(1)).unwrap_or(finished);
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}-{}@bookie", book.id, finished.format("%Y%m%d")),
+        format!("DTSTAMP:{stamp}"),
+        format!(
+            "DTSTART;VALUE=DATE:{}",
+            started.unwrap_or(finished).format("%Y%m%d")
+        ),
+        format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+        format!("SUMMARY:{}", escape(&summary)),
+        "TRANSP:TRANSPARENT".to_string(),
+    ];
+    if !description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+    }
+    if !book.tags.is_empty() {
+        let tags = book.tags.iter().map(|t| escape(t)).collect::<Vec<_>>();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// Escapes the characters that separate values in text properties.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Breaks long lines into continuation lines starting with a space, without
+/// splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,269 @@
+use crate::{
+    export::{
+        catalog::{push_text, save_pages, text_width, truncate, BOLD, PAGE_HEIGHT, REGULAR},
+        site::escape,
+        text,
+    },
+    model::book::Book,
+};
+use lopdf::{content::Operation, Document};
+use rxing::qrcode::{common::ErrorCorrectionLevel, encoder::qrcode_encoder};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A sheet of 3 by 7 labels of 63.5 by 38.1 mm, like Avery L7160, in points.
+const COLUMNS: usize = 3;
+const ROWS: usize = 7;
+const LABEL_WIDTH: f32 = 180.0;
+const LABEL_HEIGHT: f32 = 108.0;
+const COLUMN_PITCH: f32 = 187.2;
+const LEFT: f32 = 20.3;
+const TOP: f32 = 43.0;
+const PADDING: f32 = 8.0;
+/// Side of the QR code, including its quiet zone.
+const QR_SIZE: f32 = 84.0;
+const QUIET_ZONE: usize = 4;
+const TEXT_WIDTH: f32 = LABEL_WIDTH - QR_SIZE - 3.0 * PADDING;
+const TITLE_SIZE: f32 = 9.0;
+const TITLE_LINES: usize = 4;
+const SMALL_SIZE: f32 = 7.0;
+
+/// The modules of a QR code, true for dark.
+struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Uppercase ids fit the smaller alphanumeric mode, so the modules are
+    /// larger and easier to scan.
+    fn new(book: &Book) -> io::Result<Self> {
+        let text = book.id.to_string().to_uppercase();
+        let code = qrcode_encoder::encode(&text, ErrorCorrectionLevel::M)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let matrix = code
+            .getMatrix()
+            .as_ref()
+            .ok_or_else(|| io::Error::other("QR encoder returned no matrix"))?;
+        let size = matrix.getWidth() as usize;
+        let modules = (0..size * size)
+            .map(|i| matrix.get((i % size) as u32, (i / size) as u32) == 1)
+            .collect();
+        Ok(Self { size, modules })
+    }
+
+    /// Runs of dark modules per row as (column, row, length), so a code is
+    /// drawn with few rectangles.
+    fn runs(&self) -> Vec<(usize, usize, usize)> {
+        let mut runs = vec![];
+        for y in 0..self.size {
+            let mut x = 0;
+            while x < self.size {
+                if !self.modules[y * self.size + x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < self.size && self.modules[y * self.size + x] {
+                    x += 1;
+                }
+                runs.push((start, y, x - start));
+            }
+        }
+        runs
+    }
+
+    fn module_size(&self) -> f32 {
+        QR_SIZE / (self.size + 2 * QUIET_ZONE) as f32
+    }
+}
+
+/// What goes on a label next to the QR code.
+struct Label {
+    qr: QrCode,
+    title: Vec<String>,
+    authors: String,
+    location: String,
+}
+
+impl Label {
+    fn new(book: &Book) -> io::Result<Self> {
+        Ok(Self {
+            qr: QrCode::new(book)?,
+            title: text::wrap(&book.title, TEXT_WIDTH, TITLE_LINES, |t| {
+                text_width(t, BOLD, TITLE_SIZE)
+            }),
+            authors: truncate(&book.authors.join(", "), REGULAR, SMALL_SIZE, TEXT_WIDTH),
+            location: truncate(
+                book.location.as_deref().unwrap_or_default(),
+                BOLD,
+                SMALL_SIZE,
+                TEXT_WIDTH,
+            ),
+        })
+    }
+}
+
+/// Top left corner of the n-th label on its sheet, measured from the top.
+fn position(n: usize) -> (f32, f32) {
+    let n = n % (COLUMNS * ROWS);
+    (
+        LEFT + (n % COLUMNS) as f32 * COLUMN_PITCH,
+        TOP + (n / COLUMNS) as f32 * LABEL_HEIGHT,
+    )
+}
+
+/// Writes the label sheets as a PDF, or as SVG files with one sheet each,
+/// numbered when there are several. Returns the files written.
+pub fn write(books: &[Book], path: &Path) -> io::Result<Vec<PathBuf>> {
+    let labels = books
+        .iter()
+        .map(Label::new)
+        .collect::<io::Result<Vec<_>>>()?;
+    let is_pdf = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        write_pdf(&labels, path)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let sheets = labels.chunks(COLUMNS * ROWS).collect::<Vec<_>>();
+    let mut written = vec![];
+    for (number, sheet) in sheets.iter().enumerate() {
+        let sheet_path = if sheets.len() == 1 {
+            path.to_path_buf()
+        } else {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            path.with_file_name(format!("{stem}-{}.svg", number + 1))
+        };
+        fs::write(&sheet_path, svg_sheet(sheet))?;
+        written.push(sheet_path);
+    }
+    Ok(written)
+}
+
+fn svg_sheet(labels: &[Label]) -> String {
+    let mut svg = vec![
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="210mm" height="297mm" viewBox="0 0 595 842">"#.to_string(),
+        r#"<style>text { font-family: Helvetica, Arial, sans-serif; fill: #000; } .bold { font-weight: bold; }</style>"#.to_string(),
+    ];
+    for (n, label) in labels.iter().enumerate() {
+        let (x, y) = position(n);
+        svg.push(format!(
+            r##"<rect x="{x}" y="{y}" width="{LABEL_WIDTH}" height="{LABEL_HEIGHT}" rx="6" fill="none" stroke="#ccc" stroke-width="0.5"/>"##
+        ));
+
+        let module = label.qr.module_size();
+        let origin_x = x + PADDING + QUIET_ZONE as f32 * module;
+        let origin_y = y + (LABEL_HEIGHT - QR_SIZE) / 2.0 + QUIET_ZONE as f32 * module;
+        let path = label
+            .qr
+            .runs()
+            .into_iter()
+            .map(|(column, row, length)| {
+                format!(
+                    "M{:.2} {:.2}h{:.2}v{:.2}h-{:.2}z",
+                    origin_x + column as f32 * module,
+                    origin_y + row as f32 * module,
+                    length as f32 * module,
+                    module,
+                    length as f32 * module
+                )
+            })
+            .collect::<String>();
+        svg.push(format!(r##"<path d="{path}" fill="#000"/>"##));
+
+        let text_x = x + QR_SIZE + 2.0 * PADDING;
+        let mut line_y = y + PADDING + TITLE_SIZE;
+        for line in &label.title {
+            svg.push(format!(
+                r#"<text x="{text_x}" y="{line_y}" font-size="{TITLE_SIZE}" class="bold">{}</text>"#,
+                escape(line)
+            ));
+            line_y += TITLE_SIZE * 1.2;
+        }
+        svg.push(format!(
+            r#"<text x="{text_x}" y="{}" font-size="{SMALL_SIZE}">{}</text>"#,
+            line_y + 2.0,
+            escape(&label.authors)
+        ));
+        svg.push(format!(
+            r#"<text x="{text_x}" y="{}" font-size="{SMALL_SIZE}" class="bold">{}</text>"#,
+            y + LABEL_HEIGHT - PADDING,
+            escape(&label.location)
+        ));
+    }
+    svg.push("</svg>\n".to_string());
+    svg.join("\n")
+}
+
+fn write_pdf(labels: &[Label], path: &Path) -> io::Result<()> {
+    let mut pages = vec![];
+    for sheet in labels.chunks(COLUMNS * ROWS) {
+        let mut operations = vec![];
+        for (n, label) in sheet.iter().enumerate() {
+            // PDF measures from the bottom of the page.
+            let (x, top) = position(n);
+            let y = PAGE_HEIGHT - top - LABEL_HEIGHT;
+            operations.extend([
+                Operation::new("G", vec![0.8.into()]),
+                Operation::new("w", vec![0.5.into()]),
+                Operation::new(
+                    "re",
+                    vec![x.into(), y.into(), LABEL_WIDTH.into(), LABEL_HEIGHT.into()],
+                ),
+                Operation::new("S", vec![]),
+                Operation::new("g", vec![0.into()]),
+            ]);
+
+            let module = label.qr.module_size();
+            let origin_x = x + PADDING + QUIET_ZONE as f32 * module;
+            let origin_top = y + (LABEL_HEIGHT + QR_SIZE) / 2.0 - QUIET_ZONE as f32 * module;
+            for (column, row, length) in label.qr.runs() {
+                operations.push(Operation::new(
+                    "re",
+                    vec![
+                        (origin_x + column as f32 * module).into(),
+                        (origin_top - (row + 1) as f32 * module).into(),
+                        (length as f32 * module).into(),
+                        module.into(),
+                    ],
+                ));
+            }
+            operations.push(Operation::new("f", vec![]));
+
+            let text_x = x + QR_SIZE + 2.0 * PADDING;
+            let mut line_y = y + LABEL_HEIGHT - PADDING - TITLE_SIZE;
+            for line in &label.title {
+                push_text(&mut operations, BOLD, TITLE_SIZE, text_x, line_y, line);
+                line_y -= TITLE_SIZE * 1.2;
+            }
+            push_text(
+                &mut operations,
+                REGULAR,
+                SMALL_SIZE,
+                text_x,
+                line_y - 2.0,
+                &label.authors,
+            );
+            push_text(
+                &mut operations,
+                BOLD,
+                SMALL_SIZE,
+                text_x,
+                y + PADDING,
+                &label.location,
+            );
+        }
+        pages.push(operations);
+    }
+    save_pages(
+        Document::with_version("1.5"),
+        pages,
+        lopdf::Dictionary::new(),
+        path,
+    )
+}
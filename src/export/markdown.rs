@@ -0,0 +1,300 @@
+use crate::model::book::{reading_status::ReadingStatus, Book};
+use chrono::Datelike;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::Path,
+};
+use uuid::Uuid;
+
+/// Everything below this line in a note is the user's and survives re-exports.
+pub const MARKER: &str = "<!-- bookie: notes below this line are kept on export -->";
+const BOOKS_DIR: &str = "books";
+const AUTHORS_DIR: &str = "authors";
+const YEARS_DIR: &str = "years";
+
+#[derive(Default)]
+pub struct MarkdownReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+/// Writes a note per book to `dir/books`, and index notes per author and per
+/// year of reading to `dir/authors` and `dir/years`. Book notes are found again
+/// by the `id` in their frontmatter, so renamed notes are updated in place.
+pub fn write(books: &[Book], dir: &Path) -> io::Result<MarkdownReport> {
+    let mut report = MarkdownReport::default();
+    for sub_dir in [BOOKS_DIR, AUTHORS_DIR, YEARS_DIR] {
+        fs::create_dir_all(dir.join(sub_dir))?;
+    }
+
+    let names = note_names(books, &existing_notes(&dir.join(BOOKS_DIR))?);
+    let mut authors: BTreeMap<&str, Vec<&Book>> = BTreeMap::new();
+    let mut years: BTreeMap<i32, Vec<&Book>> = BTreeMap::new();
+    for book in books {
+        for author in &book.authors {
+            authors.entry(author).or_default().push(book);
+        }
+        for year in finished_years(book) {
+            years.entry(year).or_default().push(book);
+        }
+        let path = dir.join(BOOKS_DIR).join(format!("{}.md", names[&book.id]));
+        write_note(&path, &book_note(book), &mut report)?;
+    }
+    for (author, books) in authors {
+        let path = dir
+            .join(AUTHORS_DIR)
+            .join(format!("{}.md", file_name(author)));
+        write_note(&path, &author_note(author, &books, &names), &mut report)?;
+    }
+    for (year, books) in years {
+        let path = dir.join(YEARS_DIR).join(format!("{year}.md"));
+        write_note(&path, &year_note(year, &books, &names), &mut report)?;
+    }
+    Ok(report)
+}
+
+/// Replaces the generated part of a note and keeps what follows the marker.
+/// A note without the marker is kept whole below it, so no user text is lost.
+fn write_note(path: &Path, generated: &str, report: &mut MarkdownReport) -> io::Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    let kept = match &existing {
+        Some(text) => match text.split_once(MARKER) {
+            Some((_, kept)) => kept.to_string(),
+            None => format!("\n{text}"),
+        },
+        None => "\n".to_string(),
+    };
+    let content = format!("{generated}\n{MARKER}{kept}");
+    match existing {
+        Some(text) if text == content => report.unchanged += 1,
+        Some(_) => {
+            fs::write(path, content)?;
+            report.updated += 1;
+        }
+        None => {
+            fs::write(path, content)?;
+            report.created += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Ids of earlier exported notes, read from their frontmatter.
+fn existing_notes(dir: &Path) -> io::Result<HashMap<Uuid, String>> {
+    let mut notes = HashMap::new();
+    for path in fs::read_dir(dir)?.filter_map(|e| e.ok().map(|e| e.path())) {
+        if path.extension().is_none_or(|e| e != "md") {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let id = text
+            .strip_prefix("---\n")
+            .and_then(|t| t.split("\n---").next())
+            .and_then(|front| front.lines().find_map(|l| l.strip_prefix("id: ")))
+            .and_then(|id| Uuid::parse_str(id.trim().trim_matches('"')).ok());
+        if let (Some(id), Some(stem)) = (id, path.file_stem()) {
+            notes.insert(id, stem.to_string_lossy().to_string());
+        }
+    }
+    Ok(notes)
+}
+
+/// Existing notes keep their name. New ones are named after the title, with
+/// the author or the id added when another book already uses the name.
+fn note_names(books: &[Book], existing: &HashMap<Uuid, String>) -> HashMap<Uuid, String> {
+    let mut names = HashMap::new();
+    let mut taken = HashSet::new();
+    for book in books {
+        if let Some(name) = existing.get(&book.id) {
+            names.insert(book.id, name.clone());
+            taken.insert(name.to_lowercase());
+        }
+    }
+    let new_books = books
+        .iter()
+        .filter(|b| !names.contains_key(&b.id))
+        .collect::<Vec<_>>();
+    for book in new_books {
+        let title = file_name(&book.title);
+        let with_author = match book.authors.first() {
+            Some(author) => format!("{title} ({})", file_name(author)),
+            None => title.clone(),
+        };
+        let with_id = format!("{title} ({})", &book.id.to_string()[..8]);
+        let name = [title, with_author, with_id]
+            .into_iter()
+            .find(|n| !taken.contains(&n.to_lowercase()))
+            .unwrap_or_else(|| book.id.to_string());
+        taken.insert(name.to_lowercase());
+        names.insert(book.id, name);
+    }
+    names
+}
+
+fn book_note(book: &Book) -> String {
+    let mut note = String::from("---\n");
+    note.push_str(&format!("id: {}\n", book.id));
+    note.push_str(&format!("title: {}\n", quoted(&book.title)));
+    note.push_str(&yaml_list(
+        "authors",
+        book.authors.iter().map(|a| quoted(a)).collect(),
+    ));
+    if book.year > 0 {
+        note.push_str(&format!("year: {}\n", book.year));
+    }
+    note.push_str(&format!("status: {}\n", status(book)));
+    note.push_str(&format!("rating: {}\n", book.rating));
+    note.push_str(&yaml_list(
+        "finished",
+        book.finished_at.iter().map(|d| d.to_string()).collect(),
+    ));
+    note.push_str(&yaml_list(
+        "tags",
+        book.tags
+            .iter()
+            .map(|t| quoted(&t.replace(' ', "-")))
+            .collect(),
+    ));
+    if let Some(isbn) = book.identifiers.isbn() {
+        note.push_str(&format!("isbn: {}\n", quoted(isbn)));
+    }
+    if let Some(series) = &book.series {
+        note.push_str(&format!("series: {}\n", quoted(series)));
+    }
+    note.push_str("---\n\n");
+
+    note.push_str(&format!("# {}\n\n", book.title));
+    if !book.authors.is_empty() {
+        let authors = book
+            .authors
+            .iter()
+            .map(|a| format!("[[{}]]", file_name(a)))
+            .collect::<Vec<_>>();
+        note.push_str(&format!("by {}\n", authors.join(", ")));
+    }
+    let years = finished_years(book);
+    if !years.is_empty() {
+        let years = years.iter().map(|y| format!("[[{y}]]")).collect::<Vec<_>>();
+        note.push_str(&format!("Read in {}\n", years.join(", ")));
+    }
+    if !book.highlights.is_empty() {
+        note.push_str("\n## Highlights\n");
+        for highlight in &book.highlights {
+            note.push('\n');
+            for line in highlight.text.lines() {
+                note.push_str(&format!("> {line}\n"));
+            }
+            if let Some(text) = &highlight.note {
+                note.push_str(&format!("\n*Note:* {text}\n"));
+            }
+        }
+    }
+    note
+}
+
+fn author_note(author: &str, books: &[&Book], names: &HashMap<Uuid, String>) -> String {
+    let mut books = books.to_vec();
+    books.sort_by_key(|b| (b.year, b.title_normalized()));
+    let mut note = format!("# {author}\n\n");
+    for book in books {
+        let year = if book.year > 0 {
+            format!(" ({})", book.year)
+        } else {
+            String::new()
+        };
+        note.push_str(&format!(
+            "- [[{}]]{year} · {}\n",
+            names[&book.id],
+            status(book)
+        ));
+    }
+    note
+}
+
+fn year_note(year: i32, books: &[&Book], names: &HashMap<Uuid, String>) -> String {
+    let mut finished = books
+        .iter()
+        .flat_map(|b| {
+            b.finished_at
+                .iter()
+                .filter(|d| d.year() == year)
+                .map(move |d| (*d, *b))
+        })
+        .collect::<Vec<_>>();
+    finished.sort_by_key(|(date, _)| *date);
+    let count = match finished.len() {
+        1 => "1 book".to_string(),
+        n => format!("{n} books"),
+    };
+    let mut note = format!("# {year}\n\nFinished {count}.\n\n");
+    for (date, book) in finished {
+        let authors = if book.authors.is_empty() {
+            String::new()
+        } else {
+            format!(" by {}", book.authors.join(", "))
+        };
+        note.push_str(&format!("- {date} [[{}]]{authors}\n", names[&book.id]));
+    }
+    note
+}
+
+fn finished_years(book: &Book) -> Vec<i32> {
+    let mut years = book
+        .finished_at
+        .iter()
+        .map(|d| d.year())
+        .collect::<Vec<_>>();
+    years.sort();
+    years.dedup();
+    years
+}
+
+fn status(book: &Book) -> &'static str {
+    match book.reading_status {
+        ReadingStatus::ToRead => "to-read",
+        ReadingStatus::Reading => "reading",
+        ReadingStatus::Read => "read",
+    }
+}
+
+/// A JSON string is also a valid double-quoted YAML scalar.
+fn quoted(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+fn yaml_list(key: &str, values: Vec<String>) -> String {
+    if values.is_empty() {
+        return format!("{key}: []\n");
+    }
+    let mut text = format!("{key}:\n");
+    for value in values {
+        text.push_str(&format!("  - {value}\n"));
+    }
+    text
+}
+
+/// Drops the characters that file systems or Obsidian links do not allow.
+pub fn file_name(text: &str) -> String {
+    let name = text
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c => c,
+        })
+        .collect::<String>();
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = name.trim_start_matches('.').to_string();
+    if name.is_empty() {
+        "Untitled".to_string()
+    } else {
+        name
+    }
+}
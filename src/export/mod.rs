@@ -0,0 +1,71 @@
+pub mod atom;
+pub mod bibliography;
+pub mod card;
+pub mod catalog;
+pub mod columns;
+pub mod goodreads;
+pub mod ical;
+pub mod labels;
+pub mod markdown;
+pub mod site;
+pub mod template;
+pub mod text;
+
+use crate::{export::columns::Column, model::book::Book};
+use clap::ValueEnum;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    /// The CSV Goodreads accepts under "Import and export".
+    Goodreads,
+    /// Every field, or the chosen columns, with lists joined by "; ".
+    Csv,
+    /// One JSON object per book, with lists as arrays.
+    Jsonl,
+    /// BibTeX entries for LaTeX and reference managers.
+    Bibtex,
+    /// CSL-JSON, as read by Zotero and pandoc.
+    CslJson,
+    /// RIS records for EndNote, Zotero, Mendeley and others.
+    Ris,
+    /// An iCalendar file with an event per finished read.
+    Ical,
+}
+
+/// `columns` selects the fields of the CSV and JSON Lines exports; the other
+/// formats always get their own fields.
+pub fn export(
+    format: ExportFormat,
+    books: &[Book],
+    columns: &[Column],
+    output: impl Write,
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Goodreads => goodreads::write(books, output),
+        ExportFormat::Csv => columns::write_csv(books, columns, output),
+        ExportFormat::Jsonl => columns::write_jsonl(books, columns, output),
+        ExportFormat::Bibtex => bibliography::write_bibtex(books, output),
+        ExportFormat::CslJson => bibliography::write_csl_json(books, output),
+        ExportFormat::Ris => bibliography::write_ris(books, output),
+        ExportFormat::Ical => ical::write(books, output),
+    }
+}
+
+/// A reader that stops early, like `head`, closes the pipe; that is not an error.
+pub fn ignore_broken_pipe(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// A CSV error as an I/O error that keeps the kind of the underlying I/O
+/// failure, so a closed pipe is still recognised.
+pub fn csv_error(e: csv::Error) -> io::Error {
+    let kind = match e.kind() {
+        csv::ErrorKind::Io(e) => e.kind(),
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, e)
+}
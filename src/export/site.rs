@@ -0,0 +1,393 @@
+use crate::model::{
+    book::{item_type::ItemType, reading_status::ReadingStatus, Book, COVERS_DIR},
+    stats::Stats,
+};
+use chrono::Datelike;
+use image::imageops::FilterType;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+const SITE_TITLE: &str = "Bookshelf";
+const THUMBNAIL_WIDTH: u32 = 240;
+const THUMBNAIL_HEIGHT: u32 = 360;
+/// Lists the files the last run wrote, one path relative to the output per
+/// line, so only those are ever removed.
+const MANIFEST: &str = ".bookie-site";
+const STYLE: &str = r#"body { font-family: system-ui, sans-serif; margin: 0 auto; max-width: 64rem; padding: 1rem; color: #222; }
+a { color: #2a5db0; text-decoration: none; }
+a:hover { text-decoration: underline; }
+nav { display: flex; gap: 1rem; flex-wrap: wrap; border-bottom: 1px solid #ddd; padding-bottom: .5rem; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(9rem, 1fr)); gap: 1rem; padding: 0; list-style: none; }
+.grid li { font-size: .85rem; }
+.cover { width: 100%; aspect-ratio: 2 / 3; object-fit: cover; background: #eee; display: flex; align-items: center; justify-content: center; text-align: center; padding: .5rem; box-sizing: border-box; }
+.book { display: flex; gap: 2rem; flex-wrap: wrap; }
+.book .cover { width: 15rem; }
+.muted { color: #777; }
+.rating { color: #c90; }
+blockquote { border-left: 3px solid #ddd; margin-left: 0; padding-left: 1rem; }
+table { border-collapse: collapse; }
+td, th { padding: .25rem 1rem .25rem 0; text-align: left; }
+"#;
+
+#[derive(Default)]
+pub struct SiteReport {
+    pub pages: usize,
+    pub thumbnails: usize,
+    /// Books left out because they are marked private.
+    pub private: usize,
+    /// Files of an earlier run that no longer belong to the site.
+    pub removed: usize,
+    /// Covers that could not be read, with the reason.
+    pub failed_covers: Vec<(PathBuf, String)>,
+}
+
+/// Writes a static site to `dir`: a cover grid, a page per book, a page per
+/// year of reading and a stats page. Pages only use HTML and CSS.
+pub fn write(books: &[Book], dir: &Path) -> io::Result<SiteReport> {
+    // Thumbnails would replace the library's own covers.
+    let library_covers = Path::new(COVERS_DIR).canonicalize().ok();
+    if library_covers.is_some() && dir.join("covers").canonicalize().ok() == library_covers {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} holds the library's covers, choose another folder for the site",
+                dir.display()
+            ),
+        ));
+    }
+    let mut report = SiteReport::default();
+    for sub_dir in ["books", "years", "covers"] {
+        fs::create_dir_all(dir.join(sub_dir))?;
+    }
+    fs::write(dir.join("style.css"), STYLE)?;
+
+    report.private = books.iter().filter(|b| b.private).count();
+    let mut books = books
+        .iter()
+        .filter(|b| !b.private)
+        .cloned()
+        .collect::<Vec<_>>();
+    books.sort_by_key(|b| b.title_normalized());
+
+    let mut thumbnails = BTreeMap::new();
+    for book in &books {
+        let Some(source) = book.cover_path.as_ref().filter(|p| p.is_file()) else {
+            continue;
+        };
+        let name = format!("{}.jpg", slug(book));
+        match write_thumbnail(source, &dir.join("covers").join(&name)) {
+            Ok(written) => {
+                report.thumbnails += usize::from(written);
+                thumbnails.insert(book.id, name);
+            }
+            Err(e) => report.failed_covers.push((source.clone(), e)),
+        }
+    }
+    let site = Site {
+        books: &books,
+        thumbnails,
+    };
+
+    let mut years: BTreeMap<i32, Vec<&Book>> = BTreeMap::new();
+    for book in &books {
+        let mut finished = book
+            .finished_at
+            .iter()
+            .map(|d| d.year())
+            .collect::<Vec<_>>();
+        finished.sort();
+        finished.dedup();
+        for year in finished {
+            years.entry(year).or_default().push(book);
+        }
+    }
+    let year_list = years.keys().rev().copied().collect::<Vec<_>>();
+
+    let mut pages = vec![
+        (dir.join("index.html"), site.index(&year_list)),
+        (dir.join("stats.html"), site.stats(&year_list)),
+    ];
+    for book in &books {
+        let path = dir.join("books").join(format!("{}.html", slug(book)));
+        pages.push((path, site.book(book, &year_list)));
+    }
+    for (year, year_books) in &years {
+        let path = dir.join("years").join(format!("{year}.html"));
+        pages.push((path, site.year(*year, year_books, &year_list)));
+    }
+    let mut written = site
+        .thumbnails
+        .values()
+        .map(|name| dir.join("covers").join(name))
+        .collect::<HashSet<_>>();
+    for (path, html) in pages {
+        fs::write(&path, html)?;
+        written.insert(path);
+        report.pages += 1;
+    }
+    // Pages and covers of books that were removed or made private must not stay
+    // online, but files the generator didn't write are left alone.
+    let manifest = dir.join(MANIFEST);
+    let previous = fs::read_to_string(&manifest).unwrap_or_default();
+    let listed = previous
+        .lines()
+        .map(Path::new)
+        .filter(|p| p.components().all(|c| matches!(c, Component::Normal(_))));
+    for path in listed.map(|p| dir.join(p)) {
+        if path.is_file() && !written.contains(&path) {
+            fs::remove_file(path)?;
+            report.removed += 1;
+        }
+    }
+    let mut files = written
+        .iter()
+        .filter_map(|path| path.strip_prefix(dir).ok())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+    fs::write(manifest, files.join("\n") + "\n")?;
+    Ok(report)
+}
+
+struct Site<'a> {
+    books: &'a [Book],
+    /// File names in `covers/` by book id.
+    thumbnails: BTreeMap<uuid::Uuid, String>,
+}
+
+impl Site<'_> {
+    fn index(&self, years: &[i32]) -> String {
+        let body = format!(
+            "<h1>{SITE_TITLE}</h1>\n<p class=\"muted\">{} books</p>\n{}",
+            self.books.len(),
+            self.grid(self.books.iter().collect(), "")
+        );
+        page(SITE_TITLE, "", years, &body)
+    }
+
+    fn book(&self, book: &Book, years: &[i32]) -> String {
+        let mut details = vec![];
+        if !book.authors.is_empty() {
+            details.push(format!("by {}", escape(&book.authors.join(", "))));
+        }
+        let mut facts = vec![];
+        if book.year > 0 {
+            facts.push(book.year.to_string());
+        }
+        if book.pages > 0 {
+            facts.push(format!("{} pages", book.pages));
+        }
+        if let Some(publisher) = &book.publisher {
+            facts.push(escape(publisher));
+        }
+        if let Some(series) = &book.series {
+            facts.push(match book.series_index {
+                Some(index) => format!("{} #{index}", escape(series)),
+                None => escape(series),
+            });
+        }
+        if !facts.is_empty() {
+            details.push(format!(
+                "<span class=\"muted\">{}</span>",
+                facts.join(" · ")
+            ));
+        }
+        details.push(status(book).to_string());
+        if book.rating > 0 {
+            details.push(format!(
+                "<span class=\"rating\">{}</span>",
+                "★".repeat(book.rating as usize)
+            ));
+        }
+        if !book.finished_at.is_empty() {
+            let dates = book
+                .finished_at
+                .iter()
+                .map(|d| format!("<a href=\"../years/{}.html\">{d}</a>", d.year()))
+                .collect::<Vec<_>>();
+            details.push(format!("Finished {}", dates.join(", ")));
+        }
+        if !book.tags.is_empty() {
+            details.push(format!(
+                "<span class=\"muted\">{}</span>",
+                escape(&book.tags.join(", "))
+            ));
+        }
+
+        let mut body = format!(
+            "<div class=\"book\">\n{}\n<div>\n<h1>{}</h1>\n{}\n</div>\n</div>\n",
+            self.cover(book, "../"),
+            escape(&book.title),
+            details
+                .iter()
+                .map(|d| format!("<p>{d}</p>"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        if !book.highlights.is_empty() {
+            body.push_str("<h2>Highlights</h2>\n");
+            for highlight in &book.highlights {
+                body.push_str(&format!(
+                    "<blockquote>{}</blockquote>\n",
+                    escape(&highlight.text).replace('\n', "<br>")
+                ));
+                if let Some(note) = &highlight.note {
+                    body.push_str(&format!("<p class=\"muted\">{}</p>\n", escape(note)));
+                }
+            }
+        }
+        page(&book.title, "../", years, &body)
+    }
+
+    fn year(&self, year: i32, books: &[&Book], years: &[i32]) -> String {
+        let mut books = books.to_vec();
+        books.sort_by_key(|b| {
+            b.finished_at
+                .iter()
+                .rev()
+                .find(|d| d.year() == year)
+                .copied()
+        });
+        let stats = Stats::new(self.books);
+        let body = format!(
+            "<h1>Books I read in {year}</h1>\n<p class=\"muted\">{} books · {} pages</p>\n{}",
+            stats.books_read_in_year(year as u16),
+            stats.pages_read_in_year(year as u16),
+            self.grid(books, "../")
+        );
+        page(&format!("{year}"), "../", years, &body)
+    }
+
+    fn stats(&self, years: &[i32]) -> String {
+        let stats = Stats::new(self.books);
+        let rows = [
+            ("Books", stats.books_in_db()),
+            ("Authors", stats.unique_authors()),
+            ("Read", stats.books_read()),
+            ("Reading", stats.books_reading()),
+            ("To read", stats.books_to_read()),
+            ("Comics", stats.items_of_type(ItemType::Comic)),
+            ("Magazines", stats.items_of_type(ItemType::Magazine)),
+            ("Papers", stats.items_of_type(ItemType::Paper)),
+        ];
+        let mut body = String::from("<h1>Stats</h1>\n<table>\n");
+        for (label, value) in rows {
+            body.push_str(&format!("<tr><th>{label}</th><td>{value}</td></tr>\n"));
+        }
+        body.push_str("</table>\n<h2>By year</h2>\n<table>\n");
+        body.push_str("<tr><th>Year</th><th>Books</th><th>Pages</th><th>Other items</th></tr>\n");
+        for year in years {
+            let year = *year as u16;
+            let other = [ItemType::Comic, ItemType::Magazine, ItemType::Paper]
+                .iter()
+                .map(|t| stats.items_read_in_year(*t, year))
+                .sum::<usize>();
+            body.push_str(&format!(
+                "<tr><td><a href=\"years/{year}.html\">{year}</a></td><td>{}</td><td>{}</td><td>{other}</td></tr>\n",
+                stats.books_read_in_year(year),
+                stats.pages_read_in_year(year),
+            ));
+        }
+        body.push_str("</table>\n");
+        page("Stats", "", years, &body)
+    }
+
+    fn grid(&self, books: Vec<&Book>, root: &str) -> String {
+        let mut html = String::from("<ul class=\"grid\">\n");
+        for book in books {
+            html.push_str(&format!(
+                "<li><a href=\"{root}books/{}.html\">{}<br>{}</a><br><span class=\"muted\">{}</span></li>\n",
+                slug(book),
+                self.cover(book, root),
+                escape(&book.title),
+                escape(&book.authors.join(", "))
+            ));
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+
+    /// The thumbnail, or the title on a grey box for books without a cover.
+    fn cover(&self, book: &Book, root: &str) -> String {
+        match self.thumbnails.get(&book.id) {
+            Some(name) => format!(
+                "<img class=\"cover\" src=\"{root}covers/{name}\" alt=\"Cover of {}\" loading=\"lazy\">",
+                escape(&book.title)
+            ),
+            None => format!("<div class=\"cover\">{}</div>", escape(&book.title)),
+        }
+    }
+}
+
+fn page(title: &str, root: &str, years: &[i32], body: &str) -> String {
+    let year_links = years
+        .iter()
+        .map(|y| format!("<a href=\"{root}years/{y}.html\">{y}</a>"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{}</title>
+<link rel=\"stylesheet\" href=\"{root}style.css\">
+</head>
+<body>
+<nav><a href=\"{root}index.html\">{SITE_TITLE}</a> <a href=\"{root}stats.html\">Stats</a> {year_links}</nav>
+<main>
+{body}</main>
+</body>
+</html>
+",
+        escape(title)
+    )
+}
+
+/// Resizes a cover unless the thumbnail is newer than it. Returns whether it was written.
+fn write_thumbnail(source: &Path, target: &Path) -> Result<bool, String> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    if let (Some(source_time), Some(target_time)) = (modified(source), modified(target)) {
+        if target_time >= source_time {
+            return Ok(false);
+        }
+    }
+    let image = image::open(source).map_err(|e| e.to_string())?;
+    image
+        .resize(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT, FilterType::Triangle)
+        .to_rgb8()
+        .save(target)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+pub fn status(book: &Book) -> &'static str {
+    match book.reading_status {
+        ReadingStatus::ToRead => "To read",
+        ReadingStatus::Reading => "Reading",
+        ReadingStatus::Read => "Read",
+    }
+}
+
+/// A file name from the title that stays the same across runs.
+pub fn slug(book: &Book) -> String {
+    let title = book
+        .title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>();
+    let words = title.split_whitespace().collect::<Vec<_>>().join("-");
+    format!("{words}-{}", &book.id.to_string()[..8])
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
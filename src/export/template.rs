@@ -0,0 +1,96 @@
+use crate::model::{
+    book::{author_name::AuthorName, Book},
+    stats::Stats,
+};
+use chrono::{Datelike, Local};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, error::Error, io, io::Write, path::Path};
+use tera::{Context, Tera};
+
+/// Renders the books through a Tera template. The context has `books`,
+/// `stats` with the numbers of the stats panel, `authors` and `years`.
+pub fn write(books: &[Book], template: &Path, mut output: impl Write) -> io::Result<()> {
+    let name = template
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut tera = Tera::default();
+    tera.autoescape_on(vec![
+        ".html",
+        ".htm",
+        ".xml",
+        ".html.tera",
+        ".htm.tera",
+        ".xml.tera",
+    ]);
+    tera.add_template_file(template, Some(&name))
+        .map_err(template_error)?;
+    let context = Context::from_value(context(books)).map_err(template_error)?;
+    tera.render_to(&name, &context, &mut output)
+        .map_err(template_error)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn context(books: &[Book]) -> Value {
+    let stats = Stats::new(books);
+
+    let mut by_author = BTreeMap::<(String, String), Vec<&Book>>::new();
+    for book in books {
+        for author in &book.authors {
+            let family = AuthorName::parse(author).family.to_lowercase();
+            by_author
+                .entry((family, author.clone()))
+                .or_default()
+                .push(book);
+        }
+    }
+    let authors = by_author
+        .into_iter()
+        .map(|((_, name), books)| json!({"name": name, "books": books}))
+        .collect::<Vec<_>>();
+
+    let mut by_year = BTreeMap::<u16, Vec<(chrono::NaiveDate, &Book)>>::new();
+    for book in books {
+        for date in &book.finished_at {
+            by_year
+                .entry(date.year() as u16)
+                .or_default()
+                .push((*date, book));
+        }
+    }
+    let years = by_year
+        .into_iter()
+        .rev()
+        .map(|(year, mut finished)| {
+            finished.sort_by_key(|(date, _)| *date);
+            let books = finished.into_iter().map(|(_, b)| b).collect::<Vec<_>>();
+            let summary = stats.summary(year);
+            json!({
+                "year": year,
+                "books": books,
+                "books_read": summary.books_read_this_year,
+                "pages_read": summary.pages_read_this_year,
+                "other_items_read": summary.other_items_read_this_year,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "books": books,
+        "stats": stats.summary(Local::now().year() as u16),
+        "authors": authors,
+        "years": years,
+    })
+}
+
+/// Tera puts the line and the reason of a mistake in the error's sources.
+fn template_error(error: tera::Error) -> io::Error {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    io::Error::other(message)
+}
@@ -0,0 +1,72 @@
+/// Shortens the text with an ellipsis until it fits `max_width`, as measured
+/// by `width`.
+pub fn truncate(text: &str, max_width: f32, width: impl Fn(&str) -> f32) -> String {
+    if width(text) <= max_width {
+        return text.to_string();
+    }
+    let mut chars = text.chars().collect::<Vec<_>>();
+    while !chars.is_empty() {
+        chars.pop();
+        let shortened = format!("{}…", chars.iter().collect::<String>().trim_end());
+        if width(&shortened) <= max_width {
+            return shortened;
+        }
+    }
+    "…".to_string()
+}
+
+/// Breaks the text into lines that fit `max_width`, shortening the last one
+/// with an ellipsis when there are too many.
+pub fn wrap(
+    text: &str,
+    max_width: f32,
+    max_lines: usize,
+    width: impl Fn(&str) -> f32,
+) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if width(&format!("{line} {word}")) <= max_width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    if lines.len() > max_lines {
+        let rest = lines.split_off(max_lines.saturating_sub(1)).join(" ");
+        lines.push(rest);
+    }
+    lines
+        .into_iter()
+        .map(|line| truncate(&line, max_width, &width))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn chars(text: &str) -> f32 {
+        text.chars().count() as f32
+    }
+
+    #[rstest]
+    #[case("Dune", 10.0, "Dune")]
+    #[case("The Left Hand of Darkness", 10.0, "The Left…")]
+    #[case("Dune Messiah", 6.0, "Dune…")]
+    #[case("Dune", 0.0, "…")]
+    fn truncates(#[case] text: &str, #[case] max_width: f32, #[case] expected: &str) {
+        assert_eq!(truncate(text, max_width, chars), expected);
+    }
+
+    #[rstest]
+    #[case("The Left Hand of Darkness", 3, &["The Left", "Hand of", "Darkness"])]
+    #[case("The Left Hand of Darkness", 2, &["The Left", "Hand of D…"])]
+    #[case("Incomprehensibilities", 1, &["Incompreh…"])]
+    #[case("", 2, &[])]
+    fn wraps(#[case] text: &str, #[case] max_lines: usize, #[case] expected: &[&str]) {
+        assert_eq!(wrap(text, 10.0, max_lines, chars), expected);
+    }
+}
@@ -0,0 +1,474 @@
+use crate::{
+    import::{optional, parse_isbn, ImportReport, ImportedBook, RowProblem},
+    metadata::Metadata,
+    model::book::{
+        author_name::{author_sort, AuthorName},
+        reading_status::ReadingStatus,
+    },
+};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Reads every entry of a `.bib` file as a to-read book. `@string` macros are
+/// expanded and `@comment` and `@preamble` blocks are skipped.
+pub fn read(path: &Path, report: &mut ImportReport) -> csv::Result<Vec<ImportedBook>> {
+    Ok(parse(&fs::read_to_string(path)?, report))
+}
+
+/// A broken entry is reported and skipped up to the next `@` at the start of a line.
+fn parse(source: &str, report: &mut ImportReport) -> Vec<ImportedBook> {
+    let mut parser = Parser {
+        chars: source.chars().collect(),
+        pos: 0,
+        strings: HashMap::new(),
+    };
+    let mut books = vec![];
+    while let Some(start) = parser.next_entry() {
+        let line = parser.line_at(start);
+        match parser.entry() {
+            Ok(Some(fields)) => {
+                let mut warnings = vec![];
+                match to_book(&fields, &mut warnings) {
+                    Ok(book) => {
+                        books.push(book);
+                        report.warnings.extend(
+                            warnings
+                                .into_iter()
+                                .map(|message| RowProblem { line, message }),
+                        );
+                    }
+                    Err(message) => report.invalid.push(RowProblem { line, message }),
+                }
+            }
+            Ok(None) => {}
+            Err(message) => {
+                report.invalid.push(RowProblem { line, message });
+                parser.pos = start;
+                parser.skip_to_next_entry();
+            }
+        }
+    }
+    books
+}
+
+fn to_book(
+    fields: &HashMap<String, String>,
+    warnings: &mut Vec<String>,
+) -> Result<ImportedBook, String> {
+    let field = |name: &str| fields.get(name).and_then(|v| optional(v));
+    let title = field("title").ok_or("The entry has no title")?;
+    let names = field("author")
+        .or_else(|| field("editor"))
+        .map(|names| split_names(&names))
+        .unwrap_or_default();
+    let year = field("year")
+        .or_else(|| field("date"))
+        .and_then(|date| date.get(..4).and_then(|y| y.parse::<u16>().ok()));
+    Ok(ImportedBook {
+        metadata: Metadata {
+            title: Some(title),
+            authors: names
+                .iter()
+                .map(|a| AuthorName::parse(a).natural())
+                .collect(),
+            author_sort: author_sort(&names),
+            year,
+            publisher: field("publisher"),
+            isbn: field("isbn").and_then(|isbn| parse_isbn(&isbn, warnings)),
+            ..Metadata::default()
+        },
+        reading_status: ReadingStatus::ToRead,
+        started_at: vec![],
+        finished_at: vec![],
+    })
+}
+
+/// Splits `Herbert, Frank and Le Guin, Ursula K.` into the names as written.
+fn split_names(names: &str) -> Vec<String> {
+    let mut authors = vec![];
+    let mut rest = names;
+    while let Some(index) = rest.find(" and ") {
+        authors.push(&rest[..index]);
+        rest = &rest[index + " and ".len()..];
+    }
+    authors.push(rest);
+    authors
+        .into_iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty() && *a != "others")
+        .map(str::to_string)
+        .collect()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    strings: HashMap<String, String>,
+}
+
+impl Parser {
+    /// Moves to the next `@` and returns its position.
+    fn next_entry(&mut self) -> Option<usize> {
+        while self.pos < self.chars.len() {
+            if self.chars[self.pos] == '@' {
+                return Some(self.pos);
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    /// Skips a broken entry up to the next `@` at the start of a line.
+    fn skip_to_next_entry(&mut self) {
+        self.pos += 1;
+        while self.pos < self.chars.len() && !(self.chars[self.pos] == '@' && self.at_line_start())
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// The fields of a reference entry, or None for `@string`, `@comment` and `@preamble`.
+    fn entry(&mut self) -> Result<Option<HashMap<String, String>>, String> {
+        self.pos += 1;
+        let entry_type = self.identifier().to_lowercase();
+        self.skip_space();
+        let close = match self.peek() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => return Err(format!("'@{entry_type}' is not followed by a brace")),
+        };
+        self.pos += 1;
+        match entry_type.as_str() {
+            "comment" | "preamble" => {
+                self.pos -= 1;
+                self.braced()?;
+                return Ok(None);
+            }
+            "string" => {
+                let (name, value) = self.field()?;
+                self.strings.insert(name, value);
+                self.skip_space();
+                self.expect(close)?;
+                return Ok(None);
+            }
+            _ => {}
+        }
+
+        self.skip_space();
+        while self.peek().is_some_and(|c| c != ',' && c != close) {
+            self.pos += 1;
+        }
+        let mut fields = HashMap::new();
+        loop {
+            self.skip_space();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(Some(fields));
+                }
+                None => return Err("The entry is not closed".to_string()),
+                _ => {
+                    let (name, value) = self.field()?;
+                    fields.insert(name, value);
+                }
+            }
+        }
+    }
+
+    /// `name = {value} # "value" # macro`
+    fn field(&mut self) -> Result<(String, String), String> {
+        self.skip_space();
+        let name = self.identifier().to_lowercase();
+        if name.is_empty() {
+            return Err("Expected a field name".to_string());
+        }
+        self.skip_space();
+        self.expect('=')?;
+        let mut value = String::new();
+        loop {
+            self.skip_space();
+            match self.peek() {
+                Some('{') => value.push_str(&self.braced()?),
+                Some('"') => value.push_str(&self.quoted()?),
+                Some(c) if c.is_alphanumeric() => {
+                    let word = self.identifier();
+                    match self.strings.get(&word.to_lowercase()) {
+                        Some(text) => value.push_str(text),
+                        None => value.push_str(&word),
+                    }
+                }
+                _ => return Err(format!("Field '{name}' has no value")),
+            }
+            self.skip_space();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Ok((name, decode_latex(&value)))
+    }
+
+    /// The text between matching braces, with inner braces kept for `decode_latex`.
+    fn braced(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err("A brace is not closed".to_string());
+            };
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(text),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err("A quote is not closed".to_string());
+            };
+            self.pos += 1;
+            match c {
+                '"' if depth == 0 => return Ok(text),
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || "_-:.+/".contains(c))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected '{expected}'"))
+        }
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.chars[..self.pos]
+            .iter()
+            .rev()
+            .take_while(|c| **c != '\n')
+            .all(|c| c.is_whitespace())
+    }
+
+    fn line_at(&self, pos: usize) -> usize {
+        self.chars[..pos].iter().filter(|c| **c == '\n').count() + 1
+    }
+}
+
+/// Turns the LaTeX of titles and names into plain text: accents such as
+/// `\"{o}` or `{\'e}` become letters, escapes lose their backslash and
+/// grouping braces are dropped.
+fn decode_latex(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut decoded = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                let command = chars[i + 1];
+                i += 2;
+                if "\"'`^~=.".contains(command)
+                    || ("cv".contains(command) && chars.get(i) == Some(&'{'))
+                {
+                    let braced = chars.get(i) == Some(&'{');
+                    let mut p = i + usize::from(braced);
+                    // Dotless i and j, as in \'\i.
+                    if chars.get(p) == Some(&'\\') && matches!(chars.get(p + 1), Some('i' | 'j')) {
+                        p += 1;
+                    }
+                    if let Some(letter) = chars.get(p).copied() {
+                        decoded.push(accented(command, letter).unwrap_or(letter));
+                        i = p + 1 + usize::from(braced && chars.get(p + 1) == Some(&'}'));
+                        continue;
+                    }
+                }
+                if command.is_alphabetic() {
+                    let start = i - 1;
+                    while i < chars.len() && chars[i].is_alphabetic() {
+                        i += 1;
+                    }
+                    let name = chars[start..i].iter().collect::<String>();
+                    match letter_command(&name) {
+                        Some(letter) => {
+                            decoded.push(letter);
+                            if chars.get(i) == Some(&' ') {
+                                i += 1;
+                            }
+                        }
+                        // Commands like \textit{...} keep only their argument,
+                        // logos like \TeX keep their name.
+                        None if chars.get(i) == Some(&'{') => {}
+                        None => decoded.push_str(&name),
+                    }
+                } else {
+                    decoded.push(command);
+                }
+            }
+            '{' | '}' => i += 1,
+            '~' => {
+                decoded.push(' ');
+                i += 1;
+            }
+            c => {
+                decoded.push(c);
+                i += 1;
+            }
+        }
+    }
+    decoded
+        .replace("---", "—")
+        .replace("--", "–")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn letter_command(name: &str) -> Option<char> {
+    Some(match name {
+        "ss" => 'ß',
+        "o" => 'ø',
+        "O" => 'Ø',
+        "aa" => 'å',
+        "AA" => 'Å',
+        "ae" => 'æ',
+        "AE" => 'Æ',
+        "oe" => 'œ',
+        "OE" => 'Œ',
+        "l" => 'ł',
+        "L" => 'Ł',
+        "i" => 'ı',
+        _ => return None,
+    })
+}
+
+fn accented(accent: char, letter: char) -> Option<char> {
+    let (plain, marked) = match accent {
+        '"' => ("aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+        '\'' => ("aeiouyncAEIOUYNC", "áéíóúýńćÁÉÍÓÚÝŃĆ"),
+        '`' => ("aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        '^' => ("aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        '~' => ("anoANO", "ãñõÃÑÕ"),
+        'c' => ("csCS", "çşÇŞ"),
+        'v' => ("cszrnecCSZRNE", "čšžřňěčČŠŽŘŇĚ"),
+        _ => return None,
+    };
+    let index = plain.chars().position(|c| c == letter)?;
+    marked.chars().nth(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r#"G{\"o}del, Escher, Bach"#, "Gödel, Escher, Bach")]
+    #[case(r"{\'E}mile Zola", "Émile Zola")]
+    #[case(r#"Br\"uder"#, "Brüder")]
+    #[case(r"Fran\c{c}ois", "François")]
+    #[case(r"Dvo\v{r}\'ak", "Dvořák")]
+    #[case(r#"Na\"{\i}ve"#, "Naïve")]
+    #[case(r"Stra\ss e", "Straße")]
+    #[case(r"\O{}ystein S\o rensen", "Øystein Sørensen")]
+    #[case(r"The {LaTeX} \TeX book", "The LaTeX TeX book")]
+    #[case(r"\textit{Dune} Messiah", "Dune Messiah")]
+    #[case(r"Salt \& Pepper, 100\%", "Salt & Pepper, 100%")]
+    #[case("Pages 1--10 --- roughly", "Pages 1–10 — roughly")]
+    #[case("Frank~Herbert", "Frank Herbert")]
+    #[case("  spread \n over   lines ", "spread over lines")]
+    fn decodes_latex(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(decode_latex(text), expected);
+    }
+
+    #[rstest]
+    #[case("Herbert, Frank", &["Herbert, Frank"])]
+    #[case(
+        "Herbert, Frank and Le Guin, Ursula K.",
+        &["Herbert, Frank", "Le Guin, Ursula K."]
+    )]
+    #[case(
+        "Terry Pratchett and Neil Gaiman and others",
+        &["Terry Pratchett", "Neil Gaiman"]
+    )]
+    #[case("Alexander Anderson", &["Alexander Anderson"])]
+    #[case("", &[])]
+    fn splits_names(#[case] names: &str, #[case] expected: &[&str]) {
+        assert_eq!(split_names(names), expected);
+    }
+
+    #[test]
+    fn recovers_from_an_unclosed_entry() {
+        let source = r#"@book{dune,
+  title = {Dune},
+  author = {Herbert, Frank},
+
+@book{earthsea,
+  title = "A Wizard of Earthsea",
+  author = {Le Guin, Ursula K.},
+  year = 1968
+}
+"#;
+        let mut report = ImportReport::default();
+        let books = parse(source, &mut report);
+        assert_eq!(books.len(), 1);
+        assert_eq!(
+            books[0].metadata.title.as_deref(),
+            Some("A Wizard of Earthsea")
+        );
+        assert_eq!(books[0].metadata.authors, ["Ursula K. Le Guin"]);
+        assert_eq!(books[0].metadata.year, Some(1968));
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.invalid[0].line, 1);
+    }
+
+    #[test]
+    fn expands_string_macros() {
+        let source = r#"@string{ace = "Ace Books"}
+@comment{not an entry}
+@book{dune, title = {Dune}, publisher = ace # " (reprint)"}"#;
+        let mut report = ImportReport::default();
+        let books = parse(source, &mut report);
+        assert_eq!(books.len(), 1);
+        assert_eq!(
+            books[0].metadata.publisher.as_deref(),
+            Some("Ace Books (reprint)")
+        );
+        assert!(report.invalid.is_empty());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    library::epub,
+    metadata::Metadata,
+    model::book::{linked_file::LinkedFile, Book},
+};
+use log::warn;
+use rusqlite::{Connection, OpenFlags};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const DATABASE_FILE: &str = "metadata.db";
+const OPF_FILE: &str = "metadata.opf";
+const COVER_FILE: &str = "cover.jpg";
+/// Separates list values in SQL results; Calibre author names may contain `|`.
+const SEPARATOR: char = '\u{1f}';
+
+/// How Calibre fields end up in bookie, printed before importing.
+pub const MAPPING: &[(&str, &str)] = &[
+    ("title", "title"),
+    ("authors", "authors"),
+    ("author sort", "author sort name"),
+    ("pubdate", "year"),
+    ("series, series index", "series, series index"),
+    ("tags", "tags"),
+    (
+        "identifiers isbn, amazon, goodreads, openlibrary",
+        "identifiers (others are dropped)",
+    ),
+    ("publisher", "publisher"),
+    ("rating (0-10 half stars)", "rating (0-5 stars, rounded)"),
+    ("languages", "language (first one)"),
+    ("cover.jpg", "covers/<title>.jpg"),
+    ("EPUB or PDF format", "linked ebook file"),
+];
+
+pub struct CalibreBook {
+    /// The book's folder in the Calibre library.
+    pub dir: PathBuf,
+    pub metadata: Metadata,
+    pub cover: Option<PathBuf>,
+    pub file: Option<PathBuf>,
+}
+
+pub enum Action {
+    Add,
+    /// Fills in empty fields of the book at this index, which may be one
+    /// added by an earlier action.
+    Update(usize),
+    Unchanged,
+}
+
+pub struct CalibreLibrary {
+    pub books: Vec<CalibreBook>,
+    /// Book folders whose metadata could not be read or has no title.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl CalibreLibrary {
+    /// A book can't be added without a title, so those are reported instead.
+    fn new(entries: Vec<CalibreBook>, mut failed: Vec<(PathBuf, String)>) -> Self {
+        let (books, untitled): (Vec<_>, Vec<_>) =
+            entries.into_iter().partition(|e| title(e).is_some());
+        failed.extend(
+            untitled
+                .into_iter()
+                .map(|e| (e.dir, "No title".to_string())),
+        );
+        Self { books, failed }
+    }
+}
+
+/// Reads `metadata.opf` from every book folder (`Author/Title (id)/`).
+pub fn read_folders(library: &Path) -> io::Result<CalibreLibrary> {
+    let mut books = vec![];
+    let mut failed = vec![];
+    for author_dir in sorted_dirs(library)? {
+        for book_dir in sorted_dirs(&author_dir)? {
+            let opf = book_dir.join(OPF_FILE);
+            if !opf.is_file() {
+                continue;
+            }
+            match fs::read_to_string(&opf)
+                .map_err(|e| e.to_string())
+                .and_then(|source| epub::read_opf(&source))
+            {
+                Ok(metadata) => books.push(CalibreBook {
+                    metadata,
+                    cover: Some(book_dir.join(COVER_FILE)).filter(|c| c.is_file()),
+                    file: ebook_file(&book_dir),
+                    dir: book_dir,
+                }),
+                Err(e) => failed.push((opf, e)),
+            }
+        }
+    }
+    Ok(CalibreLibrary::new(books, failed))
+}
+
+/// Reads the library database instead, which also covers books whose OPF is stale.
+pub fn read_database(library: &Path) -> rusqlite::Result<CalibreLibrary> {
+    let db = Connection::open_with_flags(
+        library.join(DATABASE_FILE),
+        OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?;
+
+    let mut identifiers: HashMap<i64, Vec<(String, String)>> = HashMap::new();
+    let mut statement = db.prepare("SELECT book, type, val FROM identifiers")?;
+    for row in statement.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))? {
+        let (book, kind, value): (i64, String, String) = row?;
+        identifiers.entry(book).or_default().push((kind, value));
+    }
+
+    let mut files: HashMap<i64, String> = HashMap::new();
+    let mut statement = db.prepare(
+        "SELECT book, name || '.' || lower(format) FROM data
+         WHERE format IN ('EPUB', 'PDF') ORDER BY format DESC",
+    )?;
+    for row in statement.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))? {
+        let (book, name) = row?;
+        files.insert(book, name);
+    }
+
+    let query = format!(
+        "SELECT b.id, b.title, b.author_sort, b.pubdate, b.series_index, b.path, b.has_cover,
+            (SELECT group_concat(name, '{SEPARATOR}') FROM
+                (SELECT a.name FROM books_authors_link l
+                    JOIN authors a ON a.id = l.author WHERE l.book = b.id ORDER BY l.id)),
+            (SELECT s.name FROM books_series_link l
+                JOIN series s ON s.id = l.series WHERE l.book = b.id),
+            (SELECT group_concat(t.name, '{SEPARATOR}') FROM books_tags_link l
+                JOIN tags t ON t.id = l.tag WHERE l.book = b.id),
+            (SELECT p.name FROM books_publishers_link l
+                JOIN publishers p ON p.id = l.publisher WHERE l.book = b.id),
+            (SELECT r.rating FROM books_ratings_link l
+                JOIN ratings r ON r.id = l.rating WHERE l.book = b.id),
+            (SELECT g.lang_code FROM books_languages_link l
+                JOIN languages g ON g.id = l.lang_code WHERE l.book = b.id
+                ORDER BY l.item_order LIMIT 1)
+        FROM books b ORDER BY b.sort"
+    );
+    let mut statement = db.prepare(&query)?;
+    let rows = statement.query_map([], |r| {
+        let id: i64 = r.get(0)?;
+        let dir = library.join(r.get::<_, String>(5)?);
+        let mut metadata = Metadata {
+            title: r.get(1)?,
+            author_sort: r.get(2)?,
+            year: r
+                .get::<_, Option<String>>(3)?
+                .and_then(|d| d.get(..4)?.parse().ok())
+                .filter(|y| *y > 101),
+            series_index: r.get::<_, Option<f64>>(4)?.map(|i| i as f32),
+            authors: split_list(r.get(7)?)
+                .into_iter()
+                // Calibre stores commas in author names as `|`.
+                .map(|a| a.replace('|', ","))
+                .collect(),
+            series: r.get(8)?,
+            tags: split_list(r.get(9)?),
+            publisher: r.get(10)?,
+            rating: r
+                .get::<_, Option<i64>>(11)?
+                .map(|r| ((r as f32) / 2.0).round().clamp(0.0, 5.0) as u8),
+            language: r.get(12)?,
+            ..Metadata::default()
+        };
+        for (kind, value) in identifiers.remove(&id).unwrap_or_default() {
+            apply_identifier(&mut metadata, &kind, value);
+        }
+        Ok(CalibreBook {
+            dir: dir.clone(),
+            metadata,
+            cover: r
+                .get::<_, bool>(6)?
+                .then(|| dir.join(COVER_FILE))
+                .filter(|c| c.is_file()),
+            file: files
+                .get(&id)
+                .map(|name| dir.join(name))
+                .filter(|f| f.is_file()),
+        })
+    })?;
+    Ok(CalibreLibrary::new(
+        rows.collect::<rusqlite::Result<_>>()?,
+        vec![],
+    ))
+}
+
+/// Decides for every Calibre book whether it is new or matches a book in the
+/// library. Books added earlier in the plan are matched too, so a book that
+/// Calibre lists twice is only added once.
+pub fn plan(entries: &[CalibreBook], books: &[Book]) -> Vec<Action> {
+    let mut library = books.to_vec();
+    entries
+        .iter()
+        .map(|entry| {
+            match library
+                .iter()
+                .position(|b| b.matches_metadata(&entry.metadata))
+            {
+                Some(index) => {
+                    let book = &mut library[index];
+                    let changed = book.fill_missing(&entry.metadata)
+                        || (book.file.is_none() && entry.file.is_some());
+                    if changed {
+                        Action::Update(index)
+                    } else {
+                        Action::Unchanged
+                    }
+                }
+                None => match title(entry) {
+                    Some(title) => {
+                        library.push(Book::from_metadata(title, &entry.metadata));
+                        Action::Add
+                    }
+                    None => Action::Unchanged,
+                },
+            }
+        })
+        .collect()
+}
+
+pub fn apply(entries: Vec<CalibreBook>, actions: &[Action], books: &mut Vec<Book>) {
+    for (entry, action) in entries.into_iter().zip(actions) {
+        let book = match action {
+            Action::Add => {
+                let Some(title) = title(&entry) else {
+                    continue;
+                };
+                books.push(Book::from_metadata(title, &entry.metadata));
+                books.last_mut().unwrap()
+            }
+            Action::Update(index) => {
+                books[*index].fill_missing(&entry.metadata);
+                &mut books[*index]
+            }
+            Action::Unchanged => continue,
+        };
+        if book.file.is_none() {
+            book.file = entry.file.and_then(|f| LinkedFile::read(&f).ok());
+        }
+        if let Some(cover) = entry.cover {
+            copy_cover(book, &cover);
+        }
+    }
+}
+
+fn title(entry: &CalibreBook) -> Option<String> {
+    entry
+        .metadata
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+fn apply_identifier(metadata: &mut Metadata, kind: &str, value: String) {
+    match kind.to_lowercase().as_str() {
+        "isbn" => metadata.isbn = Some(value),
+        "amazon" | "asin" | "mobi-asin" => metadata.asin = Some(value),
+        "goodreads" => metadata.goodreads_id = Some(value),
+        "openlibrary" => metadata.openlibrary_id = Some(value),
+        _ => {}
+    }
+}
+
+fn split_list(list: Option<String>) -> Vec<String> {
+    list.map(|l| l.split(SEPARATOR).map(|v| v.to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn sorted_dirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// The book's EPUB, or its PDF when there is no EPUB.
+fn ebook_file(dir: &Path) -> Option<PathBuf> {
+    let files = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()));
+    let mut files = files
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("epub") || e.eq_ignore_ascii_case("pdf"))
+        })
+        .collect::<Vec<_>>();
+    files.sort_by_key(|p| {
+        !p.extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("epub"))
+    });
+    files.into_iter().next()
+}
+
+fn copy_cover(book: &Book, cover: &Path) {
+    let path = Book::default_cover_path(&book.title);
+    if path.exists() {
+        return;
+    }
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::copy(cover, &path));
+    if let Err(e) = result {
+        warn!("Cannot copy cover of '{}': {e}", book.title);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    import::{kobo, koreader},
+    metadata::Metadata,
+    model::book::{
+        highlight::Highlight, reading_position::ReadingPosition, reading_status::ReadingStatus,
+        Book,
+    },
+};
+use chrono::{Local, NaiveDate};
+use std::path::{Path, PathBuf};
+
+/// Reading state of one book as an e-reader tracked it.
+pub struct DeviceBook {
+    /// Title, authors and ISBN used to find the book in the library.
+    pub metadata: Metadata,
+    pub progress: u8,
+    pub finished: bool,
+    pub last_read: Option<NaiveDate>,
+    pub reading_seconds: u64,
+    pub highlights: Vec<Highlight>,
+}
+
+#[derive(Default)]
+pub struct DeviceImport {
+    pub books: Vec<DeviceBook>,
+    /// Databases or sidecar files that could not be read.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+#[derive(Default)]
+pub struct DeviceReport {
+    pub updated: Vec<String>,
+    pub unchanged: usize,
+    /// Titles on the device without a matching book in the library.
+    pub unmatched: Vec<String>,
+}
+
+/// Reads a Kobo database and every KOReader sidecar folder found at `path`,
+/// which is a device root, a copied folder or a `KoboReader.sqlite` file.
+pub fn read(path: &Path) -> DeviceImport {
+    let mut import = DeviceImport::default();
+    let databases = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        [
+            path.join(kobo::DATABASE_PATH),
+            path.join(kobo::DATABASE_FILE),
+        ]
+        .into_iter()
+        .filter(|p| p.is_file())
+        .collect()
+    };
+    for database in databases {
+        match kobo::read(&database) {
+            Ok(books) => import.books.extend(books),
+            Err(e) => import.failed.push((database, e.to_string())),
+        }
+    }
+    if path.is_dir() {
+        for sidecar in koreader::sidecar_files(path) {
+            match koreader::read(&sidecar) {
+                Ok(Some(book)) => import.books.push(book),
+                Ok(None) => {}
+                Err(e) => import.failed.push((sidecar, e)),
+            }
+        }
+    }
+    import
+}
+
+/// Copies the device state onto the matching library books. Progress, reading
+/// time and highlights are merged, so importing the same device twice is harmless.
+pub fn apply(device_books: Vec<DeviceBook>, books: &mut [Book]) -> DeviceReport {
+    let mut report = DeviceReport::default();
+    for device_book in device_books {
+        let title = device_book.metadata.title.clone().unwrap_or_default();
+        match find_book(books, &device_book.metadata) {
+            Some(book) => {
+                if update(book, device_book) {
+                    report.updated.push(book.title.clone());
+                } else {
+                    report.unchanged += 1;
+                }
+            }
+            None => report.unmatched.push(title),
+        }
+    }
+    report
+}
+
+/// Matches by ISBN or title and author, or by title alone when only one
+/// library book has it, since devices often spell authors differently.
+fn find_book<'a>(books: &'a mut [Book], metadata: &Metadata) -> Option<&'a mut Book> {
+    if let Some(index) = books.iter().position(|b| b.matches_metadata(metadata)) {
+        return Some(&mut books[index]);
+    }
+    let title = metadata.title.as_ref()?.to_lowercase();
+    let mut same_title = books.iter_mut().filter(|b| b.title.to_lowercase() == title);
+    let book = same_title.next()?;
+    same_title.next().is_none().then_some(book)
+}
+
+fn update(book: &mut Book, device_book: DeviceBook) -> bool {
+    let before = book.clone();
+
+    if device_book.finished {
+        // A book that was already read only gets another date when a re-read
+        // was started since, not every time the device was opened again.
+        let new_read = book.reading_status != ReadingStatus::Read
+            || book.finished_at.is_empty()
+            || book.started_at.last() > book.finished_at.last();
+        if let Some(date) = device_book.last_read.filter(|_| new_read) {
+            if book.finished_at.last().is_none_or(|last| *last < date) {
+                book.finished_at.push(date);
+            }
+        }
+        book.reading_status = ReadingStatus::Read;
+    } else if device_book.progress > 0 && book.reading_status == ReadingStatus::ToRead {
+        book.reading_status = ReadingStatus::Reading;
+        // The device only knows when the book was last opened, which is
+        // closer to the start than the day of the import.
+        book.start_reading(
+            device_book
+                .last_read
+                .unwrap_or_else(|| Local::now().date_naive()),
+        );
+    }
+
+    let progress = if device_book.finished {
+        100
+    } else {
+        device_book.progress
+    };
+    match &mut book.position {
+        Some(position) => position.progress = position.progress.max(progress),
+        None if progress > 0 => {
+            book.position = Some(ReadingPosition {
+                progress,
+                ..ReadingPosition::default()
+            })
+        }
+        None => {}
+    }
+
+    book.reading_seconds = book.reading_seconds.max(device_book.reading_seconds);
+    for highlight in device_book.highlights {
+        if !book.highlights.iter().any(|h| h.is_same(&highlight)) {
+            book.highlights.push(highlight);
+        }
+    }
+
+    book.reading_status != before.reading_status
+        || book.started_at != before.started_at
+        || book.finished_at != before.finished_at
+        || book.position != before.position
+        || book.reading_seconds != before.reading_seconds
+        || book.highlights != before.highlights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[rstest]
+    #[case(ReadingStatus::Reading, &[1], &[], &[20])]
+    #[case(ReadingStatus::Read, &[1], &[], &[20])]
+    #[case(ReadingStatus::Read, &[1], &[10], &[10])]
+    #[case(ReadingStatus::Read, &[1, 15], &[10], &[10, 20])]
+    #[case(ReadingStatus::Read, &[1], &[25], &[25])]
+    fn adds_finish_dates_for_new_reads(
+        #[case] status: ReadingStatus,
+        #[case] started: &[u32],
+        #[case] finished: &[u32],
+        #[case] expected: &[u32],
+    ) {
+        let mut book = Book {
+            reading_status: status,
+            started_at: started.iter().map(|d| date(*d)).collect(),
+            finished_at: finished.iter().map(|d| date(*d)).collect(),
+            ..Book::default()
+        };
+        let device_book = DeviceBook {
+            metadata: Metadata::default(),
+            progress: 100,
+            finished: true,
+            last_read: Some(date(20)),
+            reading_seconds: 0,
+            highlights: vec![],
+        };
+        update(&mut book, device_book);
+        assert_eq!(book.reading_status, ReadingStatus::Read);
+        assert_eq!(
+            book.finished_at,
+            expected.iter().map(|d| date(*d)).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    import::{
+        optional, parse_date, parse_isbn, parse_shelf, read_rows, split_list, ImportReport,
+        ImportedBook, Row,
+    },
+    metadata::Metadata,
+    model::book::reading_status::ReadingStatus,
+};
+use std::path::Path;
+
+/// Shelves every Goodreads account has; a book is on exactly one of them.
+const EXCLUSIVE_SHELVES: &[&str] = &["read", "currently-reading", "to-read"];
+
+/// Reads `goodreads_library_export.csv` from "My Books → Import and export".
+pub fn read(path: &Path, report: &mut ImportReport) -> csv::Result<Vec<ImportedBook>> {
+    read_rows(path, report, parse_row)
+}
+
+fn parse_row(row: &Row, warnings: &mut Vec<String>) -> Result<ImportedBook, String> {
+    let title = optional(row.get("Title")).ok_or("Title is empty")?;
+
+    let mut authors = vec![];
+    authors.extend(optional(row.get("Author")));
+    authors.extend(split_list(row.get("Additional Authors")));
+
+    let rating = match row.get("My Rating") {
+        "" | "0" => None,
+        text => match text.parse::<u8>() {
+            Ok(rating) if rating <= 5 => Some(rating),
+            _ => return Err(format!("'{text}' is not a rating from 0 to 5")),
+        },
+    };
+
+    let shelf = row.get("Exclusive Shelf");
+    let reading_status = parse_shelf(shelf).unwrap_or_else(|| {
+        warnings.push(format!("Unknown shelf '{shelf}', imported as to read"));
+        ReadingStatus::ToRead
+    });
+    let finished_at = parse_date(row.get("Date Read"))?.into_iter().collect();
+
+    let isbn = parse_isbn(unquote(row.get("ISBN13")), warnings)
+        .or_else(|| parse_isbn(unquote(row.get("ISBN")), warnings));
+    let year = [
+        row.get("Original Publication Year"),
+        row.get("Year Published"),
+    ]
+    .into_iter()
+    .find_map(|y| y.parse::<u16>().ok());
+
+    Ok(ImportedBook {
+        metadata: Metadata {
+            title: Some(title),
+            authors,
+            year,
+            pages: row.get("Number of Pages").parse().ok(),
+            publisher: optional(row.get("Publisher")),
+            tags: split_list(row.get("Bookshelves"))
+                .into_iter()
+                .filter(|s| !EXCLUSIVE_SHELVES.contains(&s.as_str()))
+                .collect(),
+            rating,
+            isbn,
+            goodreads_id: optional(row.get("Book Id")),
+            ..Metadata::default()
+        },
+        reading_status,
+        started_at: vec![],
+        finished_at,
+    })
+}
+
+/// Goodreads writes ISBNs as `="0441172717"` so spreadsheets keep leading zeros.
+fn unquote(text: &str) -> &str {
+    text.trim_start_matches('=').trim_matches('"')
+}
@@ -0,0 +1,87 @@
+use crate::{
+    import::device::DeviceBook,
+    metadata::Metadata,
+    model::book::{highlight::Highlight, isbn},
+};
+use chrono::NaiveDate;
+use rusqlite::{Connection, OpenFlags, Row};
+use std::{collections::HashMap, path::Path};
+
+pub const DATABASE_FILE: &str = "KoboReader.sqlite";
+/// Where the database sits on a mounted Kobo.
+pub const DATABASE_PATH: &str = ".kobo/KoboReader.sqlite";
+
+/// `ContentType` of whole books; chapters and other parts use other values.
+const BOOK_CONTENT_TYPE: i64 = 6;
+const READ_STATUS_FINISHED: i64 = 2;
+
+/// Reads the books with any reading activity. Columns that older firmware
+/// does not have are treated as empty.
+pub fn read(database: &Path) -> rusqlite::Result<Vec<DeviceBook>> {
+    let db = Connection::open_with_flags(database, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let mut highlights: HashMap<String, Vec<Highlight>> = HashMap::new();
+    let mut statement = db.prepare("SELECT * FROM Bookmark ORDER BY DateCreated")?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let Some(volume) = optional::<String>(row, "VolumeID") else {
+            continue;
+        };
+        let text = optional::<String>(row, "Text").unwrap_or_default();
+        let note = optional::<String>(row, "Annotation").filter(|n| !n.trim().is_empty());
+        if text.trim().is_empty() && note.is_none() {
+            // Plain bookmarks mark a page without any text.
+            continue;
+        }
+        highlights.entry(volume).or_default().push(Highlight {
+            text: text.trim().to_string(),
+            note,
+            created: optional(row, "DateCreated"),
+            // The chapter file and the position of the passage inside it.
+            location: [
+                optional::<String>(row, "ContentID"),
+                optional::<String>(row, "StartContainerPath"),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|l| !l.is_empty())
+            .reduce(|chapter, position| format!("{chapter}#{position}")),
+        });
+    }
+
+    let mut books = vec![];
+    let mut statement = db.prepare("SELECT * FROM content WHERE ContentType = ?1")?;
+    let mut rows = statement.query([BOOK_CONTENT_TYPE])?;
+    while let Some(row) = rows.next()? {
+        let id = optional::<String>(row, "ContentID").unwrap_or_default();
+        let read_status = optional::<i64>(row, "ReadStatus").unwrap_or_default();
+        let progress = optional::<i64>(row, "___PercentRead").unwrap_or_default();
+        let highlights = highlights.remove(&id).unwrap_or_default();
+        if read_status == 0 && progress == 0 && highlights.is_empty() {
+            continue;
+        }
+        books.push(DeviceBook {
+            metadata: Metadata {
+                title: optional(row, "Title"),
+                authors: optional::<String>(row, "Attribution")
+                    .map(|a| a.split(" & ").map(|a| a.trim().to_string()).collect())
+                    .unwrap_or_default(),
+                isbn: optional::<String>(row, "ISBN").filter(|i| isbn::parse(i).is_ok()),
+                ..Metadata::default()
+            },
+            progress: progress.clamp(0, 100) as u8,
+            finished: read_status == READ_STATUS_FINISHED,
+            // Stored as `2023-05-14T20:11:04Z` or with fractional seconds.
+            last_read: optional::<String>(row, "DateLastRead")
+                .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok()),
+            reading_seconds: optional::<i64>(row, "TimeSpentReading").unwrap_or_default() as u64,
+            highlights,
+        });
+    }
+    Ok(books)
+}
+
+/// The column's value, or `None` when it is NULL, of another type or missing.
+fn optional<T: rusqlite::types::FromSql>(row: &Row, column: &str) -> Option<T> {
+    row.get::<_, Option<T>>(column).ok().flatten()
+}
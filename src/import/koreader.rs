@@ -0,0 +1,166 @@
+use crate::{
+    import::{
+        device::DeviceBook,
+        lua::{self, LuaValue},
+    },
+    metadata::Metadata,
+    model::book::{highlight::Highlight, isbn},
+};
+use chrono::NaiveDate;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// `metadata.epub.lua`, `metadata.pdf.lua` and so on inside `<book>.sdr/` folders.
+pub fn sidecar_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if path.is_dir() {
+            files.extend(sidecar_files(&path));
+        } else if name.starts_with("metadata.")
+            && name.ends_with(".lua")
+            && !name.ends_with(".old.lua")
+            && path
+                .parent()
+                .and_then(|p| p.extension())
+                .is_some_and(|e| e == "sdr")
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Reads one sidecar file; books never opened past the first page are skipped.
+pub fn read(path: &Path) -> Result<Option<DeviceBook>, String> {
+    let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let table = lua::parse(&source)?;
+
+    let props = table.get("doc_props");
+    let stats = table.get("stats");
+    let text = |key: &str| {
+        [props, stats]
+            .into_iter()
+            .flatten()
+            .find_map(|t| t.get(key)?.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let title = text("title").or_else(|| title_from_path(path));
+
+    let status = table
+        .get("summary")
+        .and_then(|s| s.get("status"))
+        .and_then(|s| s.as_str());
+    let percent = table
+        .get("percent_finished")
+        .and_then(|p| p.as_f64())
+        .unwrap_or_default();
+    let highlights = highlights(&table);
+    if percent <= 0.0 && status.is_none() && highlights.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(DeviceBook {
+        metadata: Metadata {
+            title,
+            // Several authors are written one per line.
+            authors: text("authors")
+                .map(|a| a.lines().map(|l| l.trim().to_string()).collect())
+                .unwrap_or_default(),
+            isbn: text("identifiers").and_then(|ids| {
+                ids.lines()
+                    .map(|l| l.rsplit(':').next().unwrap_or(l).trim())
+                    .find(|id| isbn::parse(id).is_ok())
+                    .map(|id| id.to_string())
+            }),
+            ..Metadata::default()
+        },
+        progress: (percent * 100.0).round().clamp(0.0, 100.0) as u8,
+        finished: status == Some("complete"),
+        last_read: table
+            .get("summary")
+            .and_then(|s| s.get("modified"))
+            .and_then(|m| m.as_str())
+            .and_then(|m| NaiveDate::parse_from_str(m, "%Y-%m-%d").ok()),
+        reading_seconds: stats
+            .and_then(|s| s.get("total_time_in_sec"))
+            .and_then(|t| t.as_f64())
+            .unwrap_or_default()
+            .max(0.0) as u64,
+        highlights,
+    }))
+}
+
+/// Recent KOReader versions keep an `annotations` list; older ones a
+/// `highlight` table by page.
+fn highlights(table: &LuaValue) -> Vec<Highlight> {
+    let entries: Vec<&LuaValue> = match table.get("annotations") {
+        Some(annotations) => annotations.values(),
+        None => table
+            .get("highlight")
+            .map(|pages| {
+                pages
+                    .values()
+                    .into_iter()
+                    .flat_map(|p| p.values())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let text = entry.get("text")?.as_str()?.trim().to_string();
+            let note = entry
+                .get("note")
+                .and_then(|n| n.as_str())
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty());
+            (!text.is_empty() || note.is_some()).then(|| Highlight {
+                text,
+                note,
+                created: entry
+                    .get("datetime")
+                    .and_then(|d| d.as_str())
+                    .map(|d| d.to_string()),
+                location: location(entry),
+            })
+        })
+        .collect()
+}
+
+/// The chapter and the position in it: an XPointer in EPUBs, a page in PDFs.
+fn location(entry: &LuaValue) -> Option<String> {
+    let chapter = entry.get("chapter").and_then(|c| c.as_str());
+    let position = entry
+        .get("pos0")
+        .and_then(|p| p.as_str())
+        .map(|p| p.to_string())
+        .or_else(|| {
+            entry.get("page").map(|p| match p.as_f64() {
+                Some(page) => format!("page {page}"),
+                None => p.as_str().unwrap_or_default().to_string(),
+            })
+        });
+    [chapter.map(str::to_string), position]
+        .into_iter()
+        .flatten()
+        .filter(|l| !l.is_empty())
+        .reduce(|chapter, position| format!("{chapter}, {position}"))
+}
+
+/// `Dune.sdr/metadata.epub.lua` belongs to `Dune.epub`.
+fn title_from_path(path: &Path) -> Option<String> {
+    let dir = path.parent()?;
+    Some(dir.file_stem()?.to_string_lossy().to_string())
+}
@@ -0,0 +1,430 @@
+/// A value of the Lua subset KOReader writes its sidecar files in: a single
+/// `return { ... }` of nested tables, strings, numbers and booleans.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LuaValue {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Table(Vec<(LuaValue, LuaValue)>),
+}
+
+impl LuaValue {
+    /// The value stored under a string key.
+    pub fn get(&self, key: &str) -> Option<&LuaValue> {
+        match self {
+            LuaValue::Table(entries) => entries
+                .iter()
+                .find(|(k, _)| matches!(k, LuaValue::String(s) if s == key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            LuaValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            LuaValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Values of a table in key order, for the array-like tables KOReader writes.
+    pub fn values(&self) -> Vec<&LuaValue> {
+        match self {
+            LuaValue::Table(entries) => {
+                let mut entries = entries.iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| {
+                    a.as_f64()
+                        .unwrap_or(f64::MAX)
+                        .total_cmp(&b.as_f64().unwrap_or(f64::MAX))
+                });
+                entries.into_iter().map(|(_, v)| v).collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<LuaValue, String> {
+    let mut parser = Parser {
+        chars: source.chars().collect(),
+        pos: 0,
+    };
+    parser.skip_space();
+    if parser.source_starts_with("return") {
+        parser.pos += "return".len();
+    }
+    let value = parser.value()?;
+    parser.skip_space();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("unexpected text after the table"));
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn value(&mut self) -> Result<LuaValue, String> {
+        self.skip_space();
+        match self.peek() {
+            Some('{') => self.table(),
+            Some('"' | '\'') => Ok(LuaValue::String(self.string()?)),
+            Some('[') if self.long_bracket_level().is_some() => {
+                Ok(LuaValue::String(self.long_string()?))
+            }
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_alphabetic() => match self.identifier().as_str() {
+                "true" => Ok(LuaValue::Bool(true)),
+                "false" => Ok(LuaValue::Bool(false)),
+                "nil" => Ok(LuaValue::Nil),
+                other => Err(self.error(&format!("unknown value '{other}'"))),
+            },
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn table(&mut self) -> Result<LuaValue, String> {
+        self.pos += 1;
+        let mut entries = vec![];
+        let mut next_index = 1.0;
+        loop {
+            self.skip_space();
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(LuaValue::Table(entries));
+                }
+                Some(',' | ';') => {
+                    self.pos += 1;
+                    continue;
+                }
+                None => return Err(self.error("unterminated table")),
+                _ => {}
+            }
+            let key = if self.peek() == Some('[') && self.long_bracket_level().is_none() {
+                self.pos += 1;
+                let key = self.value()?;
+                self.skip_space();
+                self.expect(']')?;
+                self.skip_space();
+                self.expect('=')?;
+                key
+            } else if self.peek().is_some_and(|c| c.is_alphabetic() || c == '_')
+                && self.is_named_field()
+            {
+                let key = LuaValue::String(self.identifier());
+                self.skip_space();
+                self.expect('=')?;
+                key
+            } else {
+                let key = LuaValue::Number(next_index);
+                next_index += 1.0;
+                key
+            };
+            let value = self.value()?;
+            entries.push((key, value));
+        }
+    }
+
+    /// Whether an identifier at the cursor is followed by `=` rather than being a value.
+    fn is_named_field(&self) -> bool {
+        let mut pos = self.pos;
+        while pos < self.chars.len()
+            && (self.chars[pos].is_alphanumeric() || self.chars[pos] == '_')
+        {
+            pos += 1;
+        }
+        while pos < self.chars.len() && self.chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        self.chars.get(pos) == Some(&'=') && self.chars.get(pos + 1) != Some(&'=')
+    }
+
+    /// Decimal escapes such as `\195\169` are bytes of UTF-8 text.
+    fn string(&mut self) -> Result<String, String> {
+        let quote = self.chars[self.pos];
+        self.pos += 1;
+        let mut bytes = vec![];
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(String::from_utf8_lossy(&bytes).to_string()),
+                '\\' => {
+                    let Some(escaped) = self.peek() else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        'n' => bytes.push(b'\n'),
+                        't' => bytes.push(b'\t'),
+                        'r' => bytes.push(b'\r'),
+                        // A backslash before a line break continues the string on the next line.
+                        '\n' => bytes.push(b'\n'),
+                        d if d.is_ascii_digit() => {
+                            let mut code = d.to_digit(10).unwrap_or_default();
+                            for _ in 0..2 {
+                                match self.peek().and_then(|c| c.to_digit(10)) {
+                                    Some(digit) => {
+                                        code = code * 10 + digit;
+                                        self.pos += 1;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            bytes.push(code.min(255) as u8);
+                        }
+                        other => push_char(&mut bytes, other),
+                    }
+                }
+                c => push_char(&mut bytes, c),
+            }
+        }
+    }
+
+    /// The number of `=` in a `[[` or `[==[` at the cursor.
+    fn long_bracket_level(&self) -> Option<usize> {
+        let level = self.chars[self.pos + 1..]
+            .iter()
+            .take_while(|c| **c == '=')
+            .count();
+        (self.chars.get(self.pos + 1 + level) == Some(&'[')).then_some(level)
+    }
+
+    /// `[[...]]` strings, taken as written without escapes. A line break right
+    /// after the opening bracket is not part of the string.
+    fn long_string(&mut self) -> Result<String, String> {
+        let Some(level) = self.long_bracket_level() else {
+            return Err(self.error("expected a long string"));
+        };
+        self.pos += level + 2;
+        if self.source_starts_with("\r\n") {
+            self.pos += 2;
+        } else if self.peek() == Some('\n') {
+            self.pos += 1;
+        }
+        let close = format!("]{}]", "=".repeat(level));
+        let start = self.pos;
+        while !self.source_starts_with(&close) {
+            if self.peek().is_none() {
+                return Err(self.error("unterminated long string"));
+            }
+            self.pos += 1;
+        }
+        let text = self.chars[start..self.pos].iter().collect();
+        self.pos += close.len();
+        Ok(text)
+    }
+
+    fn number(&mut self) -> Result<LuaValue, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))
+        {
+            self.pos += 1;
+        }
+        let text = self.chars[start..self.pos].iter().collect::<String>();
+        text.parse()
+            .map(LuaValue::Number)
+            .map_err(|_| self.error(&format!("'{text}' is not a number")))
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Skips whitespace and `--` comments, including `--[[ ... ]]` ones.
+    fn skip_space(&mut self) {
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            if self.source_starts_with("--") {
+                self.pos += 2;
+                if self.peek() == Some('[') && self.long_bracket_level().is_some() {
+                    // An unterminated comment runs to the end of the file.
+                    if self.long_string().is_err() {
+                        self.pos = self.chars.len();
+                    }
+                    continue;
+                }
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{expected}'")))
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn source_starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.chars[..self.pos.min(self.chars.len())]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count()
+            + 1;
+        format!("line {line}: {message}")
+    }
+}
+
+fn push_char(bytes: &mut Vec<u8>, c: char) {
+    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn string(s: &str) -> LuaValue {
+        LuaValue::String(s.to_string())
+    }
+
+    #[test]
+    fn parses_nested_tables() {
+        let value = parse(
+            r#"-- we can read Lua syntax here!
+return {
+    ["doc_props"] = {
+        ["title"] = "Dune",
+        authors = "Frank Herbert",
+    },
+    ["annotations"] = {
+        [1] = { ["text"] = "Fear is the mind-killer.", ["page"] = 12 },
+        [2] = { ["text"] = "The spice must flow.", ["page"] = 200 },
+    },
+    ["summary"] = { status = "complete" },
+}"#,
+        )
+        .unwrap();
+        let props = value.get("doc_props").unwrap();
+        assert_eq!(props.get("title"), Some(&string("Dune")));
+        assert_eq!(props.get("authors"), Some(&string("Frank Herbert")));
+        let annotations = value.get("annotations").unwrap().values();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(
+            annotations[1].get("text"),
+            Some(&string("The spice must flow."))
+        );
+        assert_eq!(annotations[1].get("page"), Some(&LuaValue::Number(200.0)));
+        assert_eq!(
+            value.get("summary").unwrap().get("status"),
+            Some(&string("complete"))
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{ ["key"] = 1 }"#, string("key"))]
+    #[case("{ [1] = 1 }", LuaValue::Number(1.0))]
+    #[case("{ key = 1 }", string("key"))]
+    #[case("{ 1 }", LuaValue::Number(1.0))]
+    #[case("{ [ [[key]] ] = 1 }", string("key"))]
+    fn parses_keys(#[case] source: &str, #[case] key: LuaValue) {
+        assert_eq!(
+            parse(source),
+            Ok(LuaValue::Table(vec![(key, LuaValue::Number(1.0))]))
+        );
+    }
+
+    #[test]
+    fn numbers_positional_values_in_order() {
+        assert_eq!(
+            parse("{ 'a', x = true, 'b' }").unwrap().values(),
+            vec![&string("a"), &string("b"), &LuaValue::Bool(true)]
+        );
+    }
+
+    #[rstest]
+    #[case(r#""say \"hi\"""#, "say \"hi\"")]
+    #[case(r#"'it\'s'"#, "it's")]
+    #[case(r#""a\nb\tc""#, "a\nb\tc")]
+    #[case(r#""back\\slash""#, "back\\slash")]
+    #[case(r#""caf\195\169""#, "café")]
+    #[case("\"one\\\ntwo\"", "one\ntwo")]
+    #[case("[[long]]", "long")]
+    #[case("[[\nfirst\nsecond]]", "first\nsecond")]
+    #[case("[==[a ]] b]==]", "a ]] b")]
+    #[case(r#"[[no \n escapes]]"#, r#"no \n escapes"#)]
+    fn parses_strings(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(parse(source), Ok(string(expected)));
+    }
+
+    #[rstest]
+    #[case("42", 42.0)]
+    #[case("-7", -7.0)]
+    #[case("0.25", 0.25)]
+    #[case("-3.5", -3.5)]
+    #[case("1e3", 1000.0)]
+    #[case("-2.5e-2", -0.025)]
+    fn parses_numbers(#[case] source: &str, #[case] expected: f64) {
+        assert_eq!(parse(source), Ok(LuaValue::Number(expected)));
+    }
+
+    #[test]
+    fn skips_comments() {
+        assert_eq!(
+            parse("--[[ a\nlong comment ]] return { -- note\n true }"),
+            Ok(LuaValue::Table(vec![(
+                LuaValue::Number(1.0),
+                LuaValue::Bool(true)
+            )]))
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("return")]
+    #[case("return {")]
+    #[case("return { [\"title\"]")]
+    #[case("return { [\"title\"] =")]
+    #[case("return { title = \"Dune")]
+    #[case("return { \"Dune\\")]
+    #[case("return { [[Dune")]
+    #[case("return { [=[Dune]]")]
+    #[case("return { [")]
+    #[case("return { -")]
+    #[case("return { 1, 2")]
+    #[case("return { a = { b = { c = 1 }")]
+    #[case("return {} }")]
+    #[case("--[[ return {}")]
+    fn rejects_truncated_input(#[case] source: &str) {
+        assert!(parse(source).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+pub mod bibtex;
+pub mod calibre;
+pub mod device;
+pub mod goodreads;
+mod kobo;
+mod koreader;
+mod lua;
+pub mod ris;
+pub mod storygraph;
+
+use crate::{
+    metadata::Metadata,
+    model::book::{isbn, reading_status::ReadingStatus, Book},
+};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use csv::StringRecord;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ImportFormat {
+    Goodreads,
+    Storygraph,
+    /// A `.bib` file from a reference manager; entries become to-read books.
+    Bibtex,
+    /// A `.ris` file from a reference manager; records become to-read books.
+    Ris,
+}
+
+/// A book read from another service's export, with the reading history bookie keeps.
+pub struct ImportedBook {
+    pub metadata: Metadata,
+    pub reading_status: ReadingStatus,
+    pub started_at: Vec<NaiveDate>,
+    pub finished_at: Vec<NaiveDate>,
+}
+
+/// A row that was skipped, or imported with some of its values dropped.
+pub struct RowProblem {
+    /// Line in the file, counting the header as line 1.
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub struct ImportReport {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub invalid: Vec<RowProblem>,
+    pub warnings: Vec<RowProblem>,
+}
+
+/// Reads an export file and merges it into `books`. Bad rows end up in the
+/// report instead of failing the whole import.
+pub fn import(
+    format: ImportFormat,
+    path: &Path,
+    books: &mut Vec<Book>,
+) -> csv::Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let imported = match format {
+        ImportFormat::Goodreads => goodreads::read(path, &mut report)?,
+        ImportFormat::Storygraph => storygraph::read(path, &mut report)?,
+        ImportFormat::Bibtex => bibtex::read(path, &mut report)?,
+        ImportFormat::Ris => ris::read(path, &mut report)?,
+    };
+    for book in imported {
+        merge(book, books, &mut report);
+    }
+    Ok(report)
+}
+
+/// Adds the book, or completes the matching one: empty fields are filled,
+/// start and finish dates are combined and a more advanced reading status wins.
+fn merge(imported: ImportedBook, books: &mut Vec<Book>, report: &mut ImportReport) {
+    let Some(book) = books
+        .iter_mut()
+        .find(|b| b.matches_metadata(&imported.metadata))
+    else {
+        let title = imported.metadata.title.clone().unwrap_or_default();
+        let mut book = Book::from_metadata(title, &imported.metadata);
+        book.reading_status = imported.reading_status;
+        book.started_at = imported.started_at;
+        book.finished_at = imported.finished_at;
+        books.push(book);
+        report.added += 1;
+        return;
+    };
+
+    let mut changed = book.fill_missing(&imported.metadata);
+    if imported.reading_status.index() > book.reading_status.index() {
+        book.reading_status = imported.reading_status;
+        changed = true;
+    }
+    for date in imported.started_at {
+        if !book.started_at.contains(&date) {
+            book.started_at.push(date);
+            changed = true;
+        }
+    }
+    book.started_at.sort();
+    for date in imported.finished_at {
+        if !book.finished_at.contains(&date) {
+            book.finished_at.push(date);
+            changed = true;
+        }
+    }
+    book.finished_at.sort();
+    if changed {
+        report.updated += 1;
+    } else {
+        report.unchanged += 1;
+    }
+}
+
+/// A CSV record whose values are looked up by column name.
+pub struct Row<'a> {
+    headers: &'a StringRecord,
+    record: &'a StringRecord,
+}
+
+impl Row<'_> {
+    /// The trimmed value, empty when the column is missing.
+    pub fn get(&self, column: &str) -> &str {
+        self.headers
+            .iter()
+            .position(|h| h.trim() == column)
+            .and_then(|i| self.record.get(i))
+            .unwrap_or_default()
+            .trim()
+    }
+}
+
+/// Parses every row of a CSV file with a header line. Rows the parser rejects
+/// are recorded as invalid, and its warnings are recorded with the row's line.
+fn read_rows(
+    path: &Path,
+    report: &mut ImportReport,
+    parse: impl Fn(&Row, &mut Vec<String>) -> Result<ImportedBook, String>,
+) -> csv::Result<Vec<ImportedBook>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let mut books = vec![];
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line() as usize);
+                report.invalid.push(RowProblem {
+                    line,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |p| p.line() as usize);
+        let row = Row {
+            headers: &headers,
+            record: &record,
+        };
+        let mut warnings = vec![];
+        match parse(&row, &mut warnings) {
+            Ok(book) => {
+                books.push(book);
+                report.warnings.extend(
+                    warnings
+                        .into_iter()
+                        .map(|message| RowProblem { line, message }),
+                );
+            }
+            Err(message) => report.invalid.push(RowProblem { line, message }),
+        }
+    }
+    Ok(books)
+}
+
+/// Both services write dates as `2023/05/14`.
+fn parse_date(text: &str) -> Result<Option<NaiveDate>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(text, "%Y/%m/%d")
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y-%m-%d"))
+        .map(Some)
+        .map_err(|_| format!("'{text}' is not a date"))
+}
+
+fn parse_shelf(shelf: &str) -> Option<ReadingStatus> {
+    match shelf.trim() {
+        "read" => Some(ReadingStatus::Read),
+        "currently-reading" => Some(ReadingStatus::Reading),
+        "to-read" | "did-not-finish" => Some(ReadingStatus::ToRead),
+        _ => None,
+    }
+}
+
+/// The ISBN if it is valid; a wrong one is reported and dropped.
+fn parse_isbn(text: &str, warnings: &mut Vec<String>) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    match isbn::parse(text) {
+        Ok((isbn_13, _)) => Some(isbn_13),
+        Err(e) => {
+            warnings.push(e);
+            None
+        }
+    }
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn optional(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
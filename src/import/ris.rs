@@ -0,0 +1,140 @@
+use crate::{
+    import::{optional, parse_isbn, ImportReport, ImportedBook, RowProblem},
+    metadata::Metadata,
+    model::book::{
+        author_name::{author_sort, AuthorName},
+        reading_status::ReadingStatus,
+    },
+};
+use std::{fs, path::Path};
+
+/// Reads the records of a `.ris` file as to-read books. Each record runs from
+/// `TY  - ` to `ER  - `, with one `TAG  - value` per line.
+pub fn read(path: &Path, report: &mut ImportReport) -> csv::Result<Vec<ImportedBook>> {
+    let source = fs::read_to_string(path)?;
+    let mut books = vec![];
+    let mut record: Option<(usize, Vec<(String, String)>)> = None;
+    for (index, line) in source.lines().enumerate() {
+        let Some((tag, value)) = parse_line(line) else {
+            continue;
+        };
+        match tag {
+            "TY" => record = Some((index + 1, vec![])),
+            "ER" => {
+                if let Some((line, tags)) = record.take() {
+                    let mut warnings = vec![];
+                    match to_book(&tags, &mut warnings) {
+                        Ok(book) => {
+                            books.push(book);
+                            report.warnings.extend(
+                                warnings
+                                    .into_iter()
+                                    .map(|message| RowProblem { line, message }),
+                            );
+                        }
+                        Err(message) => report.invalid.push(RowProblem { line, message }),
+                    }
+                }
+            }
+            _ => {
+                if let Some((_, tags)) = &mut record {
+                    tags.push((tag.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+    if let Some((line, _)) = record {
+        report.invalid.push(RowProblem {
+            line,
+            message: "The record has no 'ER' line".to_string(),
+        });
+    }
+    Ok(books)
+}
+
+/// `AU  - Herbert, Frank`; some exporters drop the space before the dash.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start_matches('\u{feff}');
+    let tag = line.get(..2)?;
+    if !tag
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    {
+        return None;
+    }
+    let value = line[2..].trim_start().strip_prefix('-')?;
+    Some((tag, value.trim()))
+}
+
+fn to_book(tags: &[(String, String)], warnings: &mut Vec<String>) -> Result<ImportedBook, String> {
+    let first = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            tags.iter()
+                .find(|(tag, _)| tag == name)
+                .and_then(|(_, value)| optional(value))
+        })
+    };
+    let all = |names: &[&str]| {
+        tags.iter()
+            .filter(|(tag, _)| names.contains(&tag.as_str()))
+            .filter_map(|(_, value)| optional(value))
+            .collect::<Vec<_>>()
+    };
+
+    let title = first(&["TI", "T1", "BT", "CT"]).ok_or("The record has no title")?;
+    let mut authors = all(&["AU", "A1"]);
+    if authors.is_empty() {
+        authors = all(&["A2", "ED"]);
+    }
+    // Dates look like `1965`, `1965///` or `1965/08/01`.
+    let year = first(&["PY", "Y1", "DA"])
+        .and_then(|date| date.get(..4).and_then(|y| y.parse::<u16>().ok()));
+    // SN holds an ISSN for journals, so only values that read as an ISBN are kept.
+    let isbn = first(&["SN"]).and_then(|sn| {
+        let isbn = sn.split_whitespace().next().unwrap_or_default().to_string();
+        let digits = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).count();
+        if digits == 10 || digits == 13 {
+            parse_isbn(&isbn, warnings)
+        } else {
+            None
+        }
+    });
+    Ok(ImportedBook {
+        metadata: Metadata {
+            title: Some(title),
+            authors: authors
+                .iter()
+                .map(|a| AuthorName::parse(a).natural())
+                .collect(),
+            author_sort: author_sort(&authors),
+            year,
+            publisher: first(&["PB"]),
+            isbn,
+            ..Metadata::default()
+        },
+        reading_status: ReadingStatus::ToRead,
+        started_at: vec![],
+        finished_at: vec![],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("AU  - Herbert, Frank", Some(("AU", "Herbert, Frank")))]
+    #[case("AU - Herbert, Frank", Some(("AU", "Herbert, Frank")))]
+    #[case("AU- Herbert, Frank", Some(("AU", "Herbert, Frank")))]
+    #[case("AU-Herbert, Frank", Some(("AU", "Herbert, Frank")))]
+    #[case("\u{feff}TY  - BOOK", Some(("TY", "BOOK")))]
+    #[case("ER  -", Some(("ER", "")))]
+    #[case("T1  - Dune - Part One", Some(("T1", "Dune - Part One")))]
+    #[case("continued title text", None)]
+    #[case("au  - lowercase", None)]
+    #[case("A", None)]
+    fn parses_lines(#[case] line: &str, #[case] expected: Option<(&str, &str)>) {
+        assert_eq!(parse_line(line), expected);
+    }
+}
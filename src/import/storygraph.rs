@@ -0,0 +1,88 @@
+use crate::{
+    import::{
+        optional, parse_date, parse_isbn, parse_shelf, read_rows, split_list, ImportReport,
+        ImportedBook, Row,
+    },
+    metadata::Metadata,
+    model::book::{isbn, reading_status::ReadingStatus},
+};
+use std::path::Path;
+
+/// Tag kept on books StoryGraph marks as not finished, since bookie has no such status.
+const DID_NOT_FINISH_TAG: &str = "did-not-finish";
+
+/// Reads the CSV from StoryGraph's "Manage Account → Export StoryGraph Library".
+pub fn read(path: &Path, report: &mut ImportReport) -> csv::Result<Vec<ImportedBook>> {
+    read_rows(path, report, parse_row)
+}
+
+fn parse_row(row: &Row, warnings: &mut Vec<String>) -> Result<ImportedBook, String> {
+    let title = optional(row.get("Title")).ok_or("Title is empty")?;
+
+    let rating = match row.get("Star Rating") {
+        "" => None,
+        text => match text.parse::<f32>() {
+            Ok(rating) if (0.0..=5.0).contains(&rating) => Some(rating.round() as u8),
+            _ => return Err(format!("'{text}' is not a rating from 0 to 5")),
+        },
+    };
+
+    let mut tags = split_list(row.get("Tags"));
+    let status = row.get("Read Status");
+    let reading_status = match status {
+        "paused" => ReadingStatus::Reading,
+        _ => parse_shelf(status).unwrap_or_else(|| {
+            warnings.push(format!(
+                "Unknown read status '{status}', imported as to read"
+            ));
+            ReadingStatus::ToRead
+        }),
+    };
+    if status == DID_NOT_FINISH_TAG {
+        tags.push(DID_NOT_FINISH_TAG.to_string());
+    }
+
+    // "2023/01/05-2023/01/20, 2024/03/01-2024/03/09", or only the end dates.
+    let mut started_at = vec![];
+    let mut finished_at = vec![];
+    for range in split_list(row.get("Dates Read")) {
+        // The dash between the dates, not one inside an ISO date.
+        let (start, end) = range
+            .match_indices('-')
+            .map(|(i, _)| (&range[..i], &range[i + 1..]))
+            .find(|(start, end)| {
+                parse_date(start).is_ok_and(|d| d.is_some()) && parse_date(end).is_ok()
+            })
+            .map_or(("", range.as_str()), |(start, end)| (start, end));
+        started_at.extend(parse_date(start)?);
+        finished_at.extend(parse_date(end)?);
+    }
+    if finished_at.is_empty() {
+        finished_at.extend(parse_date(row.get("Last Date Read"))?);
+    }
+    if reading_status != ReadingStatus::Read {
+        finished_at.clear();
+    }
+
+    // The column holds a StoryGraph id for books without an ISBN.
+    let uid = row.get("ISBN/UID");
+    let isbn = if isbn::parse(uid).is_ok() {
+        parse_isbn(uid, warnings)
+    } else {
+        None
+    };
+
+    Ok(ImportedBook {
+        metadata: Metadata {
+            title: Some(title),
+            authors: split_list(row.get("Authors")),
+            tags,
+            rating,
+            isbn,
+            ..Metadata::default()
+        },
+        reading_status,
+        started_at,
+        finished_at,
+    })
+}
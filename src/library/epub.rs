@@ -0,0 +1,339 @@
+use crate::{library::html, metadata::Metadata};
+use percent_encoding::percent_decode_str;
+use roxmltree::{Document, Node};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+use zip::ZipArchive;
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+struct ManifestItem {
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+pub struct Chapter {
+    pub title: String,
+    pub paragraphs: Vec<String>,
+}
+
+/// An EPUB file opened for reading its package document (OPF).
+pub struct Epub {
+    archive: ZipArchive<File>,
+    pub metadata: Metadata,
+    manifest: HashMap<String, ManifestItem>,
+    cover_id: Option<String>,
+    /// Archive paths of the documents in reading order.
+    spine: Vec<String>,
+    toc_id: Option<String>,
+}
+
+impl Epub {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+        let container = read_entry(&mut archive, CONTAINER_PATH)?;
+        let opf_path = Document::parse(&container)
+            .map_err(|e| e.to_string())?
+            .descendants()
+            .find(|n| n.has_tag_name("rootfile"))
+            .and_then(|n| n.attribute("full-path"))
+            .map(|p| p.to_string())
+            .ok_or("container.xml names no package document")?;
+        let opf = read_entry(&mut archive, &opf_path)?;
+        let doc = Document::parse(&opf).map_err(|e| e.to_string())?;
+
+        let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let manifest = doc
+            .descendants()
+            .filter(|n| n.tag_name().name() == "item")
+            .filter_map(|n| {
+                let item = ManifestItem {
+                    href: resolve_href(base, n.attribute("href")?),
+                    media_type: n.attribute("media-type").unwrap_or_default().to_string(),
+                    properties: n.attribute("properties").unwrap_or_default().to_string(),
+                };
+                Some((n.attribute("id")?.to_string(), item))
+            })
+            .collect::<HashMap<_, _>>();
+        // EPUB 3 marks the cover in the manifest, EPUB 2 in a `<meta name="cover">`.
+        let cover_id = manifest
+            .iter()
+            .find(|(_, item)| {
+                item.properties
+                    .split_whitespace()
+                    .any(|p| p == "cover-image")
+            })
+            .map(|(id, _)| id.clone())
+            .or_else(|| {
+                doc.descendants()
+                    .find(|n| n.tag_name().name() == "meta" && n.attribute("name") == Some("cover"))
+                    .and_then(|n| n.attribute("content"))
+                    .map(|id| id.to_string())
+            });
+
+        let spine = doc
+            .descendants()
+            .filter(|n| n.tag_name().name() == "itemref" && n.attribute("linear") != Some("no"))
+            .filter_map(|n| manifest.get(n.attribute("idref")?))
+            .map(|item| item.href.clone())
+            .collect();
+        // EPUB 3 navigation document, otherwise the EPUB 2 NCX named by the spine.
+        let toc_id = manifest
+            .iter()
+            .find(|(_, item)| item.properties.split_whitespace().any(|p| p == "nav"))
+            .map(|(id, _)| id.clone())
+            .or_else(|| {
+                doc.descendants()
+                    .find(|n| n.tag_name().name() == "spine")
+                    .and_then(|n| n.attribute("toc"))
+                    .map(|id| id.to_string())
+            });
+
+        Ok(Self {
+            archive,
+            metadata: read_metadata(&doc),
+            manifest,
+            cover_id,
+            spine,
+            toc_id,
+        })
+    }
+
+    /// The text of every spine document that has any, titled from the table of contents.
+    pub fn chapters(&mut self) -> Result<Vec<Chapter>, String> {
+        let titles = self.toc_titles();
+        let mut chapters = vec![];
+        for href in self.spine.clone() {
+            let paragraphs = html::paragraphs(&read_entry(&mut self.archive, &href)?);
+            if paragraphs.is_empty() {
+                continue;
+            }
+            let title = titles
+                .get(&href)
+                .cloned()
+                .unwrap_or_else(|| format!("Section {}", chapters.len() + 1));
+            chapters.push(Chapter { title, paragraphs });
+        }
+        Ok(chapters)
+    }
+
+    /// The first table of contents label pointing into each document.
+    fn toc_titles(&mut self) -> HashMap<String, String> {
+        let mut titles = HashMap::new();
+        let Some(item) = self.toc_id.as_ref().and_then(|id| self.manifest.get(id)) else {
+            return titles;
+        };
+        let href = item.href.clone();
+        let base = href.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let Ok(source) = read_entry(&mut self.archive, &href) else {
+            return titles;
+        };
+        // Navigation documents are XHTML and may use entities XML does not know.
+        let Ok(doc) = Document::parse(&source) else {
+            return titles;
+        };
+        let entries = doc.descendants().filter_map(|n| match n.tag_name().name() {
+            "navPoint" => {
+                let label = n.descendants().find(|c| c.tag_name().name() == "text")?;
+                let src = n
+                    .children()
+                    .find(|c| c.tag_name().name() == "content")?
+                    .attribute("src")?;
+                Some((all_text(label), src))
+            }
+            "a" if n.ancestors().any(|a| a.tag_name().name() == "nav") => {
+                Some((all_text(n), n.attribute("href")?))
+            }
+            _ => None,
+        });
+        for (label, src) in entries {
+            if !label.is_empty() {
+                titles.entry(resolve_href(base, src)).or_insert(label);
+            }
+        }
+        titles
+    }
+
+    /// The embedded cover image, if the package names one.
+    pub fn cover(&mut self) -> Option<Vec<u8>> {
+        let item = self.manifest.get(self.cover_id.as_ref()?)?;
+        if !item.media_type.starts_with("image/") {
+            return None;
+        }
+        let href = item.href.clone();
+        read_bytes(&mut self.archive, &href).ok()
+    }
+}
+
+/// Reads a standalone package document, such as the `metadata.opf` Calibre
+/// keeps next to every book.
+pub fn read_opf(source: &str) -> Result<Metadata, String> {
+    Ok(read_metadata(
+        &Document::parse(source).map_err(|e| e.to_string())?,
+    ))
+}
+
+fn read_metadata(doc: &Document) -> Metadata {
+    let Some(metadata) = doc
+        .descendants()
+        .find(|n| n.tag_name().name() == "metadata")
+    else {
+        return Metadata::default();
+    };
+    let dc = |name: &'static str| {
+        metadata
+            .children()
+            .filter(move |n| n.is_element() && n.tag_name().name() == name)
+    };
+
+    let mut result = Metadata {
+        title: dc("title").find_map(text),
+        authors: dc("creator")
+            .filter(|n| is_author(n))
+            .filter_map(|n| text(n))
+            .collect(),
+        author_sort: dc("creator")
+            .filter(|n| is_author(n))
+            .filter_map(|n| attribute(n, "file-as"))
+            .reduce(|a, b| format!("{a} & {b}")),
+        // Calibre writes 0101-01-01 for an unknown date.
+        year: dc("date").find_map(|n| {
+            text(n)
+                .and_then(|d| d.get(..4)?.parse().ok())
+                .filter(|y| *y > 101)
+        }),
+        language: dc("language").find_map(text),
+        tags: dc("subject").filter_map(text).collect(),
+        publisher: dc("publisher").find_map(text),
+        ..Metadata::default()
+    };
+    for meta in dc("meta") {
+        let Some(content) = meta.attribute("content") else {
+            continue;
+        };
+        match meta.attribute("name") {
+            Some("calibre:series") => result.series = Some(content.to_string()),
+            Some("calibre:series_index") => result.series_index = content.parse().ok(),
+            // Calibre rates from 0 to 10, in half stars.
+            Some("calibre:rating") => {
+                result.rating = content
+                    .parse::<f32>()
+                    .ok()
+                    .map(|r| (r / 2.0).round().clamp(0.0, 5.0) as u8)
+            }
+            _ => {}
+        }
+    }
+    for identifier in dc("identifier") {
+        let Some(value) = text(identifier) else {
+            continue;
+        };
+        let scheme = attribute(identifier, "scheme")
+            .unwrap_or_default()
+            .to_lowercase();
+        let lower = value.to_lowercase();
+        if let Some(isbn) = lower
+            .strip_prefix("urn:isbn:")
+            .or(lower.strip_prefix("isbn:"))
+        {
+            result.isbn.get_or_insert(isbn.to_string());
+        } else if scheme == "isbn" {
+            result.isbn.get_or_insert(value);
+        } else if scheme == "amazon" || scheme == "mobi-asin" || scheme == "asin" {
+            result.asin.get_or_insert(value);
+        } else if scheme == "goodreads" {
+            result.goodreads_id.get_or_insert(value);
+        } else if scheme == "openlibrary" {
+            result.openlibrary_id.get_or_insert(value);
+        }
+    }
+    result
+}
+
+/// Creators without a role, or with the `aut` role, are authors; editors,
+/// illustrators and translators are not.
+fn is_author(node: &Node) -> bool {
+    attribute(*node, "role").is_none_or(|role| role == "aut")
+}
+
+/// Looks up an attribute by local name, as OPF 2 puts them in the `opf` namespace.
+fn attribute(node: Node, name: &str) -> Option<String> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value().to_string())
+}
+
+fn text(node: Node) -> Option<String> {
+    let text = node
+        .text()?
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+fn all_text(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Manifest hrefs are relative to the package document and may be URL-encoded.
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let href = percent_decode_str(href).decode_utf8_lossy();
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<String, String> {
+    String::from_utf8(read_bytes(archive, name)?).map_err(|e| format!("{name}: {e}"))
+}
+
+fn read_bytes(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive.by_name(name).map_err(|e| format!("{name}: {e}"))?;
+    let mut bytes = vec![];
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e: io::Error| format!("{name}: {e}"))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("OEBPS/", "text/chapter1.xhtml", "OEBPS/text/chapter1.xhtml")]
+    #[case("OEBPS/text/", "../images/cover.jpg", "OEBPS/images/cover.jpg")]
+    #[case("", "Chapter%201.xhtml#start", "Chapter 1.xhtml")]
+    #[case(
+        "OEBPS/",
+        "Caf%C3%A9%20%26%20Cr%C3%A8me.xhtml",
+        "OEBPS/Café & Crème.xhtml"
+    )]
+    fn resolves_hrefs(#[case] base: &str, #[case] href: &str, #[case] path: &str) {
+        assert_eq!(resolve_href(base, href), path);
+    }
+}
@@ -0,0 +1,150 @@
+/// Elements that start a new paragraph when reading text out of XHTML.
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "li",
+    "blockquote",
+    "pre",
+    "tr",
+    "section",
+    "article",
+    "hr",
+    "dt",
+    "dd",
+    "figcaption",
+];
+const SKIPPED_TAGS: &[&str] = &["head", "script", "style"];
+
+/// Splits an XHTML document into paragraphs of plain text. Ebook markup is
+/// often not well-formed, so this scans tags instead of parsing a tree.
+pub fn paragraphs(html: &str) -> Vec<String> {
+    let mut paragraphs = vec![];
+    let mut current = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        append_text(&mut current, &rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if !closing && !tag.ends_with('/') && SKIPPED_TAGS.contains(&name.as_str()) {
+            let close = format!("</{name}");
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or("", |i| &rest[i..]);
+            continue;
+        }
+        if BLOCK_TAGS.contains(&name.as_str()) {
+            flush(&mut paragraphs, &mut current);
+        }
+    }
+    append_text(&mut current, rest);
+    flush(&mut paragraphs, &mut current);
+    paragraphs
+}
+
+fn append_text(current: &mut String, text: &str) {
+    let text = decode_entities(text).replace('\u{ad}', "");
+    if text.starts_with(char::is_whitespace) && !current.ends_with(' ') {
+        current.push(' ');
+    }
+    current.push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+    if text.ends_with(char::is_whitespace) && !current.is_empty() {
+        current.push(' ');
+    }
+}
+
+fn flush(paragraphs: &mut Vec<String>, current: &mut String) {
+    let text = current.trim();
+    if !text.is_empty() {
+        paragraphs.push(text.to_string());
+    }
+    current.clear();
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end + 1])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "shy" => '\u{ad}',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "copy" => '©',
+        "eacute" => 'é',
+        "egrave" => 'è',
+        "agrave" => 'à',
+        "aacute" => 'á',
+        "ccedil" => 'ç',
+        "uuml" => 'ü',
+        "ouml" => 'ö',
+        "auml" => 'ä',
+        "szlig" => 'ß',
+        _ => return None,
+    })
+}
@@ -0,0 +1,200 @@
+pub mod epub;
+mod html;
+mod pdf;
+
+use crate::{
+    library::epub::Epub,
+    metadata::Metadata,
+    model::book::{linked_file::LinkedFile, Book},
+};
+use image::ImageFormat;
+use log::{info, warn};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub const EBOOK_EXTENSIONS: &[&str] = &["epub", "pdf"];
+
+#[derive(Debug, Default)]
+pub struct ScanSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub linked: usize,
+    pub moved: usize,
+    pub duplicates: Vec<PathBuf>,
+    pub missing: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Links every EPUB and PDF below `dir` to a book, adding books for files not
+/// in the library yet. Files are matched by path first, then by content so
+/// moved files keep their book, then by ISBN or title and authors.
+pub fn scan(dir: &Path, books: &mut Vec<Book>) -> io::Result<ScanSummary> {
+    let dir = dir.canonicalize()?;
+    let mut summary = ScanSummary::default();
+
+    for path in ebook_paths(&dir, &mut summary.failed) {
+        let file = match LinkedFile::read(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                summary.failed.push((path, e.to_string()));
+                continue;
+            }
+        };
+
+        if let Some(book) = books
+            .iter_mut()
+            .find(|b| b.file.as_ref().is_some_and(|f| f.path == path))
+        {
+            book.file = Some(file);
+            if let Ok((metadata, _)) = read_ebook(&path) {
+                if book.fill_missing(&metadata) {
+                    summary.updated += 1;
+                }
+            }
+            continue;
+        }
+
+        if let Some(book) = books
+            .iter_mut()
+            .find(|b| b.file.as_ref().is_some_and(|f| f.same_content(&file)))
+        {
+            let previous = book
+                .file
+                .as_ref()
+                .map(|f| f.path.clone())
+                .unwrap_or_default();
+            if previous.is_file() {
+                // A second copy of a file that is already linked.
+                summary.duplicates.push(path);
+            } else {
+                info!(
+                    "'{}' moved from {} to {}",
+                    book.title,
+                    previous.display(),
+                    path.display()
+                );
+                book.file = Some(file);
+                summary.moved += 1;
+            }
+            continue;
+        }
+
+        let (metadata, cover) = match read_ebook(&path) {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Cannot read {}: {e}", path.display());
+                summary.failed.push((path, e));
+                continue;
+            }
+        };
+        let book = match books
+            .iter_mut()
+            .find(|b| b.file.is_none() && b.matches_metadata(&metadata))
+        {
+            Some(book) => {
+                book.fill_missing(&metadata);
+                summary.linked += 1;
+                book
+            }
+            None => {
+                let title = metadata.title.clone().unwrap_or_else(|| {
+                    path.file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                });
+                books.push(Book::from_metadata(title, &metadata));
+                summary.added += 1;
+                books.last_mut().unwrap()
+            }
+        };
+        book.file = Some(file);
+        if let Some(cover) = cover {
+            save_cover(book, &cover);
+        }
+    }
+
+    summary.missing = books
+        .iter()
+        .filter_map(|b| b.file.as_ref())
+        .filter(|f| f.path.starts_with(&dir) && !f.exists())
+        .map(|f| f.path.clone())
+        .collect();
+    Ok(summary)
+}
+
+/// Unreadable folders and entries go to `failed`, the rest is still scanned.
+fn ebook_paths(dir: &Path, failed: &mut Vec<(PathBuf, String)>) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Cannot read {}: {e}", dir.display());
+            failed.push((dir.to_path_buf(), e.to_string()));
+            return vec![];
+        }
+    };
+    let mut paths = vec![];
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                failed.push((dir.to_path_buf(), e.to_string()));
+                continue;
+            }
+        };
+        let path = entry.path();
+        // Symlinked folders are skipped, as a link back up the tree would
+        // never end; symlinked files are still read.
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => paths.extend(ebook_paths(&path, failed)),
+            Ok(_) => {
+                if path.is_file()
+                    && extension(&path).is_some_and(|e| EBOOK_EXTENSIONS.contains(&e.as_str()))
+                {
+                    paths.push(path);
+                }
+            }
+            Err(e) => failed.push((path, e.to_string())),
+        }
+    }
+    paths.sort();
+    paths
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+}
+
+fn read_ebook(path: &Path) -> Result<(Metadata, Option<Vec<u8>>), String> {
+    match extension(path).as_deref() {
+        Some("epub") => {
+            let mut epub = Epub::open(path)?;
+            let cover = epub.cover();
+            Ok((epub.metadata, cover))
+        }
+        _ => Ok((pdf::read_metadata(path)?, None)),
+    }
+}
+
+/// Stores the embedded cover as the book's JPEG cover unless it already has one.
+fn save_cover(book: &Book, bytes: &[u8]) {
+    let path = Book::default_cover_path(&book.title);
+    if path.exists() {
+        return;
+    }
+    let result = image::load_from_memory(bytes)
+        .map_err(|e| e.to_string())
+        .and_then(|img| {
+            fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+                .map_err(|e| e.to_string())?;
+            img.to_rgb8()
+                .save_with_format(&path, ImageFormat::Jpeg)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!("Cannot save cover of '{}': {e}", book.title);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::metadata::Metadata;
+use std::path::Path;
+
+/// Reads the info dictionary. Producers often leave it empty or fill the title
+/// with the name of the source document, so callers fall back to the file name.
+pub fn read_metadata(path: &Path) -> Result<Metadata, String> {
+    let info = lopdf::Document::load_metadata(path).map_err(|e| e.to_string())?;
+    Ok(Metadata {
+        title: info
+            .title
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty() && !looks_like_file_name(t)),
+        authors: info
+            .author
+            .unwrap_or_default()
+            .split([';', '&'])
+            .flat_map(|a| a.split(" and "))
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty())
+            .collect(),
+        year: info
+            .creation_date
+            .and_then(|d| d.trim_start_matches("D:").get(..4)?.parse().ok()),
+        pages: u16::try_from(info.page_count).ok(),
+        ..Metadata::default()
+    })
+}
+
+/// Titles such as "Microsoft Word - draft.docx" or "thesis.dvi".
+fn looks_like_file_name(title: &str) -> bool {
+    title.rsplit_once('.').is_some_and(|(_, ext)| {
+        (2..=4).contains(&ext.len()) && ext.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
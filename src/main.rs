@@ -1,22 +1,42 @@
+mod barcode;
+mod cli;
+mod clipboard;
+mod config;
 mod event;
+mod export;
 mod image_util;
+mod import;
+mod library;
 mod logging;
+mod metadata;
 mod model;
+mod server;
 mod view;
 
 use crate::{
+    cli::{Cli, Command},
+    config::Config,
     event::{app_event::AppEvent, handle_key, spawn_input_thread},
+    export::columns::Column,
+    import::{calibre, device},
     logging::setup_logger,
-    model::{running_state::RunningState, Model},
+    model::{persistance, running_state::RunningState, Model},
+    server::Mode,
     view::view,
 };
+use clap::{Parser, ValueEnum};
 use ratatui::DefaultTerminal;
-use std::sync::mpsc;
+use std::{fs::File, io, sync::mpsc};
 
 fn main() -> color_eyre::Result<()> {
+    let cli = Cli::parse();
     setup_logger()?;
     color_eyre::install()?;
 
+    if let Some(command) = cli.command {
+        return run_command(command);
+    }
+
     let terminal = ratatui::init();
     let result = run(terminal);
 
@@ -24,9 +44,255 @@ fn main() -> color_eyre::Result<()> {
     result
 }
 
+fn run_command(command: Command) -> color_eyre::Result<()> {
+    match command {
+        Command::IndexDump { dumps, output } => {
+            let output = output.unwrap_or(Config::load().lookup.offline_index);
+            let summary = metadata::dump_index::build(&dumps, &output)?;
+            println!(
+                "Indexed {} titles and {} authors/works into {} ({} records skipped)",
+                summary.titles,
+                summary.keys,
+                output.display(),
+                summary.skipped
+            );
+        }
+        Command::ScanLibrary { dir } => {
+            let mut state = persistance::load()?;
+            let summary = library::scan(&dir, &mut state.books)?;
+            state.books.sort_by_key(|b| b.title_normalized());
+            persistance::save(state)?;
+            println!(
+                "Added {}, linked {}, updated {} and relinked {} moved books",
+                summary.added, summary.linked, summary.updated, summary.moved
+            );
+            for path in &summary.missing {
+                println!("Missing: {}", path.display());
+            }
+            for path in &summary.duplicates {
+                println!("Duplicate copy skipped: {}", path.display());
+            }
+            for (path, error) in &summary.failed {
+                println!("Failed: {}: {error}", path.display());
+            }
+        }
+        Command::ImportCalibre {
+            library,
+            db,
+            dry_run,
+        } => {
+            let mut state = persistance::load()?;
+            let calibre_library = if db {
+                calibre::read_database(&library)?
+            } else {
+                calibre::read_folders(&library)?
+            };
+            for (path, error) in &calibre_library.failed {
+                println!("Failed: {}: {error}", path.display());
+            }
+            let entries = calibre_library.books;
+            let actions = calibre::plan(&entries, &state.books);
+
+            println!("Mapping:");
+            for (calibre_field, bookie_field) in calibre::MAPPING {
+                println!("  {calibre_field:<50} -> {bookie_field}");
+            }
+            println!();
+            let (mut added, mut updated) = (0, 0);
+            for (entry, action) in entries.iter().zip(&actions) {
+                let title = entry.metadata.title.as_deref().unwrap_or_default();
+                match action {
+                    calibre::Action::Add => {
+                        added += 1;
+                        println!("  add     {title} ({})", entry.metadata.authors.join(", "));
+                    }
+                    calibre::Action::Update(_) => {
+                        updated += 1;
+                        println!("  update  {title}");
+                    }
+                    calibre::Action::Unchanged => println!("  skip    {title}"),
+                }
+            }
+            println!(
+                "\n{added} to add, {updated} to update, {} already complete",
+                entries.len() - added - updated
+            );
+            if dry_run {
+                println!("Dry run, nothing was written");
+                return Ok(());
+            }
+            calibre::apply(entries, &actions, &mut state.books);
+            state.books.sort_by_key(|b| b.title_normalized());
+            persistance::save(state)?;
+        }
+        Command::ImportDevice { path, dry_run } => {
+            let mut state = persistance::load()?;
+            let import = device::read(&path);
+            for (path, error) in &import.failed {
+                println!("Failed: {}: {error}", path.display());
+            }
+            let found = import.books.len();
+            let report = device::apply(import.books, &mut state.books);
+            for title in &report.updated {
+                println!("  update  {title}");
+            }
+            for title in &report.unmatched {
+                println!("  no match  {title}");
+            }
+            println!(
+                "{found} books with reading activity: {} updated, {} unchanged, {} not in the library",
+                report.updated.len(),
+                report.unchanged,
+                report.unmatched.len()
+            );
+            if dry_run {
+                println!("Dry run, nothing was written");
+                return Ok(());
+            }
+            persistance::save(state)?;
+        }
+        Command::Import { format, file } => {
+            let mut state = persistance::load()?;
+            let report = import::import(format, &file, &mut state.books)?;
+            for problem in &report.invalid {
+                println!("Skipped line {}: {}", problem.line, problem.message);
+            }
+            for problem in &report.warnings {
+                println!("Line {}: {}", problem.line, problem.message);
+            }
+            println!(
+                "Added {}, updated {}, {} already up to date, {} invalid rows skipped",
+                report.added,
+                report.updated,
+                report.unchanged,
+                report.invalid.len()
+            );
+            state.books.sort_by_key(|b| b.title_normalized());
+            persistance::save(state)?;
+        }
+        Command::Export {
+            format,
+            template,
+            query,
+            columns,
+            output,
+        } => {
+            let mut state = persistance::load()?;
+            if let Some(query) = query {
+                state.books.retain(|b| b.matches_query(&query));
+            }
+            let columns = if columns.is_empty() {
+                Column::value_variants().to_vec()
+            } else {
+                columns
+            };
+            let write = |output: &mut dyn io::Write| match (&template, format) {
+                (Some(template), _) => export::template::write(&state.books, template, output),
+                (None, Some(format)) => export::export(format, &state.books, &columns, output),
+                (None, None) => unreachable!("clap requires a format or a template"),
+            };
+            match output {
+                Some(path) => write(&mut File::create(path)?)?,
+                None => export::ignore_broken_pipe(write(&mut io::stdout().lock()))?,
+            }
+        }
+        Command::ExportMarkdown { dir } => {
+            let state = persistance::load()?;
+            let report = export::markdown::write(&state.books, &dir)?;
+            println!(
+                "Wrote {} new notes, updated {}, {} unchanged",
+                report.created, report.updated, report.unchanged
+            );
+        }
+        Command::Site { outdir } => {
+            let state = persistance::load()?;
+            let report = export::site::write(&state.books, &outdir)?;
+            for (path, error) in &report.failed_covers {
+                println!("Cover {}: {error}", path.display());
+            }
+            println!(
+                "Wrote {} pages and {} thumbnails to {}, removed {} old files, left out {} private books",
+                report.pages,
+                report.thumbnails,
+                outdir.display(),
+                report.removed,
+                report.private
+            );
+        }
+        Command::Catalog {
+            output,
+            group_by,
+            query,
+        } => {
+            let mut state = persistance::load()?;
+            if let Some(query) = query {
+                state.books.retain(|b| b.matches_query(&query));
+            }
+            let report = export::catalog::write(&state.books, group_by, &output)?;
+            for (path, error) in &report.failed_covers {
+                println!("Cover {}: {error}", path.display());
+            }
+            println!(
+                "Wrote {} books on {} pages to {}",
+                report.books,
+                report.pages,
+                output.display()
+            );
+        }
+        Command::Labels { output, query } => {
+            let mut state = persistance::load()?;
+            if let Some(query) = query {
+                state.books.retain(|b| b.matches_query(&query));
+            }
+            for path in export::labels::write(&state.books, &output)? {
+                println!("Wrote {}", path.display());
+            }
+            println!("{} labels", state.books.len());
+        }
+        Command::Feed {
+            output,
+            max_entries,
+        } => {
+            let state = persistance::load()?;
+            let mut config = Config::load().feed;
+            if let Some(max_entries) = max_entries {
+                config.max_entries = max_entries;
+            }
+            match output {
+                Some(path) => export::atom::write(&state.books, &config, File::create(path)?)?,
+                None => export::ignore_broken_pipe(export::atom::write(
+                    &state.books,
+                    &config,
+                    io::stdout().lock(),
+                ))?,
+            }
+        }
+        Command::Serve {
+            opds,
+            token,
+            read_only,
+            allowed_origin,
+            address,
+            port,
+        } => {
+            let mut config = Config::load().server;
+            if let Some(token) = token {
+                config.token = token;
+            }
+            if let Some(origin) = allowed_origin {
+                config.allowed_origin = origin;
+            }
+            config.read_only |= read_only;
+            let mode = if opds { Mode::Opds } else { Mode::Api };
+            server::run(&address, port, mode, config)?;
+        }
+    }
+    Ok(())
+}
+
 fn run(mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
     let (event_tx, event_rx) = mpsc::channel::<AppEvent>();
-    let mut model = Model::load(event_tx.clone());
+    let mut model = Model::load(event_tx.clone())?;
 
     spawn_input_thread(event_tx.clone());
 
@@ -46,6 +312,15 @@ fn run(mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
             AppEvent::CoverReady(res) => {
                 model.book_info.handle_cover_response(res);
             }
+            AppEvent::MetadataReady(res) => {
+                model.handle_metadata_response(res);
+            }
+            AppEvent::BarcodesScanned(res) => {
+                model.handle_barcode_scans(res);
+            }
+            AppEvent::LabelScanned(res) => {
+                model.handle_label_scan(res);
+            }
             AppEvent::Resize => {}
         }
         terminal.draw(|frame| view(&mut model, frame))?;
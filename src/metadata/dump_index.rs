@@ -0,0 +1,274 @@
+use crate::metadata::{
+    sorted_file::{self, ExternalSorter},
+    Metadata,
+};
+use flate2::read::MultiGzDecoder;
+use log::{info, warn};
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+/// Records searchable by normalized title:
+/// `norm \t title \t author keys \t work key \t year \t pages \t isbn`.
+const TITLES_FILE: &str = "titles.tsv";
+/// Records searchable by Open Library key: `/authors/..` maps to a name and
+/// `/works/..` maps to the work's author keys.
+const KEYS_FILE: &str = "keys.tsv";
+
+const MIN_PREFIX_LEN: usize = 3;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DumpRecord {
+    key: String,
+    #[serde(rename = "type")]
+    record_type: Option<KeyRef>,
+    name: Option<String>,
+    title: Option<String>,
+    subtitle: Option<String>,
+    authors: Vec<AuthorRef>,
+    works: Vec<KeyRef>,
+    number_of_pages: Option<u32>,
+    publish_date: Option<String>,
+    first_publish_date: Option<String>,
+    isbn_13: Vec<String>,
+    isbn_10: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct KeyRef {
+    key: String,
+}
+
+/// Editions reference authors directly, works wrap them in an `author` object.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AuthorRef {
+    key: Option<String>,
+    author: Option<KeyRef>,
+}
+
+impl AuthorRef {
+    fn key(&self) -> Option<&str> {
+        self.key
+            .as_deref()
+            .or(self.author.as_ref().map(|a| a.key.as_str()))
+    }
+}
+
+pub struct IndexSummary {
+    pub titles: usize,
+    pub keys: usize,
+    pub skipped: usize,
+}
+
+/// Indexes Open Library editions, works and authors dumps (`.txt.gz` or plain
+/// JSON lines) into `out_dir`.
+pub fn build(dumps: &[PathBuf], out_dir: &Path) -> io::Result<IndexSummary> {
+    fs::create_dir_all(out_dir)?;
+    let mut titles = ExternalSorter::new(out_dir, TITLES_FILE);
+    let mut keys = ExternalSorter::new(out_dir, KEYS_FILE);
+    let mut skipped = 0;
+
+    for dump in dumps {
+        info!("Indexing {}", dump.display());
+        for line in open_dump(dump)?.lines() {
+            let line = line?;
+            // Dumps are `type \t key \t revision \t modified \t json`, but a bare
+            // JSON line is accepted as well.
+            let json = line.rsplit('\t').next().unwrap_or(&line);
+            let Ok(record) = serde_json::from_str::<DumpRecord>(json) else {
+                skipped += 1;
+                continue;
+            };
+            match record.record_type.as_ref().map(|t| t.key.as_str()) {
+                Some("/type/author") => {
+                    if let Some(name) = &record.name {
+                        keys.push(format!("{}\t{}", record.key, clean(name)))?;
+                    }
+                }
+                Some("/type/work") => {
+                    keys.push(format!("{}\t{}", record.key, author_keys(&record)))?;
+                    if let Some(line) = title_line(&record) {
+                        titles.push(line)?;
+                    }
+                }
+                Some("/type/edition") => {
+                    if let Some(line) = title_line(&record) {
+                        titles.push(line)?;
+                    }
+                }
+                _ => skipped += 1,
+            }
+        }
+    }
+
+    Ok(IndexSummary {
+        titles: titles.finish()?,
+        keys: keys.finish()?,
+        skipped,
+    })
+}
+
+pub struct DumpIndex {
+    dir: PathBuf,
+}
+
+impl DumpIndex {
+    pub fn open(dir: &Path) -> Option<Self> {
+        dir.join(TITLES_FILE).is_file().then(|| Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Titles starting with `prefix`, one entry per title and author combination.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<Metadata> {
+        let norm = normalize(prefix);
+        if norm.chars().count() < MIN_PREFIX_LEN {
+            return vec![];
+        }
+        let lines = match sorted_file::lines_with_prefix(&self.dir.join(TITLES_FILE), &norm, 200) {
+            Ok(lines) => lines,
+            Err(e) => {
+                warn!("Offline index lookup failed: {e}");
+                return vec![];
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut suggestions: Vec<Metadata> = vec![];
+        // Editions carry page counts that works lack, so look at those first.
+        let mut records: Vec<Vec<&str>> = lines.iter().map(|l| l.split('\t').collect()).collect();
+        records.sort_by_key(|r| r.get(5).is_none_or(|p| p.is_empty()));
+        for fields in records {
+            let [_, title, author_keys, work_key, year, pages, isbn] = fields[..] else {
+                continue;
+            };
+            let authors = self.resolve_authors(author_keys, work_key);
+            if !seen.insert((title.to_lowercase(), authors.join(", "))) {
+                continue;
+            }
+            suggestions.push(Metadata {
+                title: Some(title.to_string()),
+                authors,
+                year: year.parse().ok(),
+                pages: pages.parse().ok(),
+                isbn: (!isbn.is_empty()).then(|| isbn.to_string()),
+                openlibrary_id: work_key.strip_prefix("/works/").map(|id| id.to_string()),
+                ..Metadata::default()
+            });
+            if suggestions.len() >= limit {
+                break;
+            }
+        }
+        suggestions
+    }
+
+    fn resolve_authors(&self, author_keys: &str, work_key: &str) -> Vec<String> {
+        let author_keys = if author_keys.is_empty() && !work_key.is_empty() {
+            self.lookup_key(work_key).unwrap_or_default()
+        } else {
+            author_keys.to_string()
+        };
+        author_keys
+            .split(',')
+            .filter(|k| !k.is_empty())
+            .filter_map(|k| self.lookup_key(k))
+            .collect()
+    }
+
+    fn lookup_key(&self, key: &str) -> Option<String> {
+        let prefix = format!("{key}\t");
+        sorted_file::lines_with_prefix(&self.dir.join(KEYS_FILE), &prefix, 1)
+            .ok()?
+            .first()
+            .map(|line| line[prefix.len()..].to_string())
+    }
+}
+
+fn open_dump(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|e| e == "gz") {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(Box::new(BufReader::with_capacity(1 << 20, reader)))
+}
+
+fn title_line(record: &DumpRecord) -> Option<String> {
+    let title = match (&record.title, &record.subtitle) {
+        (Some(title), Some(subtitle)) => format!("{title}: {subtitle}"),
+        (Some(title), None) => title.clone(),
+        (None, _) => return None,
+    };
+    let norm = normalize(&title);
+    if norm.is_empty() {
+        return None;
+    }
+    let work_key = if record.key.starts_with("/works/") {
+        record.key.as_str()
+    } else {
+        record.works.first().map_or("", |w| w.key.as_str())
+    };
+    let year = record
+        .publish_date
+        .as_deref()
+        .or(record.first_publish_date.as_deref())
+        .and_then(find_year)
+        .map(|y| y.to_string())
+        .unwrap_or_default();
+    let pages = record
+        .number_of_pages
+        .filter(|p| *p <= u16::MAX as u32)
+        .map(|p| p.to_string())
+        .unwrap_or_default();
+    let isbn = record
+        .isbn_13
+        .first()
+        .or(record.isbn_10.first())
+        .map(|i| clean(i))
+        .unwrap_or_default();
+    Some(format!(
+        "{norm}\t{}\t{}\t{work_key}\t{year}\t{pages}\t{isbn}",
+        clean(&title),
+        author_keys(record),
+    ))
+}
+
+fn author_keys(record: &DumpRecord) -> String {
+    record
+        .authors
+        .iter()
+        .filter_map(|a| a.key())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Publish dates are free text such as "March 1990" or "1965-08".
+fn find_year(date: &str) -> Option<u16> {
+    date.as_bytes()
+        .windows(4)
+        .find(|w| w.iter().all(|b| b.is_ascii_digit()))
+        .and_then(|w| std::str::from_utf8(w).ok()?.parse().ok())
+}
+
+/// Lowercase alphanumerics separated by single spaces, so that punctuation and
+/// spacing differences do not break prefix matching.
+pub fn normalize(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn clean(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ").trim().to_string()
+}
@@ -0,0 +1,44 @@
+pub mod dump_index;
+pub mod open_library;
+mod sorted_file;
+
+use std::path::PathBuf;
+
+/// Book details found by a metadata lookup. Every field is optional because
+/// sources rarely know everything about an edition.
+#[derive(Clone, Debug, Default)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    /// Authors as sorted, e.g. "Herbert, Frank".
+    pub author_sort: Option<String>,
+    pub year: Option<u16>,
+    pub pages: Option<u16>,
+    pub language: Option<String>,
+    pub series: Option<String>,
+    pub series_index: Option<f32>,
+    pub tags: Vec<String>,
+    pub publisher: Option<String>,
+    /// Stars from 0 to 5.
+    pub rating: Option<u8>,
+    pub isbn: Option<String>,
+    pub asin: Option<String>,
+    pub openlibrary_id: Option<String>,
+    pub goodreads_id: Option<String>,
+    pub cover_path: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub enum LookupQuery {
+    Isbn(String),
+    Title(String),
+}
+
+impl LookupQuery {
+    pub fn describe(&self) -> String {
+        match self {
+            LookupQuery::Isbn(isbn) => format!("ISBN {isbn}"),
+            LookupQuery::Title(title) => format!("'{title}'"),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    config::LookupConfig,
+    event::app_event::AppEvent,
+    metadata::{LookupQuery, Metadata},
+    model::book::isbn,
+};
+use log::{error, info};
+use serde::Deserialize;
+use std::{env, fs, path::PathBuf, sync::mpsc::Sender, time::Duration};
+use ureq::Agent;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchResponse {
+    docs: Vec<SearchDoc>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchDoc {
+    title: Option<String>,
+    author_name: Vec<String>,
+    first_publish_year: Option<i32>,
+    number_of_pages_median: Option<u32>,
+    isbn: Vec<String>,
+    cover_i: Option<i64>,
+    cover_edition_key: Option<String>,
+    edition_key: Vec<String>,
+    id_goodreads: Vec<String>,
+    id_amazon: Vec<String>,
+}
+
+/// Runs the lookup on its own thread and reports back through `AppEvent::MetadataReady`.
+pub fn lookup_async(config: LookupConfig, query: LookupQuery, app_tx: Sender<AppEvent>) {
+    std::thread::spawn(move || {
+        let result = lookup(&config, &query);
+        if let Err(e) = &result {
+            error!("Metadata lookup for {} failed: {e}", query.describe());
+        }
+        app_tx.send(AppEvent::MetadataReady(result)).ok();
+    });
+}
+
+pub fn lookup(config: &LookupConfig, query: &LookupQuery) -> Result<Metadata, String> {
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(15)))
+        .build()
+        .into();
+
+    let url = format!("{}/search.json", config.base_url.trim_end_matches('/'));
+    let request = agent.get(&url).query("limit", "1");
+    let request = match query {
+        LookupQuery::Isbn(isbn) => request.query("isbn", isbn),
+        LookupQuery::Title(title) => request.query("title", title),
+    };
+    info!("Looking up metadata for {}", query.describe());
+    let response: SearchResponse = request
+        .call()
+        .map_err(|e| format!("Lookup request failed: {e}"))?
+        .body_mut()
+        .read_json()
+        .map_err(|e| format!("Lookup returned invalid JSON: {e}"))?;
+
+    let Some(doc) = response.docs.into_iter().next() else {
+        return Err(format!("Nothing found for {}", query.describe()));
+    };
+
+    let cover_id = doc.cover_i;
+    let mut metadata = to_metadata(doc, query);
+    if config.download_covers {
+        if let (Some(cover_id), Some(title)) = (cover_id, &metadata.title) {
+            match download_cover(&agent, config, cover_id, title) {
+                Ok(path) => metadata.cover_path = Some(path),
+                // A missing cover should not throw away the rest of the metadata.
+                Err(e) => error!("Cover download failed: {e}"),
+            }
+        }
+    }
+    Ok(metadata)
+}
+
+fn to_metadata(doc: SearchDoc, query: &LookupQuery) -> Metadata {
+    let isbn = match query {
+        LookupQuery::Isbn(isbn) => Some(isbn.clone()),
+        // Prefer an ISBN-13 when the work lists several editions.
+        LookupQuery::Title(_) => doc
+            .isbn
+            .iter()
+            .find(|i| isbn::is_valid_isbn13(i))
+            .or_else(|| doc.isbn.iter().find(|i| isbn::is_valid_isbn10(i)))
+            .cloned(),
+    };
+    Metadata {
+        title: doc.title,
+        authors: doc.author_name,
+        year: doc.first_publish_year.and_then(|y| u16::try_from(y).ok()),
+        pages: doc
+            .number_of_pages_median
+            .and_then(|p| u16::try_from(p).ok()),
+        isbn,
+        asin: doc
+            .id_amazon
+            .into_iter()
+            .find(|a| a.len() == 10 && a.chars().all(|c| c.is_ascii_alphanumeric())),
+        openlibrary_id: doc
+            .cover_edition_key
+            .or_else(|| doc.edition_key.into_iter().next()),
+        goodreads_id: doc.id_goodreads.into_iter().next(),
+        ..Metadata::default()
+    }
+}
+
+fn download_cover(
+    agent: &Agent,
+    config: &LookupConfig,
+    cover_id: i64,
+    title: &str,
+) -> Result<PathBuf, String> {
+    let url = format!(
+        "{}/b/id/{cover_id}-L.jpg",
+        config.covers_url.trim_end_matches('/')
+    );
+    let bytes = agent
+        .get(&url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .body_mut()
+        .read_to_vec()
+        .map_err(|e| e.to_string())?;
+    // It only goes to `covers/` when the form is saved.
+    let path = env::temp_dir().join(format!("bookie-cover-{}.jpg", Uuid::new_v4()));
+    fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    info!("Cover of '{title}' downloaded to {}", path.display());
+    Ok(path)
+}
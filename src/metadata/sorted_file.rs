@@ -0,0 +1,145 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+const CHUNK_BYTES: usize = 256 * 1024 * 1024;
+
+/// Sorts an unbounded stream of lines by spilling sorted chunks to disk and
+/// merging them at the end, so indexing a full dump does not need it in memory.
+pub struct ExternalSorter {
+    dir: PathBuf,
+    name: String,
+    lines: Vec<String>,
+    bytes: usize,
+    chunks: Vec<PathBuf>,
+}
+
+impl ExternalSorter {
+    pub fn new(dir: &Path, name: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            name: name.to_string(),
+            lines: vec![],
+            bytes: 0,
+            chunks: vec![],
+        }
+    }
+
+    pub fn push(&mut self, line: String) -> io::Result<()> {
+        self.bytes += line.len();
+        self.lines.push(line);
+        if self.bytes >= CHUNK_BYTES {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the merged, deduplicated lines to `<dir>/<name>` and returns their count.
+    pub fn finish(mut self) -> io::Result<usize> {
+        self.spill()?;
+        let mut readers = self
+            .chunks
+            .iter()
+            .map(|path| File::open(path).map(|f| BufReader::new(f).lines()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut heap = BinaryHeap::new();
+        for (i, reader) in readers.iter_mut().enumerate() {
+            if let Some(line) = reader.next() {
+                heap.push(Reverse((line?, i)));
+            }
+        }
+
+        let mut out = BufWriter::new(File::create(self.dir.join(&self.name))?);
+        let mut previous: Option<String> = None;
+        let mut count = 0;
+        while let Some(Reverse((line, i))) = heap.pop() {
+            if let Some(next) = readers[i].next() {
+                heap.push(Reverse((next?, i)));
+            }
+            if previous.as_ref() == Some(&line) {
+                continue;
+            }
+            writeln!(out, "{line}")?;
+            count += 1;
+            previous = Some(line);
+        }
+        out.flush()?;
+
+        for chunk in &self.chunks {
+            fs::remove_file(chunk)?;
+        }
+        Ok(count)
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.lines.sort_unstable();
+        let path = self
+            .dir
+            .join(format!(".{}.chunk{}", self.name, self.chunks.len()));
+        let mut out = BufWriter::new(File::create(&path)?);
+        for line in self.lines.drain(..) {
+            writeln!(out, "{line}")?;
+        }
+        out.flush()?;
+        self.chunks.push(path);
+        self.bytes = 0;
+        Ok(())
+    }
+}
+
+/// Returns up to `limit` lines starting with `prefix` from a file sorted by [`ExternalSorter`].
+pub fn lines_with_prefix(path: &Path, prefix: &str, limit: usize) -> io::Result<Vec<String>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let size = reader.get_ref().metadata()?.len();
+    let start = first_line_at_least(&mut reader, size, prefix)?;
+    reader.seek(SeekFrom::Start(start))?;
+
+    let mut lines = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if !line.starts_with(prefix) || lines.len() >= limit {
+            break;
+        }
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+/// Binary search over byte offsets: every line starting before `lo` sorts
+/// below `target`, and the first line starting at or after `hi` does not.
+fn first_line_at_least(reader: &mut BufReader<File>, size: u64, target: &str) -> io::Result<u64> {
+    let (mut lo, mut hi) = (0, size);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let start = next_line_start(reader, mid)?;
+        if start >= hi {
+            hi = mid;
+            continue;
+        }
+        let mut line = String::new();
+        let read = reader.read_line(&mut line)? as u64;
+        if line.trim_end_matches('\n') < target {
+            lo = start + read;
+        } else {
+            hi = mid;
+        }
+    }
+    next_line_start(reader, lo)
+}
+
+/// Moves the reader to the first line starting at or after `pos` and returns its offset.
+fn next_line_start(reader: &mut BufReader<File>, pos: u64) -> io::Result<u64> {
+    if pos == 0 {
+        reader.seek(SeekFrom::Start(0))?;
+        return Ok(0);
+    }
+    reader.seek(SeekFrom::Start(pos - 1))?;
+    let mut skipped = vec![];
+    let read = reader.read_until(b'\n', &mut skipped)? as u64;
+    Ok(pos - 1 + read)
+}
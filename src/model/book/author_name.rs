@@ -0,0 +1,175 @@
+use crate::model::book::Book;
+
+/// Lowercase words that belong to the family name, as in "Ursula K. Le Guin"
+/// or "Ludwig van Beethoven".
+const PARTICLES: &[&str] = &[
+    "van", "von", "de", "der", "den", "da", "di", "du", "del", "della", "des", "la", "le", "ter",
+    "ten", "zu", "st.",
+];
+const SUFFIXES: &[&str] = &["jr.", "jr", "sr.", "sr", "ii", "iii", "iv"];
+
+/// An author's name split the way reference managers store it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AuthorName {
+    pub given: String,
+    pub family: String,
+    pub suffix: Option<String>,
+}
+
+impl AuthorName {
+    /// Reads "Given Family", "Family, Given" or "Family, Suffix, Given". A
+    /// single word, such as an organisation or a pen name, is a family name.
+    pub fn parse(name: &str) -> Self {
+        let parts = name.split(',').map(str::trim).collect::<Vec<_>>();
+        match parts.as_slice() {
+            [family, suffix, given] => Self {
+                given: given.to_string(),
+                family: family.to_string(),
+                suffix: Some(suffix.to_string()),
+            },
+            [family, given] if !is_suffix(given) => Self {
+                given: given.to_string(),
+                family: family.to_string(),
+                suffix: None,
+            },
+            _ => Self::parse_natural(name),
+        }
+    }
+
+    /// Whether `name` is written "Family, Given": the part before the first
+    /// comma is one word, apart from particles, and something follows it.
+    pub fn is_inverted(name: &str) -> bool {
+        let Some((family, given)) = name.split_once(',') else {
+            return false;
+        };
+        !given.trim().is_empty()
+            && family
+                .split_whitespace()
+                .filter(|w| !PARTICLES.contains(&w.to_lowercase().as_str()))
+                .count()
+                == 1
+    }
+
+    fn parse_natural(name: &str) -> Self {
+        let mut words = name
+            .split([' ', ','])
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>();
+        let suffix = match words.last() {
+            Some(last) if words.len() > 1 && is_suffix(last) => words.pop().map(str::to_string),
+            _ => None,
+        };
+        let Some(last) = words.len().checked_sub(1) else {
+            return Self::default();
+        };
+        let mut family_start = last;
+        while family_start > 1
+            && PARTICLES.contains(&words[family_start - 1].to_lowercase().as_str())
+        {
+            family_start -= 1;
+        }
+        Self {
+            given: words[..family_start].join(" "),
+            family: words[family_start..].join(" "),
+            suffix,
+        }
+    }
+
+    /// "Herbert, Frank" or "King, Jr., Martin Luther", as BibTeX and RIS want it.
+    pub fn inverted(&self) -> String {
+        let mut parts = vec![self.family.clone()];
+        parts.extend(self.suffix.clone());
+        if !self.given.is_empty() {
+            parts.push(self.given.clone());
+        }
+        parts.join(", ")
+    }
+
+    /// "Frank Herbert", the way bookie stores authors.
+    pub fn natural(&self) -> String {
+        [Some(&self.given), Some(&self.family), self.suffix.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|p| !p.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Book {
+    /// The authors split into given and family names. The sort names from
+    /// Calibre or a reference manager, like "García Márquez, Gabriel", are
+    /// preferred because they say where the family name starts.
+    pub fn author_names(&self) -> Vec<AuthorName> {
+        let sorted = self
+            .author_sort
+            .as_ref()
+            .map(|s| s.split(" & ").collect::<Vec<_>>())
+            .filter(|s| s.len() == self.authors.len());
+        match sorted {
+            Some(sorted) => sorted.into_iter().map(AuthorName::parse).collect(),
+            None => self.authors.iter().map(|a| AuthorName::parse(a)).collect(),
+        }
+    }
+}
+
+/// The sort names of authors as reference managers list them, or None when
+/// none of them is written "Family, Given".
+pub fn author_sort(names: &[String]) -> Option<String> {
+    names
+        .iter()
+        .any(|n| n.contains(','))
+        .then(|| names.join(" & "))
+}
+
+fn is_suffix(word: &str) -> bool {
+    SUFFIXES.contains(&word.to_lowercase().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Frank Herbert", "Frank", "Herbert", None)]
+    #[case("Herbert, Frank", "Frank", "Herbert", None)]
+    #[case("Ursula K. Le Guin", "Ursula K.", "Le Guin", None)]
+    #[case("Ludwig van Beethoven", "Ludwig", "van Beethoven", None)]
+    #[case("Martin Luther King Jr.", "Martin Luther", "King", Some("Jr."))]
+    #[case("King, Jr., Martin Luther", "Martin Luther", "King", Some("Jr."))]
+    #[case("King, Jr.", "", "King", Some("Jr."))]
+    #[case("Homer", "", "Homer", None)]
+    #[case("", "", "", None)]
+    fn parses_names(
+        #[case] name: &str,
+        #[case] given: &str,
+        #[case] family: &str,
+        #[case] suffix: Option<&str>,
+    ) {
+        assert_eq!(
+            AuthorName::parse(name),
+            AuthorName {
+                given: given.to_string(),
+                family: family.to_string(),
+                suffix: suffix.map(str::to_string),
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("Frank Herbert", "Herbert, Frank", "Frank Herbert")]
+    #[case("Le Guin, Ursula K.", "Le Guin, Ursula K.", "Ursula K. Le Guin")]
+    #[case(
+        "Martin Luther King Jr.",
+        "King, Jr., Martin Luther",
+        "Martin Luther King Jr."
+    )]
+    #[case("Homer", "Homer", "Homer")]
+    fn writes_names(#[case] name: &str, #[case] inverted: &str, #[case] natural: &str) {
+        let name = AuthorName::parse(name);
+        assert_eq!(name.inverted(), inverted);
+        assert_eq!(name.natural(), natural);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::model::book::{author_name::AuthorName, item_type::ItemType, Book};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CitationStyle {
+    Apa,
+    Mla,
+    Chicago,
+    /// "Title — Author (Year)"
+    Short,
+}
+
+impl CitationStyle {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CitationStyle::Apa => "APA",
+            CitationStyle::Mla => "MLA",
+            CitationStyle::Chicago => "Chicago",
+            CitationStyle::Short => "short",
+        }
+    }
+}
+
+impl Book {
+    /// A reference list entry as plain text, so titles are not italicised.
+    pub fn citation(&self, style: CitationStyle) -> String {
+        let names = self.author_names();
+        let mut parts = vec![];
+        match style {
+            CitationStyle::Apa => {
+                if !names.is_empty() {
+                    parts.push(sentence(&apa_authors(&names)));
+                }
+                parts.push(if self.year > 0 {
+                    format!("({}).", self.year)
+                } else {
+                    "(n.d.).".to_string()
+                });
+                parts.push(sentence(&self.title));
+                parts.extend(self.source().map(|s| sentence(&s)));
+                parts.extend(
+                    self.doi
+                        .as_ref()
+                        .map(|doi| format!("https://doi.org/{doi}")),
+                );
+            }
+            CitationStyle::Mla | CitationStyle::Chicago => {
+                if !names.is_empty() {
+                    parts.push(sentence(&listed_authors(&names, style)));
+                }
+                parts.push(sentence(&self.title));
+                let published = [
+                    self.source(),
+                    (self.year > 0).then(|| self.year.to_string()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+                if !published.is_empty() {
+                    parts.push(sentence(&published.join(", ")));
+                }
+                parts.extend(
+                    self.doi
+                        .as_ref()
+                        .map(|doi| format!("https://doi.org/{doi}.")),
+                );
+            }
+            CitationStyle::Short => {
+                let mut short = self.title.clone();
+                if !self.authors.is_empty() {
+                    short.push_str(&format!(" — {}", self.authors.join(", ")));
+                }
+                if self.year > 0 {
+                    short.push_str(&format!(" ({})", self.year));
+                }
+                return short;
+            }
+        }
+        parts.join(" ")
+    }
+
+    /// The journal of a paper or magazine, otherwise the publisher.
+    fn source(&self) -> Option<String> {
+        match self.item_type {
+            ItemType::Paper | ItemType::Magazine => self.venue.clone().or(self.publisher.clone()),
+            ItemType::Book | ItemType::Comic => self.publisher.clone(),
+        }
+    }
+}
+
+/// "Pratchett, T., & Gaiman, N."
+fn apa_authors(names: &[AuthorName]) -> String {
+    let names = names
+        .iter()
+        .map(|name| {
+            let initials = name
+                .given
+                .split([' ', '-'])
+                .filter_map(|part| part.chars().next())
+                .map(|c| format!("{c}."))
+                .collect::<Vec<_>>()
+                .join(" ");
+            [name.family.clone(), initials]
+                .into_iter()
+                .chain(name.suffix.clone())
+                .filter(|p| !p.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect::<Vec<_>>();
+    match names.as_slice() {
+        [one] => one.clone(),
+        [first @ .., last] => format!("{}, & {last}", first.join(", ")),
+        [] => String::new(),
+    }
+}
+
+/// "Pratchett, Terry, and Neil Gaiman". MLA shortens three or more authors
+/// to the first one and "et al."; Chicago lists them all.
+fn listed_authors(names: &[AuthorName], style: CitationStyle) -> String {
+    let Some((first, others)) = names.split_first() else {
+        return String::new();
+    };
+    let first = first.inverted();
+    match others {
+        [] => first,
+        [second] => format!("{first}, and {}", second.natural()),
+        _ if style == CitationStyle::Mla => format!("{first}, et al"),
+        [middle @ .., last] => {
+            let mut listed = vec![first];
+            listed.extend(middle.iter().map(AuthorName::natural));
+            format!("{}, and {}", listed.join(", "), last.natural())
+        }
+    }
+}
+
+/// Ends the text with a period unless it already ends in punctuation.
+fn sentence(text: &str) -> String {
+    let text = text.trim();
+    if text.ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{text}.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn good_omens() -> Book {
+        Book {
+            title: "Good Omens".to_string(),
+            authors: vec!["Terry Pratchett".to_string(), "Neil Gaiman".to_string()],
+            year: 1990,
+            publisher: Some("Gollancz".to_string()),
+            ..Book::default()
+        }
+    }
+
+    fn three_authors() -> Book {
+        Book {
+            title: "Why?".to_string(),
+            authors: vec![
+                "Ada Lovelace".to_string(),
+                "Charles Babbage".to_string(),
+                "Mary Somerville".to_string(),
+            ],
+            ..Book::default()
+        }
+    }
+
+    fn paper() -> Book {
+        Book {
+            title: "A Study".to_string(),
+            authors: vec!["Doe, Jane".to_string()],
+            year: 2020,
+            item_type: ItemType::Paper,
+            venue: Some("Nature".to_string()),
+            doi: Some("10.1000/xyz".to_string()),
+            ..Book::default()
+        }
+    }
+
+    fn anonymous() -> Book {
+        Book {
+            title: "Beowulf".to_string(),
+            ..Book::default()
+        }
+    }
+
+    #[rstest]
+    #[case(
+        good_omens(),
+        CitationStyle::Apa,
+        "Pratchett, T., & Gaiman, N. (1990). Good Omens. Gollancz."
+    )]
+    #[case(
+        good_omens(),
+        CitationStyle::Mla,
+        "Pratchett, Terry, and Neil Gaiman. Good Omens. Gollancz, 1990."
+    )]
+    #[case(
+        good_omens(),
+        CitationStyle::Chicago,
+        "Pratchett, Terry, and Neil Gaiman. Good Omens. Gollancz, 1990."
+    )]
+    #[case(
+        good_omens(),
+        CitationStyle::Short,
+        "Good Omens — Terry Pratchett, Neil Gaiman (1990)"
+    )]
+    #[case(
+        three_authors(),
+        CitationStyle::Apa,
+        "Lovelace, A., Babbage, C., & Somerville, M. (n.d.). Why?"
+    )]
+    #[case(three_authors(), CitationStyle::Mla, "Lovelace, Ada, et al. Why?")]
+    #[case(
+        three_authors(),
+        CitationStyle::Chicago,
+        "Lovelace, Ada, Charles Babbage, and Mary Somerville. Why?"
+    )]
+    #[case(
+        paper(),
+        CitationStyle::Apa,
+        "Doe, J. (2020). A Study. Nature. https://doi.org/10.1000/xyz"
+    )]
+    #[case(
+        paper(),
+        CitationStyle::Mla,
+        "Doe, Jane. A Study. Nature, 2020. https://doi.org/10.1000/xyz."
+    )]
+    #[case(anonymous(), CitationStyle::Apa, "(n.d.). Beowulf.")]
+    #[case(anonymous(), CitationStyle::Mla, "Beowulf.")]
+    fn cites(#[case] book: Book, #[case] style: CitationStyle, #[case] citation: &str) {
+        assert_eq!(book.citation(style), citation);
+    }
+}
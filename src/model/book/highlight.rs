@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// A passage marked on an e-reader, with the note written next to it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// When it was made, as the device wrote it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Where it is in the book, such as the chapter and position, as the
+    /// device wrote it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl Highlight {
+    /// Same passage at the same place, whatever the note says.
+    pub fn is_same(&self, other: &Highlight) -> bool {
+        self.text == other.text && self.location == other.location
+    }
+}
@@ -0,0 +1,90 @@
+use crate::model::book::isbn;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Identifiers {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn_10: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn_13: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openlibrary_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goodreads_id: Option<String>,
+}
+
+impl Identifiers {
+    pub fn is_empty(&self) -> bool {
+        *self == Identifiers::default()
+    }
+
+    /// The ISBN shown to the user: ISBN-13 when known, otherwise ISBN-10.
+    pub fn isbn(&self) -> Option<&String> {
+        self.isbn_13.as_ref().or(self.isbn_10.as_ref())
+    }
+
+    pub fn set_isbn(&mut self, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        if text.is_empty() {
+            self.isbn_13 = None;
+            self.isbn_10 = None;
+            return Ok(());
+        }
+        let (isbn_13, isbn_10) = isbn::parse(text)?;
+        self.isbn_13 = Some(isbn_13);
+        self.isbn_10 = isbn_10;
+        Ok(())
+    }
+
+    pub fn has_isbn(&self, text: &str) -> bool {
+        match isbn::parse(text) {
+            Ok((isbn_13, _)) => self.isbn_13.as_deref() == Some(isbn_13.as_str()),
+            Err(_) => false,
+        }
+    }
+
+    pub fn set_asin(&mut self, text: &str) -> Result<(), String> {
+        self.asin = parse_identifier(text, "ASIN", |asin| {
+            asin.len() == 10 && asin.chars().all(|c| c.is_ascii_alphanumeric())
+        })?
+        .map(|asin| asin.to_ascii_uppercase());
+        Ok(())
+    }
+
+    pub fn set_openlibrary_id(&mut self, text: &str) -> Result<(), String> {
+        self.openlibrary_id = parse_identifier(text, "OpenLibrary ID", |id| {
+            let Some(rest) = id.strip_prefix("OL") else {
+                return false;
+            };
+            let Some(number) = rest.strip_suffix(['M', 'W', 'A']) else {
+                return false;
+            };
+            !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+        })?;
+        Ok(())
+    }
+
+    pub fn set_goodreads_id(&mut self, text: &str) -> Result<(), String> {
+        self.goodreads_id = parse_identifier(text, "Goodreads ID", |id| {
+            id.chars().all(|c| c.is_ascii_digit())
+        })?;
+        Ok(())
+    }
+}
+
+fn parse_identifier(
+    text: &str,
+    name: &str,
+    is_valid: impl Fn(&str) -> bool,
+) -> Result<Option<String>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if !is_valid(text) {
+        return Err(format!("'{text}' is not a valid {name}"));
+    }
+    Ok(Some(text.to_string()))
+}
@@ -0,0 +1,128 @@
+/// Strips hyphens and spaces and checks the ISBN-10 or ISBN-13 checksum.
+/// Returns the ISBN-13 form and, if it exists, the ISBN-10 form.
+pub fn parse(text: &str) -> Result<(String, Option<String>), String> {
+    let digits: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    match digits.len() {
+        10 => {
+            if !is_valid_isbn10(&digits) {
+                return Err(format!("'{}' is not a valid ISBN-10", text.trim()));
+            }
+            Ok((isbn10_to_13(&digits), Some(digits)))
+        }
+        13 => {
+            if !is_valid_isbn13(&digits) {
+                return Err(format!("'{}' is not a valid ISBN-13", text.trim()));
+            }
+            let isbn10 = isbn13_to_10(&digits);
+            Ok((digits, isbn10))
+        }
+        _ => Err("ISBN must have 10 or 13 digits".to_string()),
+    }
+}
+
+pub fn is_valid_isbn10(isbn: &str) -> bool {
+    let chars: Vec<char> = isbn.chars().collect();
+    if chars.len() != 10 || !chars[..9].iter().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let Some(check) = isbn10_digit_value(chars[9]) else {
+        return false;
+    };
+    let sum: u32 = chars[..9]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (10 - i as u32) * c.to_digit(10).unwrap())
+        .sum::<u32>()
+        + check;
+    sum.is_multiple_of(11)
+}
+
+pub fn is_valid_isbn13(isbn: &str) -> bool {
+    if isbn.len() != 13 || !isbn.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    isbn13_check_digit(&isbn[..12]) == isbn.chars().last().and_then(|c| c.to_digit(10))
+}
+
+/// Prefixes an already validated ISBN-10 with `978` and recomputes the check digit.
+pub fn isbn10_to_13(isbn10: &str) -> String {
+    let body = format!("978{}", &isbn10[..9]);
+    let check = isbn13_check_digit(&body).unwrap_or(0);
+    format!("{body}{check}")
+}
+
+/// Only ISBN-13s in the `978` range have an ISBN-10 equivalent.
+pub fn isbn13_to_10(isbn13: &str) -> Option<String> {
+    let body = isbn13.strip_prefix("978")?.get(..9)?;
+    let sum: u32 = body
+        .chars()
+        .enumerate()
+        .map(|(i, c)| (10 - i as u32) * c.to_digit(10).unwrap_or(0))
+        .sum();
+    let check = match (11 - sum % 11) % 11 {
+        10 => 'X',
+        d => char::from_digit(d, 10)?,
+    };
+    Some(format!("{body}{check}"))
+}
+
+fn isbn13_check_digit(body: &str) -> Option<u32> {
+    let mut sum = 0;
+    for (i, c) in body.chars().enumerate() {
+        let digit = c.to_digit(10)?;
+        sum += if i % 2 == 0 { digit } else { digit * 3 };
+    }
+    Some((10 - sum % 10) % 10)
+}
+
+fn isbn10_digit_value(c: char) -> Option<u32> {
+    match c {
+        'X' => Some(10),
+        c => c.to_digit(10),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("0306406152", "9780306406157", Some("0306406152"))]
+    #[case("9780306406157", "9780306406157", Some("0306406152"))]
+    #[case("978-0-306-40615-7", "9780306406157", Some("0306406152"))]
+    #[case("0 441 17271 7", "9780441172719", Some("0441172717"))]
+    #[case("080442957X", "9780804429573", Some("080442957X"))]
+    #[case("080442957x", "9780804429573", Some("080442957X"))]
+    #[case("9780804429573", "9780804429573", Some("080442957X"))]
+    #[case("9791034304608", "9791034304608", None)]
+    fn parses_valid_isbns(#[case] text: &str, #[case] isbn13: &str, #[case] isbn10: Option<&str>) {
+        assert_eq!(
+            parse(text),
+            Ok((isbn13.to_string(), isbn10.map(str::to_string)))
+        );
+    }
+
+    #[rstest]
+    #[case("0306406153")]
+    #[case("9780306406158")]
+    #[case("03064061X2")]
+    #[case("12345")]
+    #[case("97803064061577")]
+    #[case("")]
+    fn rejects_invalid_isbns(#[case] text: &str) {
+        assert!(parse(text).is_err());
+    }
+
+    #[rstest]
+    #[case("0306406152", "9780306406157")]
+    #[case("080442957X", "9780804429573")]
+    fn converts_between_forms(#[case] isbn10: &str, #[case] isbn13: &str) {
+        assert_eq!(isbn10_to_13(isbn10), isbn13);
+        assert_eq!(isbn13_to_10(isbn13).as_deref(), Some(isbn10));
+    }
+}
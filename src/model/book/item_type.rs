@@ -0,0 +1,65 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum ItemType {
+    #[default]
+    Book,
+    Comic,
+    Magazine,
+    Paper,
+}
+
+impl ItemType {
+    pub const ALL: [ItemType; 4] = [
+        ItemType::Book,
+        ItemType::Comic,
+        ItemType::Magazine,
+        ItemType::Paper,
+    ];
+
+    pub fn index(&self) -> usize {
+        match self {
+            ItemType::Book => 0,
+            ItemType::Comic => 1,
+            ItemType::Magazine => 2,
+            ItemType::Paper => 3,
+        }
+    }
+
+    pub fn from(index: usize) -> Self {
+        match index {
+            0 => ItemType::Book,
+            1 => ItemType::Comic,
+            2 => ItemType::Magazine,
+            3 => ItemType::Paper,
+            _ => {
+                error!("Invalid index for item type: {index}");
+                panic!("Invalid index for item type");
+            }
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemType::Book => "BOOK",
+            ItemType::Comic => "COMIC",
+            ItemType::Magazine => "MAGAZINE",
+            ItemType::Paper => "PAPER",
+        }
+    }
+
+    pub fn plural(&self) -> &'static str {
+        match self {
+            ItemType::Book => "Books",
+            ItemType::Comic => "Comics",
+            ItemType::Magazine => "Magazines",
+            ItemType::Paper => "Papers",
+        }
+    }
+
+    // Magazines are usually credited to a publication rather than to a person.
+    pub fn requires_authors(&self) -> bool {
+        *self != ItemType::Magazine
+    }
+}
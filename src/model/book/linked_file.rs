@@ -0,0 +1,46 @@
+use flate2::Crc;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+const FINGERPRINT_BYTES: u64 = 1024 * 1024;
+
+/// A file on disk that a book was scanned from. The size and a checksum of the
+/// beginning of the file recognise it again after it was moved or renamed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LinkedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub fingerprint: u32,
+}
+
+impl LinkedFile {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::from_file(path, &File::open(path)?)
+    }
+
+    /// Reads the size and checksum from a file that is already open at the start.
+    pub fn from_file(path: &Path, file: &File) -> io::Result<Self> {
+        let size = file.metadata()?.len();
+        let mut head = vec![];
+        file.take(FINGERPRINT_BYTES).read_to_end(&mut head)?;
+        let mut crc = Crc::new();
+        crc.update(&head);
+        Ok(Self {
+            path: path.to_path_buf(),
+            size,
+            fingerprint: crc.sum(),
+        })
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    pub fn same_content(&self, other: &LinkedFile) -> bool {
+        self.size == other.size && self.fingerprint == other.fingerprint
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{metadata::Metadata, model::book::Book};
+use uuid::Uuid;
+
+impl Book {
+    /// A new to-read book with everything the metadata knows.
+    pub fn from_metadata(title: String, metadata: &Metadata) -> Self {
+        let mut book = Book {
+            id: Uuid::new_v4(),
+            cover_path: Some(Self::default_cover_path(&title)),
+            title,
+            ..Book::default()
+        };
+        book.fill_missing(metadata);
+        book
+    }
+
+    /// Whether the metadata describes this book: the same ISBN, or the same
+    /// title and at least one shared author.
+    pub fn matches_metadata(&self, metadata: &Metadata) -> bool {
+        if let Some(isbn) = &metadata.isbn {
+            if self.identifiers.has_isbn(isbn) {
+                return true;
+            }
+        }
+        metadata
+            .title
+            .as_ref()
+            .is_some_and(|t| t.to_lowercase() == self.title.to_lowercase())
+            && metadata.authors.iter().any(|a| self.authors.contains(a))
+    }
+
+    /// Same ISBN, or the same title and a shared author.
+    pub fn is_same_book(&self, other: &Book) -> bool {
+        if let Some(isbn) = &other.identifiers.isbn_13 {
+            if self.identifiers.isbn_13.as_ref() == Some(isbn) {
+                return true;
+            }
+        }
+        self.title.to_lowercase() == other.title.to_lowercase()
+            && other.authors.iter().any(|a| self.authors.contains(a))
+    }
+
+    /// Copies the metadata into fields left empty, so details entered by hand
+    /// are never overwritten. Returns whether anything changed.
+    pub fn fill_missing(&mut self, metadata: &Metadata) -> bool {
+        let before = self.clone();
+        if self.authors.is_empty() {
+            self.authors = metadata.authors.clone();
+        }
+        fill(&mut self.author_sort, &metadata.author_sort);
+        if self.year == 0 {
+            self.year = metadata.year.unwrap_or_default();
+        }
+        if self.pages == 0 {
+            self.pages = metadata.pages.unwrap_or_default();
+        }
+        fill(&mut self.language, &metadata.language);
+        fill(&mut self.series, &metadata.series);
+        if self.series_index.is_none() {
+            self.series_index = metadata.series_index;
+        }
+        if self.tags.is_empty() {
+            self.tags = metadata.tags.clone();
+        }
+        fill(&mut self.publisher, &metadata.publisher);
+        if self.rating == 0 {
+            self.rating = metadata.rating.unwrap_or_default();
+        }
+
+        let identifiers = &mut self.identifiers;
+        if let (None, Some(isbn)) = (identifiers.isbn(), &metadata.isbn) {
+            identifiers.set_isbn(isbn).ok();
+        }
+        if let (None, Some(asin)) = (&identifiers.asin, &metadata.asin) {
+            identifiers.set_asin(asin).ok();
+        }
+        if let (None, Some(id)) = (&identifiers.openlibrary_id, &metadata.openlibrary_id) {
+            identifiers.set_openlibrary_id(id).ok();
+        }
+        if let (None, Some(id)) = (&identifiers.goodreads_id, &metadata.goodreads_id) {
+            identifiers.set_goodreads_id(id).ok();
+        }
+
+        self.authors != before.authors
+            || self.author_sort != before.author_sort
+            || self.year != before.year
+            || self.pages != before.pages
+            || self.language != before.language
+            || self.series != before.series
+            || self.series_index != before.series_index
+            || self.tags != before.tags
+            || self.publisher != before.publisher
+            || self.rating != before.rating
+            || self.identifiers != before.identifiers
+    }
+}
+
+fn fill(field: &mut Option<String>, value: &Option<String>) {
+    if field.is_none() {
+        field.clone_from(value);
+    }
+}
@@ -1,19 +1,82 @@
+pub mod author_name;
+pub mod citation;
+pub mod highlight;
+pub mod identifiers;
+pub mod isbn;
+pub mod item_type;
+pub mod linked_file;
+mod merge;
+pub mod reading_position;
 pub mod reading_status;
 
-use crate::model::{book::reading_status::ReadingStatus, book_info::form::BookForm};
-use chrono::NaiveDate;
+use crate::model::{
+    book::{
+        highlight::Highlight, identifiers::Identifiers, item_type::ItemType,
+        linked_file::LinkedFile, reading_position::ReadingPosition, reading_status::ReadingStatus,
+    },
+    book_info::{form::BookForm, form_field::FormField, MAX_RATING},
+};
+use chrono::{Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use uuid::Uuid;
 
+/// Where covers are kept, next to the state file.
+pub const COVERS_DIR: &str = "./covers";
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Book {
     pub id: Uuid,
+    #[serde(default)]
+    pub item_type: ItemType,
     pub title: String,
     pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_sort: Option<String>,
     pub year: u16,
     pub pages: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<String>,
+    #[serde(default, skip_serializing_if = "Identifiers::is_empty")]
+    pub identifiers: Identifiers,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_index: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// The ebook this entry was scanned from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<LinkedFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<ReadingPosition>,
+    /// Time spent reading as tracked by an e-reader.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub reading_seconds: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<Highlight>,
+    /// Where the physical copy is kept, e.g. a room and shelf.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Left out of the generated site.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub private: bool,
     pub reading_status: ReadingStatus,
+    /// Days the book was started, added when its status becomes reading.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub started_at: Vec<NaiveDate>,
     pub finished_at: Vec<NaiveDate>,
     pub rating: u8,
     pub cover_path: Option<PathBuf>,
@@ -46,7 +109,7 @@ impl Book {
             match existing_book {
                 Some(existing_book) => match existing_book.finished_at.last() {
                     Some(most_recent_finished_at) => {
-                        if new_finished_at > most_recent_finished_at.clone() {
+                        if new_finished_at > *most_recent_finished_at {
                             finished_at = existing_book.finished_at.clone();
                             finished_at.push(new_finished_at);
                         } else {
@@ -79,23 +142,209 @@ impl Book {
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
-        if authors.is_empty() {
+        if authors.is_empty() && form.item_type.requires_authors() {
             return Err("At least one author is required".to_string());
         }
 
+        let item_type = form.item_type;
+        let (mut volume, mut issue, mut issue_date, mut doi, mut venue) =
+            (None, None, None, None, None);
+        match item_type {
+            ItemType::Book => {}
+            ItemType::Comic => {
+                volume = parse_optional_number(&form.volume.text, "Volume")?;
+                issue = parse_optional_number(&form.issue.text, "Issue")?;
+                if volume.is_none() && issue.is_none() {
+                    return Err("Comic needs a volume or an issue number".to_string());
+                }
+            }
+            ItemType::Magazine => {
+                let issue_date_str = form.issue_date.text.trim();
+                if issue_date_str.is_empty() {
+                    return Err("Issue date cannot be empty".to_string());
+                }
+                issue_date = Some(
+                    issue_date_str
+                        .parse::<NaiveDate>()
+                        .map_err(|_| "Issue date must be a valid date in format YYYY-MM-DD")?,
+                );
+            }
+            ItemType::Paper => {
+                let doi_str = form.doi.text.trim();
+                if !doi_str.is_empty() {
+                    if !is_valid_doi(doi_str) {
+                        return Err("DOI must look like 10.<registrant>/<suffix>".to_string());
+                    }
+                    doi = Some(doi_str.to_string());
+                }
+                let venue_str = form.venue.text.trim();
+                if !venue_str.is_empty() {
+                    venue = Some(venue_str.to_string());
+                }
+            }
+        }
+
+        let mut identifiers = Identifiers::default();
+        if FormField::order(item_type).contains(&FormField::Isbn) {
+            identifiers.set_isbn(&form.isbn.text)?;
+            identifiers.set_asin(&form.asin.text)?;
+            identifiers.set_openlibrary_id(&form.openlibrary_id.text)?;
+            identifiers.set_goodreads_id(&form.goodreads_id.text)?;
+        }
+
+        // Details the form does not edit carry over from the book being edited.
+        let edited = existing_book.filter(|b| Some(b.id) == form.id);
+        let mut started_at = edited.map(|b| b.started_at.clone()).unwrap_or_default();
+        if form.reading_status == ReadingStatus::Reading
+            && edited.is_none_or(|b| b.reading_status != ReadingStatus::Reading)
+        {
+            start_reading(&mut started_at, &finished_at, Local::now().date_naive());
+        }
+
         Ok(Self {
             id: form.id.unwrap_or(Uuid::new_v4()),
+            item_type,
             title,
             authors,
+            author_sort: edited.and_then(|b| b.author_sort.clone()),
             year,
             pages,
+            volume,
+            issue,
+            issue_date,
+            doi,
+            venue,
+            identifiers,
+            language: edited.and_then(|b| b.language.clone()),
+            series: edited.and_then(|b| b.series.clone()),
+            series_index: edited.and_then(|b| b.series_index),
+            tags: edited.map(|b| b.tags.clone()).unwrap_or_default(),
+            publisher: edited.and_then(|b| b.publisher.clone()),
+            file: edited.and_then(|b| b.file.clone()),
+            position: edited.and_then(|b| b.position.clone()),
+            reading_seconds: edited.map_or(0, |b| b.reading_seconds),
+            highlights: edited.map(|b| b.highlights.clone()).unwrap_or_default(),
+            location: Some(form.location.text.trim().to_string()).filter(|l| !l.is_empty()),
+            private: edited.is_some_and(|b| b.private),
             reading_status: form.reading_status.clone(),
+            started_at,
             finished_at,
             rating,
-            cover_path: Some(PathBuf::from("./covers").join(format!("{}.jpg", form.title.text))),
+            cover_path: Some(Self::default_cover_path(&form.title.text)),
         })
     }
 
+    /// Fails when another of `books` already has the ISBN.
+    pub fn check_duplicate_isbn(&self, books: &[Book]) -> Result<(), String> {
+        let Some(isbn) = &self.identifiers.isbn_13 else {
+            return Ok(());
+        };
+        match books
+            .iter()
+            .find(|b| b.id != self.id && b.identifiers.isbn_13.as_ref() == Some(isbn))
+        {
+            Some(duplicate) => Err(format!(
+                "ISBN {isbn} already belongs to '{}'",
+                duplicate.title
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies the rules of the book form to a book that was not entered
+    /// through it, such as one sent to the API.
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.title = self.title.trim().to_string();
+        if self.title.is_empty() {
+            return Err("Title cannot be empty".to_string());
+        }
+        self.authors = self
+            .authors
+            .iter()
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty())
+            .collect();
+        if self.authors.is_empty() && self.item_type.requires_authors() {
+            return Err("At least one author is required".to_string());
+        }
+        if self.rating > MAX_RATING {
+            return Err(format!("Rating must be at most {MAX_RATING}"));
+        }
+        match self.item_type {
+            ItemType::Book => {}
+            ItemType::Comic => {
+                if self.volume.is_none() && self.issue.is_none() {
+                    return Err("Comic needs a volume or an issue number".to_string());
+                }
+            }
+            ItemType::Magazine => {
+                if self.issue_date.is_none() {
+                    return Err("Issue date cannot be empty".to_string());
+                }
+            }
+            ItemType::Paper => {
+                if self.doi.as_deref().is_some_and(|doi| !is_valid_doi(doi)) {
+                    return Err("DOI must look like 10.<registrant>/<suffix>".to_string());
+                }
+            }
+        }
+        let given = self.identifiers.clone();
+        self.identifiers
+            .set_isbn(given.isbn().map_or("", String::as_str))?;
+        self.identifiers
+            .set_asin(given.asin.as_deref().unwrap_or_default())?;
+        self.identifiers
+            .set_openlibrary_id(given.openlibrary_id.as_deref().unwrap_or_default())?;
+        self.identifiers
+            .set_goodreads_id(given.goodreads_id.as_deref().unwrap_or_default())?;
+        self.started_at.sort();
+        self.finished_at.sort();
+        Ok(())
+    }
+
+    /// Records a start unless the last one has not been finished yet.
+    pub fn start_reading(&mut self, today: NaiveDate) {
+        start_reading(&mut self.started_at, &self.finished_at, today);
+    }
+
+    /// Every finished read with the day it was started, when known: the
+    /// last start after the previous finish.
+    pub fn reads(&self) -> Vec<(Option<NaiveDate>, NaiveDate)> {
+        let mut previous = None;
+        self.finished_at
+            .iter()
+            .map(|&finished| {
+                let started = self
+                    .started_at
+                    .iter()
+                    .filter(|&&s| s <= finished && previous.is_none_or(|p| s > p))
+                    .max()
+                    .copied();
+                previous = Some(finished);
+                (started, finished)
+            })
+            .collect()
+    }
+
+    /// Where the cover of a book with this title is kept. Titles come from
+    /// lookups and ebook files, so anything that would leave `covers/` is
+    /// replaced.
+    pub fn default_cover_path(title: &str) -> PathBuf {
+        let name = title
+            .chars()
+            .map(|c| {
+                if matches!(c, '/' | '\\' | '\0') {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        let name = name.trim_start_matches('.');
+        let name = if name.is_empty() { "untitled" } else { name };
+        PathBuf::from(COVERS_DIR).join(format!("{name}.jpg"))
+    }
+
     pub fn title_normalized(&self) -> String {
         self.title
             .strip_prefix("The ")
@@ -105,7 +354,20 @@ impl Book {
             .to_lowercase()
     }
 
-    pub fn author_with_initials(author: &String) -> String {
+    /// Matches an ISBN exactly, otherwise looks for the query in the title and authors.
+    pub fn matches_query(&self, query: &str) -> bool {
+        if self.identifiers.has_isbn(query) {
+            return true;
+        }
+        let query = query.trim().to_lowercase();
+        self.title.to_lowercase().contains(&query)
+            || self
+                .authors
+                .iter()
+                .any(|a| a.to_lowercase().contains(&query))
+    }
+
+    pub fn author_with_initials(author: &str) -> String {
         let parts: Vec<&str> = author.split_whitespace().collect();
         if parts.is_empty() {
             return String::new();
@@ -124,3 +386,59 @@ impl Book {
         }
     }
 }
+
+fn parse_optional_number(text: &str, name: &str) -> Result<Option<u16>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse::<u16>()
+        .map(Some)
+        .map_err(|_| format!("{name} must be a valid number"))
+}
+
+fn is_valid_doi(doi: &str) -> bool {
+    match doi.split_once('/') {
+        Some((prefix, suffix)) => {
+            prefix.starts_with("10.")
+                && prefix[3..].chars().all(|c| c.is_ascii_digit() || c == '.')
+                && prefix.len() > 3
+                && !suffix.is_empty()
+        }
+        None => false,
+    }
+}
+
+fn start_reading(started_at: &mut Vec<NaiveDate>, finished_at: &[NaiveDate], today: NaiveDate) {
+    let unfinished = started_at
+        .last()
+        .is_some_and(|s| finished_at.last().is_none_or(|f| s > f));
+    if !unfinished {
+        started_at.push(today);
+    }
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Dune", "./covers/Dune.jpg")]
+    #[case("Either/Or", "./covers/Either_Or.jpg")]
+    #[case("../../etc/passwd", "./covers/_.._etc_passwd.jpg")]
+    #[case("..\\secret", "./covers/_secret.jpg")]
+    #[case(".hidden", "./covers/hidden.jpg")]
+    #[case("...", "./covers/untitled.jpg")]
+    fn keeps_covers_inside_the_covers_folder(#[case] title: &str, #[case] path: &str) {
+        assert_eq!(Book::default_cover_path(title), PathBuf::from(path));
+    }
+}
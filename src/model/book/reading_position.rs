@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// Where reading stopped in the linked ebook.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReadingPosition {
+    pub chapter: usize,
+    pub paragraph: usize,
+    /// Percentage of the book's paragraphs read.
+    pub progress: u8,
+}
@@ -1,33 +1,67 @@
-use crate::model::{
-    book::{reading_status::ReadingStatus, Book},
-    book_info::{
-        form_field::FormField, text_input::TextInput, DEFAULT_RATING, MAX_RATING, MIN_RATING,
+use crate::{
+    metadata::{LookupQuery, Metadata},
+    model::{
+        book::{item_type::ItemType, reading_status::ReadingStatus, Book},
+        book_info::{
+            form_field::FormField, text_input::TextInput, DEFAULT_RATING, MAX_RATING, MIN_RATING,
+        },
     },
 };
+use std::{fs, path::PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
 #[derive(Debug)]
 pub struct BookForm {
     pub id: Option<Uuid>,
+    pub item_type: ItemType,
     pub title: TextInput,
     pub authors: TextInput,
     pub year: TextInput,
     pub pages: TextInput,
+    pub volume: TextInput,
+    pub issue: TextInput,
+    pub issue_date: TextInput,
+    pub doi: TextInput,
+    pub venue: TextInput,
+    pub isbn: TextInput,
+    pub asin: TextInput,
+    pub openlibrary_id: TextInput,
+    pub goodreads_id: TextInput,
+    pub location: TextInput,
     pub reading_status: ReadingStatus,
     pub finished_at: TextInput,
     pub rating: u8,
     pub active: FormField,
     pub error: Option<String>,
+    pub suggestions: Vec<Metadata>,
+    pub selected_suggestion: usize,
+    /// A cover fetched by a lookup, kept in a temporary file until the form
+    /// is saved.
+    pub downloaded_cover: Option<PathBuf>,
 }
 
 impl BookForm {
     pub fn from(book: &Book) -> Self {
         Self {
             id: Some(book.id),
+            item_type: book.item_type,
             title: TextInput::new(book.title.clone()),
             authors: TextInput::new(book.authors.join(", ")),
             year: TextInput::new(book.year.to_string()),
             pages: TextInput::new(book.pages.to_string()),
+            volume: TextInput::new(optional_to_string(book.volume)),
+            issue: TextInput::new(optional_to_string(book.issue)),
+            issue_date: TextInput::new(optional_to_string(book.issue_date)),
+            doi: TextInput::new(book.doi.clone().unwrap_or_default()),
+            venue: TextInput::new(book.venue.clone().unwrap_or_default()),
+            isbn: TextInput::new(book.identifiers.isbn().cloned().unwrap_or_default()),
+            asin: TextInput::new(book.identifiers.asin.clone().unwrap_or_default()),
+            openlibrary_id: TextInput::new(
+                book.identifiers.openlibrary_id.clone().unwrap_or_default(),
+            ),
+            goodreads_id: TextInput::new(book.identifiers.goodreads_id.clone().unwrap_or_default()),
+            location: TextInput::new(book.location.clone().unwrap_or_default()),
             reading_status: book.reading_status.clone(),
             finished_at: TextInput::new(
                 book.finished_at
@@ -38,65 +72,191 @@ impl BookForm {
             rating: book.rating,
             active: FormField::Title,
             error: None,
+            suggestions: vec![],
+            selected_suggestion: 0,
+            downloaded_cover: None,
         }
     }
 
     pub fn default() -> Self {
         Self {
             id: None,
+            item_type: ItemType::Book,
             title: TextInput::default(),
             authors: TextInput::default(),
             year: TextInput::default(),
             pages: TextInput::default(),
+            volume: TextInput::default(),
+            issue: TextInput::default(),
+            issue_date: TextInput::default(),
+            doi: TextInput::default(),
+            venue: TextInput::default(),
+            isbn: TextInput::default(),
+            asin: TextInput::default(),
+            openlibrary_id: TextInput::default(),
+            goodreads_id: TextInput::default(),
+            location: TextInput::default(),
             reading_status: ReadingStatus::ToRead,
             finished_at: TextInput::default(),
             rating: DEFAULT_RATING,
             active: FormField::Title,
             error: None,
+            suggestions: vec![],
+            selected_suggestion: 0,
+            downloaded_cover: None,
         }
     }
 
-    pub fn insert_char(&mut self, c: char) {
+    pub fn fields(&self) -> &'static [FormField] {
+        FormField::order(self.item_type)
+    }
+
+    pub fn text_input(&self, field: FormField) -> Option<&TextInput> {
+        match field {
+            FormField::Title => Some(&self.title),
+            FormField::Authors => Some(&self.authors),
+            FormField::Year => Some(&self.year),
+            FormField::Pages => Some(&self.pages),
+            FormField::Volume => Some(&self.volume),
+            FormField::Issue => Some(&self.issue),
+            FormField::IssueDate => Some(&self.issue_date),
+            FormField::Doi => Some(&self.doi),
+            FormField::Venue => Some(&self.venue),
+            FormField::Isbn => Some(&self.isbn),
+            FormField::Asin => Some(&self.asin),
+            FormField::OpenLibraryId => Some(&self.openlibrary_id),
+            FormField::GoodreadsId => Some(&self.goodreads_id),
+            FormField::Location => Some(&self.location),
+            FormField::FinishedAt => Some(&self.finished_at),
+            _ => None,
+        }
+    }
+
+    fn active_text_input_mut(&mut self) -> Option<&mut TextInput> {
         match self.active {
-            FormField::Title => self.title.insert_char(c),
-            FormField::Authors => self.authors.insert_char(c),
-            FormField::Year => self.year.insert_char(c),
-            FormField::Pages => self.pages.insert_char(c),
-            FormField::FinishedAt => self.finished_at.insert_char(c),
-            _ => {}
+            FormField::Title => Some(&mut self.title),
+            FormField::Authors => Some(&mut self.authors),
+            FormField::Year => Some(&mut self.year),
+            FormField::Pages => Some(&mut self.pages),
+            FormField::Volume => Some(&mut self.volume),
+            FormField::Issue => Some(&mut self.issue),
+            FormField::IssueDate => Some(&mut self.issue_date),
+            FormField::Doi => Some(&mut self.doi),
+            FormField::Venue => Some(&mut self.venue),
+            FormField::Isbn => Some(&mut self.isbn),
+            FormField::Asin => Some(&mut self.asin),
+            FormField::OpenLibraryId => Some(&mut self.openlibrary_id),
+            FormField::GoodreadsId => Some(&mut self.goodreads_id),
+            FormField::Location => Some(&mut self.location),
+            FormField::FinishedAt => Some(&mut self.finished_at),
+            _ => None,
+        }
+    }
+
+    /// An ISBN identifies an edition exactly, so it wins over the title.
+    pub fn lookup_query(&self) -> Option<LookupQuery> {
+        let isbn = self.isbn.text.trim();
+        let title = self.title.text.trim();
+        if !isbn.is_empty() {
+            Some(LookupQuery::Isbn(isbn.replace(['-', ' '], "")))
+        } else if !title.is_empty() {
+            Some(LookupQuery::Title(title.to_string()))
+        } else {
+            None
+        }
+    }
+
+    pub fn apply_metadata(&mut self, metadata: &Metadata) {
+        fill(&mut self.title, metadata.title.clone());
+        if !metadata.authors.is_empty() {
+            self.authors = TextInput::new(metadata.authors.join(", "));
+        }
+        fill(&mut self.year, metadata.year.map(|y| y.to_string()));
+        fill(&mut self.pages, metadata.pages.map(|p| p.to_string()));
+        fill(&mut self.isbn, metadata.isbn.clone());
+        fill(&mut self.asin, metadata.asin.clone());
+        fill(&mut self.openlibrary_id, metadata.openlibrary_id.clone());
+        fill(&mut self.goodreads_id, metadata.goodreads_id.clone());
+    }
+
+    /// Replaces the cover of an earlier lookup.
+    pub fn set_downloaded_cover(&mut self, path: PathBuf) {
+        self.discard_downloaded_cover();
+        self.downloaded_cover = Some(path);
+    }
+
+    /// Deletes the cover of a lookup when the form is left without saving.
+    pub fn discard_downloaded_cover(&mut self) {
+        if let Some(path) = self.downloaded_cover.take() {
+            fs::remove_file(path).ok();
+        }
+    }
+
+    pub fn set_suggestions(&mut self, suggestions: Vec<Metadata>) {
+        self.suggestions = suggestions;
+        self.selected_suggestion = 0;
+    }
+
+    pub fn next_suggestion(&mut self) {
+        if !self.suggestions.is_empty() {
+            self.selected_suggestion = (self.selected_suggestion + 1) % self.suggestions.len();
+        }
+    }
+
+    pub fn previous_suggestion(&mut self) {
+        let len = self.suggestions.len();
+        if len > 0 {
+            self.selected_suggestion = (self.selected_suggestion + len - 1) % len;
+        }
+    }
+
+    /// A suggestion is accepted with → once the cursor is at the end of the title.
+    pub fn can_accept_suggestion(&self) -> bool {
+        self.active == FormField::Title
+            && !self.suggestions.is_empty()
+            && self.title.cursor == self.title.text.graphemes(true).count()
+    }
+
+    pub fn accept_suggestion(&mut self) {
+        if let Some(metadata) = self.suggestions.get(self.selected_suggestion).cloned() {
+            self.apply_metadata(&metadata);
+        }
+        self.set_suggestions(vec![]);
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if let Some(input) = self.active_text_input_mut() {
+            input.insert_char(c);
         }
     }
 
     pub fn delete_char(&mut self) {
-        match self.active {
-            FormField::Title => self.title.delete_char(),
-            FormField::Authors => self.authors.delete_char(),
-            FormField::Year => self.year.delete_char(),
-            FormField::Pages => self.pages.delete_char(),
-            FormField::FinishedAt => self.finished_at.delete_char(),
-            _ => {}
-        };
+        if let Some(input) = self.active_text_input_mut() {
+            input.delete_char();
+        }
     }
 
     pub fn move_cursor_left(&mut self) {
-        match self.active {
-            FormField::Title => self.title.move_cursor_left(),
-            FormField::Authors => self.authors.move_cursor_left(),
-            FormField::Year => self.year.move_cursor_left(),
-            FormField::Pages => self.pages.move_cursor_left(),
-            FormField::FinishedAt => self.finished_at.move_cursor_left(),
-            _ => {}
+        if let Some(input) = self.active_text_input_mut() {
+            input.move_cursor_left();
         }
     }
 
     pub fn move_cursor_right(&mut self) {
-        match self.active {
-            FormField::Title => self.title.move_cursor_right(),
-            FormField::Authors => self.authors.move_cursor_right(),
-            FormField::Year => self.year.move_cursor_right(),
-            FormField::Pages => self.pages.move_cursor_right(),
-            FormField::FinishedAt => self.finished_at.move_cursor_right(),
-            _ => {}
+        if let Some(input) = self.active_text_input_mut() {
+            input.move_cursor_right();
+        }
+    }
+
+    pub fn increase_item_type(&mut self) {
+        if self.active == FormField::ItemType && self.item_type != ItemType::Paper {
+            self.item_type = ItemType::from(self.item_type.index() + 1);
+        }
+    }
+
+    pub fn decrease_item_type(&mut self) {
+        if self.active == FormField::ItemType && self.item_type != ItemType::Book {
+            self.item_type = ItemType::from(self.item_type.index() - 1);
         }
     }
 
@@ -114,21 +274,31 @@ impl BookForm {
 
     pub fn increase_rating(&mut self) {
         if self.active == FormField::Rating && self.rating < MAX_RATING {
-            self.rating = self.rating + 1;
+            self.rating += 1;
         }
     }
 
     pub fn decrease_rating(&mut self) {
         if self.active == FormField::Rating && self.rating > MIN_RATING {
-            self.rating = self.rating - 1;
+            self.rating -= 1;
         }
     }
 
     pub fn next_field(&mut self) {
-        self.active = self.active.next();
+        self.active = self.active.next(self.item_type);
     }
 
     pub fn previous_field(&mut self) {
-        self.active = self.active.prev();
+        self.active = self.active.prev(self.item_type);
+    }
+}
+
+fn fill(input: &mut TextInput, value: Option<String>) {
+    if let Some(value) = value {
+        *input = TextInput::new(value);
     }
 }
+
+fn optional_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
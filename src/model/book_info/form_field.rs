@@ -1,33 +1,108 @@
+use crate::model::book::item_type::ItemType;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum FormField {
+    ItemType,
     Title,
     Authors,
     Year,
     Pages,
+    Volume,
+    Issue,
+    IssueDate,
+    Doi,
+    Venue,
+    Isbn,
+    Asin,
+    OpenLibraryId,
+    GoodreadsId,
+    Location,
     ReadingStatus,
     FinishedAt,
     Rating,
 }
 
 impl FormField {
-    pub const ORDER: [FormField; 7] = [
+    pub const BOOK_ORDER: [FormField; 13] = [
+        FormField::ItemType,
+        FormField::Title,
+        FormField::Authors,
+        FormField::Year,
+        FormField::Pages,
+        FormField::Isbn,
+        FormField::Asin,
+        FormField::OpenLibraryId,
+        FormField::GoodreadsId,
+        FormField::Location,
+        FormField::ReadingStatus,
+        FormField::FinishedAt,
+        FormField::Rating,
+    ];
+
+    pub const COMIC_ORDER: [FormField; 15] = [
+        FormField::ItemType,
+        FormField::Title,
+        FormField::Authors,
+        FormField::Volume,
+        FormField::Issue,
+        FormField::Year,
+        FormField::Pages,
+        FormField::Isbn,
+        FormField::Asin,
+        FormField::OpenLibraryId,
+        FormField::GoodreadsId,
+        FormField::Location,
+        FormField::ReadingStatus,
+        FormField::FinishedAt,
+        FormField::Rating,
+    ];
+
+    pub const MAGAZINE_ORDER: [FormField; 10] = [
+        FormField::ItemType,
         FormField::Title,
         FormField::Authors,
+        FormField::IssueDate,
         FormField::Year,
         FormField::Pages,
+        FormField::Location,
         FormField::ReadingStatus,
         FormField::FinishedAt,
         FormField::Rating,
     ];
 
-    pub fn next(&self) -> Self {
-        let pos = Self::ORDER.iter().position(|f| f == self).unwrap();
-        Self::ORDER[(pos + 1) % Self::ORDER.len()]
+    pub const PAPER_ORDER: [FormField; 11] = [
+        FormField::ItemType,
+        FormField::Title,
+        FormField::Authors,
+        FormField::Venue,
+        FormField::Doi,
+        FormField::Year,
+        FormField::Pages,
+        FormField::Location,
+        FormField::ReadingStatus,
+        FormField::FinishedAt,
+        FormField::Rating,
+    ];
+
+    pub fn order(item_type: ItemType) -> &'static [FormField] {
+        match item_type {
+            ItemType::Book => &Self::BOOK_ORDER,
+            ItemType::Comic => &Self::COMIC_ORDER,
+            ItemType::Magazine => &Self::MAGAZINE_ORDER,
+            ItemType::Paper => &Self::PAPER_ORDER,
+        }
+    }
+
+    pub fn next(&self, item_type: ItemType) -> Self {
+        let order = Self::order(item_type);
+        let pos = order.iter().position(|f| f == self).unwrap_or(0);
+        order[(pos + 1) % order.len()]
     }
 
-    pub fn prev(&self) -> Self {
-        let pos = Self::ORDER.iter().position(|f| f == self).unwrap();
-        let len = Self::ORDER.len();
-        Self::ORDER[(pos + len - 1) % len]
+    pub fn prev(&self, item_type: ItemType) -> Self {
+        let order = Self::order(item_type);
+        let pos = order.iter().position(|f| f == self).unwrap_or(0);
+        let len = order.len();
+        order[(pos + len - 1) % len]
     }
 }
@@ -1,8 +1,9 @@
 pub mod form;
 pub mod form_field;
+pub mod review_queue;
 pub mod text_input;
 
-use crate::model::book_info::form::BookForm;
+use crate::model::book_info::{form::BookForm, review_queue::ReviewQueue};
 use ratatui_image::{
     picker::Picker,
     thread::{ResizeResponse, ThreadProtocol},
@@ -17,6 +18,7 @@ pub struct BookInfoState {
     pub form: BookForm,
     pub image_picker: Picker,
     pub cover: CoverStatus,
+    pub review_queue: ReviewQueue,
 }
 
 impl BookInfoState {
@@ -26,6 +28,7 @@ impl BookInfoState {
             form: BookForm::default(),
             image_picker,
             cover: CoverStatus::None,
+            review_queue: ReviewQueue::default(),
         }
     }
 
@@ -46,5 +49,5 @@ pub enum BookInfoMode {
 pub enum CoverStatus {
     None,
     Loading,
-    Ready(ThreadProtocol),
+    Ready(Box<ThreadProtocol>),
 }
@@ -0,0 +1,53 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+/// ISBNs read from barcode photos, waiting to be confirmed one by one in the add form.
+#[derive(Debug, Default)]
+pub struct ReviewQueue {
+    pending: VecDeque<(PathBuf, String)>,
+    current: Option<PathBuf>,
+    position: usize,
+    total: usize,
+    unreadable: usize,
+}
+
+impl ReviewQueue {
+    pub fn extend(&mut self, scans: Vec<(PathBuf, String)>, unreadable: usize) {
+        self.total += scans.len();
+        self.unreadable += unreadable;
+        self.pending.extend(scans);
+    }
+
+    /// Moves on to the next photo and returns its ISBN, or resets the queue once it is empty.
+    pub fn next(&mut self) -> Option<String> {
+        match self.pending.pop_front() {
+            Some((source, isbn)) => {
+                self.current = Some(source);
+                self.position += 1;
+                Some(isbn)
+            }
+            None => {
+                self.clear();
+                None
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn describe(&self) -> Option<String> {
+        let source = self.current.as_ref()?;
+        let name = source.file_name().unwrap_or(source.as_os_str());
+        let mut text = format!(
+            "Review {}/{}: {}",
+            self.position,
+            self.total,
+            name.to_string_lossy()
+        );
+        if self.unreadable > 0 {
+            text.push_str(&format!(" ({} without ISBN)", self.unreadable));
+        }
+        Some(text)
+    }
+}
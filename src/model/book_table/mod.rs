@@ -1,9 +1,11 @@
+use crate::model::book::item_type::ItemType;
 use ratatui::widgets::{ScrollbarState, TableState};
 
 #[derive(Default)]
 pub struct BookTableState {
     pub table_state: TableState,
     pub scrollbar_state: ScrollbarState,
+    pub type_filter: Option<ItemType>,
 }
 
 impl BookTableState {
@@ -14,6 +16,7 @@ impl BookTableState {
         Self {
             table_state,
             scrollbar_state: ScrollbarState::new(size).position(selected),
+            type_filter: None,
         }
     }
 
@@ -30,4 +33,16 @@ impl BookTableState {
             .scrollbar_state
             .position(self.table_state.selected().unwrap_or(0));
     }
+
+    pub fn cycle_type_filter(&mut self) {
+        self.type_filter = match self.type_filter {
+            None => Some(ItemType::Book),
+            Some(ItemType::Paper) => None,
+            Some(item_type) => Some(ItemType::from(item_type.index() + 1)),
+        };
+    }
+
+    pub fn matches_filter(&self, item_type: ItemType) -> bool {
+        self.type_filter.is_none_or(|filter| filter == item_type)
+    }
 }
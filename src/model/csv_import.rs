@@ -0,0 +1,458 @@
+use crate::model::{
+    book::{reading_status::ReadingStatus, Book},
+    book_info::{form::BookForm, text_input::TextInput},
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+pub const DELIMITERS: &[char] = &[',', ';', '\t', '|'];
+pub const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d/%m/%Y", "%m/%d/%Y", "%d.%m.%Y"];
+pub const LIST_SEPARATORS: &[&str] = &[",", ";", "|", "&"];
+
+/// The `Book` field a CSV column is read into.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ImportField {
+    Ignore,
+    Title,
+    Authors,
+    Year,
+    Pages,
+    Isbn,
+    Asin,
+    OpenLibraryId,
+    GoodreadsId,
+    ReadingStatus,
+    FinishedAt,
+    Rating,
+    Publisher,
+    Series,
+    Language,
+    Tags,
+}
+
+impl ImportField {
+    pub const ALL: [ImportField; 16] = [
+        ImportField::Ignore,
+        ImportField::Title,
+        ImportField::Authors,
+        ImportField::Year,
+        ImportField::Pages,
+        ImportField::Isbn,
+        ImportField::Asin,
+        ImportField::OpenLibraryId,
+        ImportField::GoodreadsId,
+        ImportField::ReadingStatus,
+        ImportField::FinishedAt,
+        ImportField::Rating,
+        ImportField::Publisher,
+        ImportField::Series,
+        ImportField::Language,
+        ImportField::Tags,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportField::Ignore => "(ignore)",
+            ImportField::Title => "Title",
+            ImportField::Authors => "Authors",
+            ImportField::Year => "Year",
+            ImportField::Pages => "Pages",
+            ImportField::Isbn => "ISBN",
+            ImportField::Asin => "ASIN",
+            ImportField::OpenLibraryId => "OpenLibrary ID",
+            ImportField::GoodreadsId => "Goodreads ID",
+            ImportField::ReadingStatus => "Status",
+            ImportField::FinishedAt => "Finished on",
+            ImportField::Rating => "Rating",
+            ImportField::Publisher => "Publisher",
+            ImportField::Series => "Series",
+            ImportField::Language => "Language",
+            ImportField::Tags => "Tags",
+        }
+    }
+
+    /// Guesses the field from a column header such as "Primary Author" or "Date Read".
+    pub fn guess(header: &str) -> Self {
+        let header = header.trim().to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| header.contains(w));
+        if has(&["isbn", "ean"]) {
+            ImportField::Isbn
+        } else if has(&["asin"]) {
+            ImportField::Asin
+        } else if has(&["goodreads"]) {
+            ImportField::GoodreadsId
+        } else if has(&["openlibrary", "open library"]) {
+            ImportField::OpenLibraryId
+        } else if has(&["author", "creator"]) {
+            ImportField::Authors
+        } else if has(&["title"]) {
+            ImportField::Title
+        } else if has(&["finished", "date read", "completed"]) {
+            ImportField::FinishedAt
+        } else if has(&["year", "publication", "published"]) {
+            ImportField::Year
+        } else if has(&["pages", "length"]) {
+            ImportField::Pages
+        } else if has(&["status", "shelf"]) {
+            ImportField::ReadingStatus
+        } else if has(&["rating"]) {
+            ImportField::Rating
+        } else if has(&["publisher"]) {
+            ImportField::Publisher
+        } else if has(&["series"]) {
+            ImportField::Series
+        } else if has(&["language"]) {
+            ImportField::Language
+        } else if has(&["tags", "collections", "genre"]) {
+            ImportField::Tags
+        } else {
+            ImportField::Ignore
+        }
+    }
+}
+
+/// A saved column mapping with its formats, applied to files with the same headers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CsvPreset {
+    pub name: String,
+    pub delimiter: char,
+    pub date_format: String,
+    pub list_separator: String,
+    /// Field of every mapped column, by header.
+    pub columns: BTreeMap<String, ImportField>,
+}
+
+/// What importing a row would do.
+pub enum RowOutcome {
+    New(Box<Book>),
+    /// Title of the library book the row matches.
+    Duplicate(String),
+    Invalid(String),
+}
+
+pub struct CsvImportState {
+    pub path: PathBuf,
+    pub delimiter: char,
+    pub date_format: String,
+    pub list_separator: String,
+    pub headers: Vec<String>,
+    pub mapping: Vec<ImportField>,
+    /// Line number and cells of every record, or why it could not be read.
+    pub rows: Vec<(usize, Result<Vec<String>, String>)>,
+    pub outcomes: Vec<RowOutcome>,
+    pub selected_column: usize,
+    /// First problem shown in the validation list.
+    pub problem_scroll: usize,
+    pub preset: Option<String>,
+}
+
+impl CsvImportState {
+    /// Reads the file with the delimiter its extension or header line suggests.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let data = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let is_tsv = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("tsv"));
+        let header_line = data.lines().next().unwrap_or_default();
+        let delimiter = if is_tsv {
+            '\t'
+        } else {
+            DELIMITERS
+                .iter()
+                .copied()
+                .max_by_key(|d| header_line.matches(*d).count())
+                .unwrap_or(',')
+        };
+        let mut state = Self {
+            path,
+            delimiter,
+            date_format: DATE_FORMATS[0].to_string(),
+            list_separator: LIST_SEPARATORS[0].to_string(),
+            headers: vec![],
+            mapping: vec![],
+            rows: vec![],
+            outcomes: vec![],
+            selected_column: 0,
+            problem_scroll: 0,
+            preset: None,
+        };
+        state.read()?;
+        state.mapping = state
+            .headers
+            .iter()
+            .map(|h| ImportField::guess(h))
+            .collect();
+        Ok(state)
+    }
+
+    /// Reads the file again, e.g. after the delimiter changed.
+    pub fn read(&mut self) -> Result<(), String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter as u8)
+            .flexible(true)
+            .from_path(&self.path)
+            .map_err(|e| e.to_string())?;
+        self.headers = reader
+            .headers()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect();
+        self.rows = reader
+            .records()
+            .map(|record| match record {
+                Ok(record) => (
+                    record.position().map_or(0, |p| p.line() as usize),
+                    Ok(record.iter().map(|c| c.trim().to_string()).collect()),
+                ),
+                Err(e) => (
+                    e.position().map_or(0, |p| p.line() as usize),
+                    Err(e.to_string()),
+                ),
+            })
+            .collect();
+        self.mapping.resize(self.headers.len(), ImportField::Ignore);
+        self.selected_column = self
+            .selected_column
+            .min(self.headers.len().saturating_sub(1));
+        Ok(())
+    }
+
+    pub fn next_column(&mut self) {
+        if self.selected_column + 1 < self.headers.len() {
+            self.selected_column += 1;
+        }
+    }
+
+    pub fn previous_column(&mut self) {
+        self.selected_column = self.selected_column.saturating_sub(1);
+    }
+
+    pub fn cycle_field(&mut self, forward: bool) {
+        let Some(field) = self.mapping.get_mut(self.selected_column) else {
+            return;
+        };
+        *field = cycle(&ImportField::ALL, field, forward);
+        self.preset = None;
+    }
+
+    pub fn cycle_delimiter(&mut self) -> Result<(), String> {
+        self.delimiter = cycle(DELIMITERS, &self.delimiter, true);
+        self.preset = None;
+        self.read()?;
+        self.mapping = self.headers.iter().map(|h| ImportField::guess(h)).collect();
+        Ok(())
+    }
+
+    pub fn cycle_date_format(&mut self) {
+        self.date_format = cycle(DATE_FORMATS, &self.date_format.as_str(), true).to_string();
+        self.preset = None;
+    }
+
+    pub fn cycle_list_separator(&mut self) {
+        self.list_separator =
+            cycle(LIST_SEPARATORS, &self.list_separator.as_str(), true).to_string();
+        self.preset = None;
+    }
+
+    /// Whether every column the preset maps exists in this file.
+    pub fn fits(&self, preset: &CsvPreset) -> bool {
+        preset.columns.keys().all(|h| self.headers.contains(h))
+    }
+
+    pub fn apply_preset(&mut self, preset: &CsvPreset) -> Result<(), String> {
+        self.delimiter = preset.delimiter;
+        self.date_format = preset.date_format.clone();
+        self.list_separator = preset.list_separator.clone();
+        self.read()?;
+        self.mapping = self
+            .headers
+            .iter()
+            .map(|h| {
+                preset
+                    .columns
+                    .get(h)
+                    .copied()
+                    .unwrap_or(ImportField::Ignore)
+            })
+            .collect();
+        self.preset = Some(preset.name.clone());
+        Ok(())
+    }
+
+    pub fn to_preset(&self, name: String) -> CsvPreset {
+        CsvPreset {
+            name,
+            delimiter: self.delimiter,
+            date_format: self.date_format.clone(),
+            list_separator: self.list_separator.clone(),
+            columns: self
+                .headers
+                .iter()
+                .zip(&self.mapping)
+                .filter(|(_, f)| **f != ImportField::Ignore)
+                .map(|(h, f)| (h.clone(), *f))
+                .collect(),
+        }
+    }
+
+    /// Builds every row into a book with the same checks as the book form.
+    /// A row repeating a book from an earlier row of the file is flagged, so
+    /// it isn't imported twice.
+    pub fn validate(&mut self, books: &[Book]) {
+        let mut outcomes: Vec<RowOutcome> = vec![];
+        for (_, cells) in &self.rows {
+            let book = match cells {
+                Ok(cells) => self.build_book(cells),
+                Err(e) => Err(e.clone()),
+            };
+            let outcome = match book {
+                Ok(book) => {
+                    let earlier = outcomes.iter().zip(&self.rows).find_map(|(o, (line, _))| {
+                        matches!(o, RowOutcome::New(b) if b.is_same_book(&book)).then_some(line)
+                    });
+                    match (books.iter().find(|b| b.is_same_book(&book)), earlier) {
+                        (Some(existing), _) => RowOutcome::Duplicate(existing.title.clone()),
+                        (None, Some(line)) => RowOutcome::Invalid(format!(
+                            "'{}' is already on line {line}",
+                            book.title
+                        )),
+                        (None, None) => RowOutcome::New(Box::new(book)),
+                    }
+                }
+                Err(e) => RowOutcome::Invalid(e),
+            };
+            outcomes.push(outcome);
+        }
+        self.outcomes = outcomes;
+        self.problem_scroll = self
+            .problem_scroll
+            .min(self.problems().len().saturating_sub(1));
+    }
+
+    /// Line numbers and messages of the rows that will not be imported.
+    pub fn problems(&self) -> Vec<(usize, String)> {
+        self.rows
+            .iter()
+            .zip(&self.outcomes)
+            .filter_map(|((line, _), outcome)| match outcome {
+                RowOutcome::New(_) => None,
+                RowOutcome::Duplicate(title) => {
+                    Some((*line, format!("'{title}' is already in the library")))
+                }
+                RowOutcome::Invalid(e) => Some((*line, e.clone())),
+            })
+            .collect()
+    }
+
+    pub fn count(&self, matches: impl Fn(&RowOutcome) -> bool) -> usize {
+        self.outcomes.iter().filter(|o| matches(o)).count()
+    }
+
+    pub fn take_new_books(&mut self) -> Vec<Book> {
+        std::mem::take(&mut self.outcomes)
+            .into_iter()
+            .filter_map(|o| match o {
+                RowOutcome::New(book) => Some(*book),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn build_book(&self, cells: &[String]) -> Result<Book, String> {
+        let value = |field: ImportField| {
+            self.mapping
+                .iter()
+                .position(|f| *f == field)
+                .and_then(|i| cells.get(i))
+                .map_or("", |c| c.as_str())
+        };
+
+        let mut form = BookForm::default();
+        form.title = TextInput::new(value(ImportField::Title).to_string());
+        form.authors = TextInput::new(self.split_list(value(ImportField::Authors)).join(", "));
+        form.year = TextInput::new(number_or_zero(value(ImportField::Year)));
+        form.pages = TextInput::new(number_or_zero(value(ImportField::Pages)));
+        form.isbn = TextInput::new(value(ImportField::Isbn).to_string());
+        form.asin = TextInput::new(value(ImportField::Asin).to_string());
+        form.openlibrary_id = TextInput::new(value(ImportField::OpenLibraryId).to_string());
+        form.goodreads_id = TextInput::new(value(ImportField::GoodreadsId).to_string());
+
+        let mut finished_at = vec![];
+        for date in self.split_list(value(ImportField::FinishedAt)) {
+            finished_at.push(
+                NaiveDate::parse_from_str(&date, &self.date_format)
+                    .map_err(|_| format!("'{date}' does not match the date format"))?,
+            );
+        }
+        finished_at.sort();
+        // The form takes one date; the rest are added after its checks.
+        if let Some(last) = finished_at.last() {
+            form.finished_at = TextInput::new(last.to_string());
+        }
+        form.reading_status = match value(ImportField::ReadingStatus) {
+            "" if finished_at.is_empty() => ReadingStatus::ToRead,
+            "" => ReadingStatus::Read,
+            status => parse_status(status)?,
+        };
+        form.rating = match value(ImportField::Rating) {
+            "" => 0,
+            rating => rating
+                .parse::<f32>()
+                .ok()
+                .filter(|r| (0.0..=5.0).contains(r))
+                .map(|r| r.round() as u8)
+                .ok_or(format!("Rating '{rating}' must be a number from 0 to 5"))?,
+        };
+
+        let mut book = Book::from(&form, None)?;
+        book.finished_at = finished_at;
+        book.publisher = optional(value(ImportField::Publisher));
+        book.series = optional(value(ImportField::Series));
+        book.language = optional(value(ImportField::Language));
+        book.tags = self.split_list(value(ImportField::Tags));
+        Ok(book)
+    }
+
+    fn split_list(&self, text: &str) -> Vec<String> {
+        text.split(self.list_separator.as_str())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+}
+
+fn parse_status(text: &str) -> Result<ReadingStatus, String> {
+    match text.to_lowercase().replace(['_', ' '], "-").as_str() {
+        "read" | "finished" | "completed" | "done" => Ok(ReadingStatus::Read),
+        "reading" | "currently-reading" | "in-progress" | "started" => Ok(ReadingStatus::Reading),
+        "to-read" | "unread" | "not-started" | "want-to-read" | "planned" => {
+            Ok(ReadingStatus::ToRead)
+        }
+        _ => Err(format!("Unknown reading status '{text}'")),
+    }
+}
+
+/// Unknown years and page counts are stored as 0.
+fn number_or_zero(text: &str) -> String {
+    if text.is_empty() {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn optional(text: &str) -> Option<String> {
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn cycle<T: Copy + PartialEq>(values: &[T], current: &T, forward: bool) -> T {
+    let position = values.iter().position(|v| v == current).unwrap_or(0);
+    let len = values.len();
+    if forward {
+        values[(position + 1) % len]
+    } else {
+        values[(position + len - 1) % len]
+    }
+}
@@ -4,4 +4,8 @@ pub enum Focus {
     Table,
     Info,
     Status,
+    Prompt,
+    Reader,
+    CsvImport,
+    ListImport,
 }
@@ -0,0 +1,300 @@
+use crate::model::{
+    book::{author_name::AuthorName, reading_status::ReadingStatus, Book},
+    book_info::{form::BookForm, text_input::TextInput},
+};
+use std::path::PathBuf;
+
+/// Bullets and checkboxes that notes apps put in front of list items.
+const LIST_MARKERS: &[&str] = &[
+    "- [ ] ", "- [x] ", "* [ ] ", "[ ] ", "[x] ", "- ", "* ", "• ", "+ ",
+];
+/// Separators between title and authors, tried in this order.
+const AUTHOR_SEPARATORS: &[&str] = &[" by ", " By ", " - ", " – ", " — ", " / "];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Decision {
+    Accept,
+    Reject,
+}
+
+/// A line of the list, parsed into a book or the reason it cannot be added.
+pub struct Candidate {
+    pub line: usize,
+    pub text: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: Option<u16>,
+    pub book: Result<Book, String>,
+    pub decision: Decision,
+}
+
+impl Candidate {
+    pub fn parse(line: usize, text: &str, books: &[Book]) -> Self {
+        let (title, authors, year) = parse_line(text);
+        let mut form = BookForm::default();
+        form.title = TextInput::new(title.clone());
+        form.authors = TextInput::new(authors.join(", "));
+        form.year = TextInput::new(year.unwrap_or_default().to_string());
+        form.pages = TextInput::new("0".to_string());
+        form.reading_status = ReadingStatus::ToRead;
+        let book = Book::from(&form, None).and_then(|book| {
+            match books.iter().find(|b| b.is_same_book(&book)) {
+                Some(existing) => Err(format!("'{}' is already in the library", existing.title)),
+                None => Ok(book),
+            }
+        });
+        Self {
+            line,
+            text: text.to_string(),
+            decision: if book.is_ok() {
+                Decision::Accept
+            } else {
+                Decision::Reject
+            },
+            title,
+            authors,
+            year,
+            book,
+        }
+    }
+
+    /// The candidate written back in the form the parser reads best.
+    pub fn canonical(&self) -> String {
+        let mut text = self.title.clone();
+        if !self.authors.is_empty() {
+            text.push_str(&format!(" by {}", self.authors.join(" & ")));
+        }
+        if let Some(year) = self.year {
+            text.push_str(&format!(" ({year})"));
+        }
+        text
+    }
+}
+
+pub struct ListImportState {
+    pub path: PathBuf,
+    pub candidates: Vec<Candidate>,
+    pub selected: usize,
+}
+
+impl ListImportState {
+    /// Every non-empty line that is not a heading becomes a candidate.
+    pub fn open(path: PathBuf, books: &[Book]) -> Result<Self, String> {
+        let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let candidates = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            .map(|(i, l)| Candidate::parse(i + 1, l.trim(), books))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Err("the file has no lines to import".to_string());
+        }
+        Ok(Self {
+            path,
+            candidates,
+            selected: 0,
+        })
+    }
+
+    pub fn selected_candidate(&self) -> Option<&Candidate> {
+        self.candidates.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.candidates.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Only candidates that passed validation can be accepted.
+    pub fn decide(&mut self, decision: Decision) -> Result<(), String> {
+        let Some(candidate) = self.candidates.get_mut(self.selected) else {
+            return Ok(());
+        };
+        if decision == Decision::Accept {
+            if let Err(e) = &candidate.book {
+                return Err(format!("Line {} cannot be added: {e}", candidate.line));
+            }
+        }
+        candidate.decision = decision;
+        Ok(())
+    }
+
+    pub fn toggle(&mut self) -> Result<(), String> {
+        match self.selected_candidate().map(|c| c.decision) {
+            Some(Decision::Accept) => self.decide(Decision::Reject),
+            Some(Decision::Reject) => self.decide(Decision::Accept),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the selected candidate with an edited line.
+    pub fn edit(&mut self, text: &str, books: &[Book]) {
+        if let Some(candidate) = self.candidates.get_mut(self.selected) {
+            *candidate = Candidate::parse(candidate.line, text.trim(), books);
+        }
+    }
+
+    pub fn accepted(&self) -> usize {
+        self.candidates
+            .iter()
+            .filter(|c| c.decision == Decision::Accept)
+            .count()
+    }
+
+    /// The accepted books, each once when the list names a book twice.
+    pub fn take_accepted(&mut self) -> Vec<Book> {
+        let mut books: Vec<Book> = vec![];
+        for candidate in std::mem::take(&mut self.candidates) {
+            if candidate.decision != Decision::Accept {
+                continue;
+            }
+            if let Ok(book) = candidate.book {
+                if !books.iter().any(|b| b.is_same_book(&book)) {
+                    books.push(book);
+                }
+            }
+        }
+        books
+    }
+}
+
+/// Reads lines like "Dune - Frank Herbert (1965)", "Dune by Frank Herbert, 1965"
+/// or "1. Good Omens / Terry Pratchett & Neil Gaiman" into title, authors and year.
+pub fn parse_line(line: &str) -> (String, Vec<String>, Option<u16>) {
+    let mut text = line.trim();
+    if let Some(rest) = strip_numbering(text) {
+        text = rest;
+    }
+    for marker in LIST_MARKERS {
+        if let Some(rest) = text.strip_prefix(marker) {
+            text = rest.trim_start();
+            break;
+        }
+    }
+
+    let (text, year) = split_year(text);
+    let (title, authors) = AUTHOR_SEPARATORS
+        .iter()
+        .find_map(|separator| {
+            // The last separator, so titles like "Catch-22" or "The Fellowship - Part 1" survive.
+            let index = text.rfind(separator)?;
+            Some((&text[..index], &text[index + separator.len()..]))
+        })
+        .unwrap_or((text, ""));
+
+    let authors = authors
+        .split(['&', ';'])
+        .flat_map(|a| a.split(" and "))
+        .flat_map(|a| {
+            // "Herbert, Frank" is one author, stored the way bookie writes names.
+            if AuthorName::is_inverted(a) {
+                vec![AuthorName::parse(a).natural()]
+            } else {
+                a.split(',').map(str::to_string).collect()
+            }
+        })
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    let title = title.trim().trim_matches(['"', '“', '”', '*', '_']).trim();
+    (title.to_string(), authors, year)
+}
+
+/// "1. " or "12) " in front of a numbered list item.
+fn strip_numbering(text: &str) -> Option<&str> {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    text[digits..]
+        .strip_prefix(". ")
+        .or_else(|| text[digits..].strip_prefix(") "))
+}
+
+/// A publication year in brackets or after a comma at the end of the line.
+fn split_year(text: &str) -> (&str, Option<u16>) {
+    let trimmed = text.trim_end();
+    for (open, close) in [("(", ")"), ("[", "]"), (", ", "")] {
+        let Some(rest) = trimmed.strip_suffix(close) else {
+            continue;
+        };
+        let Some(index) = rest.rfind(open) else {
+            continue;
+        };
+        let candidate = &rest[index + open.len()..];
+        if candidate.len() == 4 {
+            if let Ok(year) = candidate.parse::<u16>() {
+                if (1000..=2100).contains(&year) {
+                    return (rest[..index].trim_end(), Some(year));
+                }
+            }
+        }
+    }
+    (trimmed, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Dune - Frank Herbert (1965)", "Dune", &["Frank Herbert"], Some(1965))]
+    #[case("Dune by Frank Herbert, 1965", "Dune", &["Frank Herbert"], Some(1965))]
+    #[case(
+        "1. Good Omens / Terry Pratchett & Neil Gaiman",
+        "Good Omens",
+        &["Terry Pratchett", "Neil Gaiman"],
+        None
+    )]
+    #[case("- [ ] Catch-22 by Joseph Heller", "Catch-22", &["Joseph Heller"], None)]
+    #[case(
+        "* The Fellowship - Part 1 - J. R. R. Tolkien [1954]",
+        "The Fellowship - Part 1",
+        &["J. R. R. Tolkien"],
+        Some(1954)
+    )]
+    #[case("Dune by Herbert, Frank", "Dune", &["Frank Herbert"], None)]
+    #[case(
+        "The Left Hand of Darkness - Le Guin, Ursula K. (1969)",
+        "The Left Hand of Darkness",
+        &["Ursula K. Le Guin"],
+        Some(1969)
+    )]
+    #[case(
+        "Good Omens by Pratchett, Terry & Gaiman, Neil",
+        "Good Omens",
+        &["Terry Pratchett", "Neil Gaiman"],
+        None
+    )]
+    #[case(
+        "Good Omens by Terry Pratchett, Neil Gaiman",
+        "Good Omens",
+        &["Terry Pratchett", "Neil Gaiman"],
+        None
+    )]
+    #[case("\"Piranesi\"", "Piranesi", &[], None)]
+    #[case("1984 by George Orwell", "1984", &["George Orwell"], None)]
+    #[case("Stories (1234567)", "Stories (1234567)", &[], None)]
+    fn parses_lines(
+        #[case] line: &str,
+        #[case] title: &str,
+        #[case] authors: &[&str],
+        #[case] year: Option<u16>,
+    ) {
+        assert_eq!(
+            parse_line(line),
+            (
+                title.to_string(),
+                authors.iter().map(|a| a.to_string()).collect(),
+                year
+            )
+        );
+    }
+}
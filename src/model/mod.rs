@@ -1,36 +1,56 @@
 pub mod book;
 pub mod book_info;
 pub mod book_table;
+pub mod csv_import;
 pub mod focus;
-mod persistance;
+pub mod list_import;
+pub mod persistance;
+pub mod reader;
 pub mod running_state;
+pub mod stats;
 pub mod status;
 
 use crate::{
+    barcode::{self, ScannedBarcode},
+    clipboard,
+    config::Config,
     event::{app_event::AppEvent, Message},
+    export::{card, site},
     image_util,
+    library::epub::Epub,
+    metadata::{dump_index::DumpIndex, open_library, Metadata},
     model::{
-        book::{reading_status::ReadingStatus, Book},
+        book::{citation::CitationStyle, reading_status::ReadingStatus, Book},
         book_info::{
-            form::BookForm, form_field::FormField, BookInfoMode, BookInfoState, CoverStatus,
+            form::BookForm, form_field::FormField, text_input::TextInput, BookInfoMode,
+            BookInfoState, CoverStatus,
         },
         book_table::BookTableState,
+        csv_import::{CsvImportState, CsvPreset, RowOutcome},
         focus::Focus,
+        list_import::{Decision, ListImportState},
         persistance::SavedState,
+        reader::ReaderState,
         running_state::RunningState,
-        status::StatusMode,
+        stats::Stats,
+        status::{Prompt, StatusMode},
     },
 };
-use chrono::Datelike;
-use log::info;
-use ratatui_image::thread::{ResizeRequest, ThreadProtocol};
+use chrono::Local;
+use log::{error, info};
+use ratatui_image::{
+    picker::Picker,
+    thread::{ResizeRequest, ThreadProtocol},
+};
 use std::{
-    collections::HashSet,
-    io,
+    fs, io,
+    path::{Path, PathBuf},
     sync::{mpsc, mpsc::Sender},
 };
 use uuid::Uuid;
 
+const MAX_SUGGESTIONS: usize = 5;
+
 pub struct Model {
     pub books: Vec<Book>,
     pub book_table: BookTableState,
@@ -38,6 +58,12 @@ pub struct Model {
     pub status: status::State,
     pub focus: Focus,
     pub running_state: RunningState,
+    pub config: Config,
+    pub offline_index: Option<DumpIndex>,
+    pub reader: Option<ReaderState>,
+    pub csv_import: Option<CsvImportState>,
+    pub csv_presets: Vec<CsvPreset>,
+    pub list_import: Option<ListImportState>,
     pub app_tx: Sender<AppEvent>,
 }
 
@@ -46,10 +72,18 @@ impl Model {
         Self {
             books: vec![],
             book_table: BookTableState::new(0, None),
-            book_info: BookInfoState::new(image_util::create_picker()),
+            // Asking the terminal for its graphics support is left to `load`,
+            // so models made for the API server don't touch it.
+            book_info: BookInfoState::new(Picker::from_fontsize((8, 12))),
             status: status::State::new(),
             focus: Focus::Table,
             running_state: RunningState::Running,
+            config: Config::default(),
+            offline_index: None,
+            reader: None,
+            csv_import: None,
+            csv_presets: vec![],
+            list_import: None,
             app_tx,
         }
     }
@@ -59,6 +93,7 @@ impl Model {
         Self {
             books: saved_state.books,
             book_table: BookTableState::new(book_count, saved_state.selected),
+            csv_presets: saved_state.csv_presets,
             ..Self::new(app_tx)
         }
     }
@@ -78,10 +113,164 @@ impl Model {
                 self.select_previous_book();
                 self.persist();
             }
+            Message::CycleTypeFilter => {
+                self.book_table.cycle_type_filter();
+                let first = (!self.visible_book_indices().is_empty()).then_some(0);
+                self.select_book_by_index(first);
+                self.update_scrollbar_length();
+                self.persist();
+            }
             Message::ConfirmDeleteBook => self.enter_confirm_mode(),
-            Message::CancelConfirm => self.enter_view_mode(),
+            Message::TogglePrivate => self.toggle_private(),
+            Message::ChooseCitationStyle => {
+                if self.get_selected_book().is_some() {
+                    self.focus = Focus::Status;
+                    self.status.mode = StatusMode::ChooseCitationStyle;
+                }
+            }
+            Message::CopyCitation(style) => self.copy_citation(style),
+            Message::CancelConfirm => self.leave_status(),
+            Message::OpenReader => self.open_reader(),
+            Message::CloseReader => {
+                self.reader = None;
+                self.enter_view_mode();
+            }
+            Message::ReaderDown
+            | Message::ReaderUp
+            | Message::ReaderPageDown
+            | Message::ReaderPageUp
+            | Message::ReaderNextChapter
+            | Message::ReaderPreviousChapter
+            | Message::ToggleToc
+            | Message::JumpToTocEntry => {
+                if let Some(reader) = &mut self.reader {
+                    match (msg, reader.toc.is_some()) {
+                        (Message::ReaderDown, true) => reader.next_toc_entry(),
+                        (Message::ReaderUp, true) => reader.previous_toc_entry(),
+                        (Message::ReaderDown, false) => {
+                            reader.scroll_down();
+                        }
+                        (Message::ReaderUp, false) => {
+                            reader.scroll_up();
+                        }
+                        (Message::ReaderPageDown, _) => reader.page_down(),
+                        (Message::ReaderPageUp, _) => reader.page_up(),
+                        (Message::ReaderNextChapter, _) => reader.next_chapter(),
+                        (Message::ReaderPreviousChapter, _) => reader.previous_chapter(),
+                        (Message::ToggleToc, _) => reader.toggle_toc(),
+                        (Message::JumpToTocEntry, _) => reader.jump_to_toc_entry(),
+                        _ => {}
+                    }
+                    self.save_reading_position();
+                }
+            }
+            Message::FinishBook => {
+                self.finish_reader_book();
+                self.persist();
+            }
+            Message::CloseCsvImport => {
+                self.csv_import = None;
+                self.enter_view_mode();
+            }
+            Message::CsvNextColumn
+            | Message::CsvPreviousColumn
+            | Message::CsvNextField
+            | Message::CsvPreviousField
+            | Message::CsvCycleDelimiter
+            | Message::CsvCycleDateFormat
+            | Message::CsvCycleListSeparator
+            | Message::CsvProblemsDown
+            | Message::CsvProblemsUp => {
+                if let Some(import) = &mut self.csv_import {
+                    match msg {
+                        Message::CsvNextColumn => import.next_column(),
+                        Message::CsvPreviousColumn => import.previous_column(),
+                        Message::CsvNextField => import.cycle_field(true),
+                        Message::CsvPreviousField => import.cycle_field(false),
+                        Message::CsvCycleDelimiter => {
+                            if let Err(error) = import.cycle_delimiter() {
+                                self.status.mode = StatusMode::Error(error);
+                            }
+                        }
+                        Message::CsvCycleDateFormat => import.cycle_date_format(),
+                        Message::CsvCycleListSeparator => import.cycle_list_separator(),
+                        Message::CsvProblemsDown => import.problem_scroll += 1,
+                        _ => import.problem_scroll = import.problem_scroll.saturating_sub(1),
+                    }
+                    import.validate(&self.books);
+                }
+            }
+            Message::CsvNextPreset => self.apply_next_csv_preset(),
+            Message::SubmitCsvImport => {
+                self.submit_csv_import();
+                self.persist();
+            }
+            Message::CloseListImport => {
+                self.list_import = None;
+                self.enter_view_mode();
+            }
+            Message::NextCandidate
+            | Message::PreviousCandidate
+            | Message::AcceptCandidate
+            | Message::RejectCandidate
+            | Message::ToggleCandidate => {
+                if let Some(import) = &mut self.list_import {
+                    let result = match msg {
+                        Message::NextCandidate => {
+                            import.next();
+                            Ok(())
+                        }
+                        Message::PreviousCandidate => {
+                            import.previous();
+                            Ok(())
+                        }
+                        Message::AcceptCandidate => import.decide(Decision::Accept),
+                        Message::RejectCandidate => import.decide(Decision::Reject),
+                        _ => import.toggle(),
+                    };
+                    if let Err(error) = result {
+                        self.status.mode = StatusMode::Error(error);
+                    }
+                }
+            }
+            Message::SubmitListImport => {
+                self.submit_list_import();
+                self.persist();
+            }
+            Message::OpenPrompt(prompt) => self.enter_prompt_mode(prompt),
+            Message::CancelPrompt => self.leave_status(),
+            Message::PromptInsertChar(c) => self.status.input.insert_char(c),
+            Message::PromptDeleteChar => self.status.input.delete_char(),
+            Message::PromptLeft => self.status.input.move_cursor_left(),
+            Message::PromptRight => self.status.input.move_cursor_right(),
+            Message::SubmitPrompt => {
+                let StatusMode::Prompt(prompt) = self.status.mode else {
+                    return None;
+                };
+                let text = self.status.input.text.clone();
+                self.leave_status();
+                match prompt {
+                    Prompt::Search => {
+                        self.search(&text);
+                        self.persist();
+                    }
+                    Prompt::BarcodeImage => self.scan_barcodes(text.trim()),
+                    Prompt::LabelImage => self.scan_label(text.trim()),
+                    Prompt::CardPath => self.save_card(text.trim()),
+                    Prompt::ImportFile => self.open_import(text.trim()),
+                    Prompt::EditCandidate => {
+                        if let Some(import) = &mut self.list_import {
+                            import.edit(&text, &self.books);
+                        }
+                    }
+                    Prompt::PresetName => {
+                        self.save_csv_preset(text.trim());
+                        self.persist();
+                    }
+                }
+            }
             Message::DeleteBook => {
-                if let Some(book_index) = self.book_table.selected() {
+                if let Some(book_index) = self.selected_book_index() {
                     self.delete_book(book_index);
                     self.enter_view_mode();
                     self.persist();
@@ -89,24 +278,47 @@ impl Model {
             }
             Message::AddBook => self.enter_add_mode(),
             Message::EditBook => self.enter_edit_mode(),
-            Message::CancelForm => self.enter_view_mode(),
-            Message::InsertChar(c) => self.book_info.form.insert_char(c),
-            Message::DeleteChar => self.book_info.form.delete_char(),
+            Message::CancelForm => {
+                self.enter_view_mode();
+                self.review_next_scan();
+            }
+            Message::StopReview => {
+                self.book_info.review_queue.clear();
+                self.enter_view_mode();
+            }
+            Message::InsertChar(c) => {
+                self.book_info.form.insert_char(c);
+                self.refresh_suggestions();
+            }
+            Message::DeleteChar => {
+                self.book_info.form.delete_char();
+                self.refresh_suggestions();
+            }
+            Message::NextSuggestion => self.book_info.form.next_suggestion(),
+            Message::PreviousSuggestion => self.book_info.form.previous_suggestion(),
             Message::FormLeft => match self.book_info.form.active {
+                FormField::ItemType => self.book_info.form.decrease_item_type(),
                 FormField::ReadingStatus => self.book_info.form.decrease_reading_status(),
                 FormField::Rating => self.book_info.form.decrease_rating(),
                 _ => self.book_info.form.move_cursor_left(),
             },
             Message::FormRight => match self.book_info.form.active {
+                FormField::ItemType => self.book_info.form.increase_item_type(),
                 FormField::ReadingStatus => self.book_info.form.increase_reading_status(),
                 FormField::Rating => self.book_info.form.increase_rating(),
+                FormField::Title if self.book_info.form.can_accept_suggestion() => {
+                    self.book_info.form.accept_suggestion()
+                }
                 _ => self.book_info.form.move_cursor_right(),
             },
             Message::NextFormField => self.book_info.form.next_field(),
             Message::PreviousFormField => self.book_info.form.previous_field(),
+            Message::LookupMetadata => self.lookup_metadata(),
             Message::SubmitForm => match Book::from(&self.book_info.form, self.get_selected_book())
+                .and_then(|book| self.check_duplicate_isbn(book))
             {
                 Ok(mut book) => {
+                    self.keep_downloaded_cover(&book);
                     match self.book_info.mode {
                         BookInfoMode::Add => self.add_book(book),
                         BookInfoMode::Edit => self.update_book(&mut book),
@@ -114,6 +326,7 @@ impl Model {
                     }
                     self.enter_view_mode();
                     self.persist();
+                    self.review_next_scan();
                 }
                 Err(error) => {
                     self.book_info.form.error = Some(error.to_string());
@@ -124,78 +337,160 @@ impl Model {
         None
     }
 
-    pub fn load(app_tx: Sender<AppEvent>) -> Self {
-        let mut model = Self::from(persistance::load().expect("Failed to load state."), app_tx);
+    pub fn load(app_tx: Sender<AppEvent>) -> color_eyre::Result<Self> {
+        let mut model = Self::from(persistance::load()?, app_tx);
+        model.config = Config::load();
+        model.offline_index = DumpIndex::open(&model.config.lookup.offline_index);
         model.book_info.image_picker = image_util::create_picker();
         model.books.iter_mut().for_each(|book| {
-            book.cover_path = Some(format!("./covers/{}.jpg", book.title).into());
+            book.cover_path = Some(Book::default_cover_path(&book.title));
         });
-        if let Some(book_index) = model.book_table.table_state.selected() {
-            model.load_book_cover_async(book_index);
-        }
-        model
+        model.load_selected_book_cover();
+        Ok(model)
     }
 
     pub fn persist(&self) {
-        persistance::save_state(&self).expect("Failed to save state.");
+        persistance::save_state(self).expect("Failed to save state.");
     }
 
-    pub fn get_selected_book(&self) -> Option<&Book> {
-        self.books.get(self.book_table.selected()?)
+    pub fn handle_metadata_response(&mut self, res: Result<Metadata, String>) {
+        // The form was closed while the lookup was running.
+        if self.book_info.mode == BookInfoMode::View {
+            return;
+        }
+        match res {
+            Ok(metadata) => {
+                self.book_info.form.apply_metadata(&metadata);
+                if let Some(path) = &metadata.cover_path {
+                    self.book_info.form.set_downloaded_cover(path.clone());
+                }
+                let title = metadata.title.unwrap_or_default();
+                self.status.mode = StatusMode::Info(match metadata.cover_path {
+                    Some(_) => format!("Filled in '{title}' and downloaded its cover"),
+                    None => format!("Filled in '{title}'"),
+                });
+            }
+            Err(error) => self.status.mode = StatusMode::Error(error),
+        }
     }
 
-    pub fn unique_authors(&self) -> usize {
-        self.books
-            .iter()
-            .flat_map(|b| b.authors.clone())
-            .collect::<HashSet<String>>()
-            .len()
+    pub fn handle_barcode_scans(&mut self, res: Result<Vec<ScannedBarcode>, String>) {
+        let scans = match res {
+            Ok(scans) => scans,
+            Err(error) => {
+                self.status.mode = StatusMode::Error(error);
+                return;
+            }
+        };
+        let photos = scans.len();
+        let mut found = vec![];
+        let mut known = 0;
+        for scan in scans {
+            match scan.isbn {
+                Ok(isbn) if self.books.iter().any(|b| b.identifiers.has_isbn(&isbn)) => known += 1,
+                Ok(isbn) => found.push((scan.source, isbn)),
+                Err(_) => {}
+            }
+        }
+        let unreadable = photos - found.len() - known;
+        if found.is_empty() {
+            self.status.mode = StatusMode::Error(format!(
+                "No new ISBN in {photos} photo(s): {known} already in the library, {unreadable} without a readable barcode"
+            ));
+            return;
+        }
+        self.book_info.review_queue.extend(found, unreadable);
+        if self.book_info.mode == BookInfoMode::View {
+            self.review_next_scan();
+        }
     }
 
-    pub fn books_read(&self) -> usize {
-        self.books
+    pub fn handle_label_scan(&mut self, res: Result<Uuid, String>) {
+        let id = match res {
+            Ok(id) => id,
+            Err(error) => {
+                self.status.mode = StatusMode::Error(error);
+                return;
+            }
+        };
+        let Some(title) = self
+            .books
             .iter()
-            .filter(|b| !b.finished_at.is_empty())
-            .count()
+            .find(|b| b.id == id)
+            .map(|b| b.title.clone())
+        else {
+            self.status.mode = StatusMode::Error(format!("No book with id {id} in the library"));
+            return;
+        };
+        self.select_book_by_id(id);
+        self.status.mode = StatusMode::Info(format!("Selected '{title}'"));
     }
 
-    pub fn books_reading(&self) -> usize {
-        self.books
-            .iter()
-            .filter(|b| b.reading_status == ReadingStatus::Reading)
-            .count()
+    pub fn get_reader_book(&self) -> Option<&Book> {
+        let id = self.reader.as_ref()?.book_id;
+        self.books.iter().find(|b| b.id == id)
     }
 
-    pub fn books_to_read(&self) -> usize {
-        self.books
-            .iter()
-            .filter(|b| b.reading_status == ReadingStatus::ToRead)
-            .count()
+    pub fn get_selected_book(&self) -> Option<&Book> {
+        self.books.get(self.selected_book_index()?)
     }
 
-    pub fn books_read_in_year(&self, year: u16) -> usize {
+    /// Indices into `books` of the rows currently shown in the table.
+    pub fn visible_book_indices(&self) -> Vec<usize> {
         self.books
             .iter()
-            .map(|b| {
-                b.finished_at
-                    .iter()
-                    .filter(|d| d.year() == (year as i32))
-                    .count()
-            })
-            .sum()
+            .enumerate()
+            .filter(|(_, b)| self.book_table.matches_filter(b.item_type))
+            .map(|(i, _)| i)
+            .collect()
     }
 
-    pub fn pages_read_in_year(&self, year: u16) -> usize {
-        self.books
-            .iter()
-            .map(|b| {
-                (b.pages as usize)
-                    * b.finished_at
-                        .iter()
-                        .filter(|d| d.year() == (year as i32))
-                        .count()
-            })
-            .sum()
+    pub fn selected_book_index(&self) -> Option<usize> {
+        let row = self.book_table.selected()?;
+        self.visible_book_indices().get(row).copied()
+    }
+
+    pub fn stats(&self) -> Stats<'_> {
+        Stats::new(&self.books)
+    }
+
+    fn copy_citation(&mut self, style: CitationStyle) {
+        self.leave_status();
+        let Some(book) = self.get_selected_book() else {
+            return;
+        };
+        let citation = book.citation(style);
+        self.status.mode = match clipboard::copy(&citation) {
+            Ok(()) => StatusMode::Info(format!("Copied {} citation: {citation}", style.label())),
+            Err(e) => StatusMode::Error(format!("Cannot copy to the clipboard: {e}")),
+        };
+    }
+
+    fn save_card(&mut self, path: &str) {
+        let Some(book) = self.get_selected_book() else {
+            return;
+        };
+        if path.is_empty() {
+            return;
+        }
+        self.status.mode = match card::write(book, Path::new(path)) {
+            Ok(()) => StatusMode::Info(format!("Saved the card for '{}' to {path}", book.title)),
+            Err(e) => StatusMode::Error(format!("Cannot write the card: {e}")),
+        };
+    }
+
+    fn toggle_private(&mut self) {
+        let Some(book_index) = self.selected_book_index() else {
+            return;
+        };
+        let book = &mut self.books[book_index];
+        book.private = !book.private;
+        self.status.mode = StatusMode::Info(if book.private {
+            format!("'{}' is private and left out of the site", book.title)
+        } else {
+            format!("'{}' is published on the site", book.title)
+        });
+        self.persist();
     }
 
     fn enter_add_mode(&mut self) {
@@ -214,11 +509,299 @@ impl Model {
     }
 
     fn enter_view_mode(&mut self) {
+        self.book_info.form.discard_downloaded_cover();
         self.focus = Focus::Table;
         self.status.mode = StatusMode::Ok;
         self.book_info.mode = BookInfoMode::View;
-        if let Some(book_index) = self.book_table.table_state.selected() {
-            self.load_book_cover_async(book_index);
+        self.load_selected_book_cover();
+    }
+
+    fn refresh_suggestions(&mut self) {
+        let form = &self.book_info.form;
+        if self.book_info.mode != BookInfoMode::Add || form.active != FormField::Title {
+            return;
+        }
+        let suggestions = match &self.offline_index {
+            Some(index) => index.suggest(&form.title.text, MAX_SUGGESTIONS),
+            None => vec![],
+        };
+        self.book_info.form.set_suggestions(suggestions);
+    }
+
+    fn lookup_metadata(&mut self) {
+        match self.book_info.form.lookup_query() {
+            Some(query) => {
+                self.status.mode = StatusMode::Info(format!("Looking up {}...", query.describe()));
+                open_library::lookup_async(self.config.lookup.clone(), query, self.app_tx.clone());
+            }
+            None => {
+                self.status.mode =
+                    StatusMode::Error("Fill in an ISBN or a title to look up".to_string())
+            }
+        }
+    }
+
+    fn scan_barcodes(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+        self.status.mode = StatusMode::Info(format!("Scanning {path} for barcodes..."));
+        barcode::scan_async(PathBuf::from(path), self.app_tx.clone());
+    }
+
+    fn scan_label(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+        self.status.mode = StatusMode::Info(format!("Reading the QR code in {path}..."));
+        barcode::scan_label_async(PathBuf::from(path), self.app_tx.clone());
+    }
+
+    /// Opens the add form for the next scanned ISBN, filled in by a lookup when one is configured.
+    fn review_next_scan(&mut self) {
+        let Some(isbn) = self.book_info.review_queue.next() else {
+            return;
+        };
+        self.enter_add_mode();
+        self.book_info.form.isbn = TextInput::new(isbn);
+        if !self.config.lookup.base_url.is_empty() {
+            self.lookup_metadata();
+        }
+    }
+
+    fn enter_prompt_mode(&mut self, prompt: Prompt) {
+        self.focus = Focus::Prompt;
+        self.status.mode = StatusMode::Prompt(prompt);
+        self.status.input = match (prompt, &self.list_import) {
+            (Prompt::EditCandidate, Some(import)) => import
+                .selected_candidate()
+                .map(|c| TextInput::new(c.canonical()))
+                .unwrap_or_default(),
+            (Prompt::CardPath, _) => self
+                .get_selected_book()
+                .map(|b| TextInput::new(format!("{}.png", site::slug(b))))
+                .unwrap_or_default(),
+            _ => TextInput::default(),
+        };
+    }
+
+    fn open_reader(&mut self) {
+        let Some(book) = self.get_selected_book() else {
+            return;
+        };
+        let Some(path) = book.file.as_ref().map(|f| f.path.clone()).filter(|p| {
+            p.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("epub"))
+        }) else {
+            self.status.mode = StatusMode::Error(format!(
+                "'{}' has no linked EPUB, link one with `bookie scan-library`",
+                book.title
+            ));
+            return;
+        };
+        let chapters = match Epub::open(&path).and_then(|mut epub| epub.chapters()) {
+            Ok(chapters) if !chapters.is_empty() => chapters,
+            Ok(_) => {
+                self.status.mode = StatusMode::Error(format!("{} has no text", path.display()));
+                return;
+            }
+            Err(error) => {
+                self.status.mode =
+                    StatusMode::Error(format!("Cannot open {}: {error}", path.display()));
+                return;
+            }
+        };
+        self.reader = Some(ReaderState::new(book.id, chapters, book.position.as_ref()));
+        self.focus = Focus::Reader;
+        self.status.mode = StatusMode::Ok;
+        self.save_reading_position();
+    }
+
+    /// Stores the reader's position on its book, marks the book as being read
+    /// and asks for a finish date once the end is reached.
+    fn save_reading_position(&mut self) {
+        let Some(reader) = &mut self.reader else {
+            return;
+        };
+        let position = reader.position();
+        let reached_end = reader.at_end() && !reader.finish_prompted;
+        if reached_end {
+            reader.finish_prompted = true;
+        }
+        let id = reader.book_id;
+        if let Some(book) = self.books.iter_mut().find(|b| b.id == id) {
+            if book.reading_status == ReadingStatus::ToRead {
+                book.reading_status = ReadingStatus::Reading;
+                book.start_reading(Local::now().date_naive());
+            }
+            book.position = Some(position);
+        }
+        if reached_end {
+            self.focus = Focus::Status;
+            self.status.mode = StatusMode::ConfirmFinishBook;
+        }
+        self.persist();
+    }
+
+    fn finish_reader_book(&mut self) {
+        let today = Local::now().date_naive();
+        let id = self.reader.as_ref().map(|r| r.book_id);
+        let Some(book) = self.books.iter_mut().find(|b| Some(b.id) == id) else {
+            return self.leave_status();
+        };
+        if book.finished_at.last().is_none_or(|last| *last < today) {
+            book.finished_at.push(today);
+        }
+        book.reading_status = ReadingStatus::Read;
+        let title = book.title.clone();
+        self.leave_status();
+        self.status.mode = StatusMode::Info(format!("Finished '{title}' on {today}"));
+    }
+
+    /// Plain-text lists go to the review table, anything else to the CSV wizard.
+    fn open_import(&mut self, path: &str) {
+        let is_list = Path::new(path)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("txt") || e.eq_ignore_ascii_case("md"));
+        if !is_list {
+            return self.open_csv_import(path);
+        }
+        match ListImportState::open(PathBuf::from(path), &self.books) {
+            Ok(import) => {
+                self.list_import = Some(import);
+                self.focus = Focus::ListImport;
+            }
+            Err(error) => {
+                self.status.mode = StatusMode::Error(format!("Cannot read {path}: {error}"))
+            }
+        }
+    }
+
+    /// Adds the accepted candidates as books to read and closes the review.
+    fn submit_list_import(&mut self) {
+        let Some(import) = &mut self.list_import else {
+            return;
+        };
+        if import.accepted() == 0 {
+            self.status.mode = StatusMode::Error("No accepted lines to add".to_string());
+            return;
+        }
+        let books = import.take_accepted();
+        let added = books.len();
+        self.list_import = None;
+        self.enter_view_mode();
+        for book in books {
+            info!("Book added from list: {:?}", book);
+            self.books.push(book);
+        }
+        self.sort_books_by_title();
+        self.update_scrollbar_length();
+        self.status.mode = StatusMode::Info(format!("Added {added} books to read"));
+    }
+
+    /// Opens the import wizard with the first saved preset that fits the file,
+    /// or with columns mapped by their headers.
+    fn open_csv_import(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+        let mut import = match CsvImportState::open(PathBuf::from(path)) {
+            Ok(import) => import,
+            Err(error) => {
+                self.status.mode = StatusMode::Error(format!("Cannot read {path}: {error}"));
+                return;
+            }
+        };
+        if let Some(preset) = self.csv_presets.iter().find(|p| import.fits(p)) {
+            if let Err(error) = import.apply_preset(preset) {
+                self.status.mode = StatusMode::Error(error);
+            }
+        }
+        import.validate(&self.books);
+        self.csv_import = Some(import);
+        self.focus = Focus::CsvImport;
+    }
+
+    /// Cycles through the saved presets that fit the open file.
+    fn apply_next_csv_preset(&mut self) {
+        let Some(import) = &mut self.csv_import else {
+            return;
+        };
+        let fitting = self
+            .csv_presets
+            .iter()
+            .filter(|p| import.fits(p))
+            .collect::<Vec<_>>();
+        if fitting.is_empty() {
+            self.status.mode = StatusMode::Error("No saved preset fits this file".to_string());
+            return;
+        }
+        let current = fitting
+            .iter()
+            .position(|p| Some(&p.name) == import.preset.as_ref());
+        let next = fitting[current.map_or(0, |i| (i + 1) % fitting.len())];
+        if let Err(error) = import.apply_preset(next) {
+            self.status.mode = StatusMode::Error(error);
+        }
+        import.validate(&self.books);
+    }
+
+    /// Saves the current mapping, replacing a preset with the same name.
+    fn save_csv_preset(&mut self, name: &str) {
+        let Some(import) = &mut self.csv_import else {
+            return;
+        };
+        if name.is_empty() {
+            return;
+        }
+        let preset = import.to_preset(name.to_string());
+        import.preset = Some(preset.name.clone());
+        self.csv_presets.retain(|p| p.name != name);
+        self.csv_presets.push(preset);
+        self.status.mode = StatusMode::Info(format!("Saved preset '{name}'"));
+    }
+
+    /// Adds the rows that passed validation and closes the wizard.
+    fn submit_csv_import(&mut self) {
+        let Some(import) = &mut self.csv_import else {
+            return;
+        };
+        let duplicates = import.count(|o| matches!(o, RowOutcome::Duplicate(_)));
+        let invalid = import.count(|o| matches!(o, RowOutcome::Invalid(_)));
+        let books = import.take_new_books();
+        let added = books.len();
+        if added == 0 {
+            import.validate(&self.books);
+            self.status.mode = StatusMode::Error("No valid new rows to import".to_string());
+            return;
+        }
+        self.csv_import = None;
+        self.enter_view_mode();
+        for book in books {
+            info!("Book imported: {:?}", book);
+            self.books.push(book);
+        }
+        self.sort_books_by_title();
+        self.update_scrollbar_length();
+        self.status.mode = StatusMode::Info(format!(
+            "Imported {added} books, skipped {duplicates} already in the library and {invalid} invalid rows"
+        ));
+    }
+
+    /// Hands the keys back to the reader or an import screen if one is open,
+    /// otherwise to the table.
+    fn leave_status(&mut self) {
+        if self.reader.is_some() {
+            self.focus = Focus::Reader;
+            self.status.mode = StatusMode::Ok;
+        } else if self.csv_import.is_some() {
+            self.focus = Focus::CsvImport;
+            self.status.mode = StatusMode::Ok;
+        } else if self.list_import.is_some() {
+            self.focus = Focus::ListImport;
+            self.status.mode = StatusMode::Ok;
+        } else {
+            self.enter_view_mode();
         }
     }
 
@@ -228,25 +811,21 @@ impl Model {
     }
 
     fn select_next_book(&mut self) {
-        if let Some(current_book_index) = self.book_table.table_state.selected() {
-            if current_book_index < self.books.len() - 1 {
+        if let Some(current_row) = self.book_table.table_state.selected() {
+            if current_row + 1 < self.visible_book_indices().len() {
                 self.book_table.table_state.select_next();
                 self.book_table.sync_scrollbar_position();
-                if let Some(new_book_index) = self.book_table.table_state.selected() {
-                    self.load_book_cover_async(new_book_index);
-                }
+                self.load_selected_book_cover();
             }
         }
     }
 
     fn select_previous_book(&mut self) {
-        if let Some(book_index) = self.book_table.table_state.selected() {
-            if book_index > 0 {
+        if let Some(current_row) = self.book_table.table_state.selected() {
+            if current_row > 0 {
                 self.book_table.table_state.select_previous();
                 self.book_table.sync_scrollbar_position();
-                if let Some(new_book_index) = self.book_table.table_state.selected() {
-                    self.load_book_cover_async(new_book_index);
-                }
+                self.load_selected_book_cover();
             }
         }
     }
@@ -254,8 +833,13 @@ impl Model {
     fn select_book_by_index(&mut self, index: Option<usize>) {
         self.book_table.table_state.select(index);
         self.book_table.sync_scrollbar_position();
-        if let Some(new_book_index) = self.book_table.table_state.selected() {
-            self.load_book_cover_async(new_book_index);
+        self.load_selected_book_cover();
+    }
+
+    fn load_selected_book_cover(&mut self) {
+        match self.selected_book_index() {
+            Some(book_index) => self.load_book_cover_async(book_index),
+            None => self.book_info.cover = CoverStatus::None,
         }
     }
 
@@ -277,10 +861,9 @@ impl Model {
             self.book_info.cover = CoverStatus::None;
             return;
         };
-        let img = match image::ImageReader::open(path).and_then(|r| {
-            r.decode()
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
-        }) {
+        let img = match image::ImageReader::open(path)
+            .and_then(|r| r.decode().map_err(io::Error::other))
+        {
             Ok(img) => img,
             Err(_) => {
                 self.book_info.cover = CoverStatus::None;
@@ -290,12 +873,36 @@ impl Model {
         let protocol = self.book_info.image_picker.new_resize_protocol(img);
 
         self.book_info.cover =
-            CoverStatus::Ready(ThreadProtocol::new(tx_resize_req, Some(protocol)));
+            CoverStatus::Ready(Box::new(ThreadProtocol::new(tx_resize_req, Some(protocol))));
+    }
+
+    /// Moves the cover of a lookup to the book's cover path, unless a cover
+    /// is already there, which may belong to another book with the title.
+    fn keep_downloaded_cover(&mut self, book: &Book) {
+        let (Some(downloaded), Some(path)) = (
+            self.book_info.form.downloaded_cover.take(),
+            &book.cover_path,
+        ) else {
+            return;
+        };
+        if path.exists() {
+            info!("Kept the existing cover {}", path.display());
+        } else {
+            // The temporary folder may be on another file system, so no rename.
+            let result = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::copy(&downloaded, path));
+            if let Err(e) = result {
+                error!("Cannot save the cover of '{}': {e}", book.title);
+            }
+        }
+        fs::remove_file(downloaded).ok();
     }
 
     fn add_book(&mut self, book: Book) {
         info!("Book added: {:?}", book);
-        let id = book.id.clone();
+        let id = book.id;
         self.books.push(book);
         self.sort_books_by_title();
         self.select_book_by_id(id);
@@ -303,10 +910,10 @@ impl Model {
     }
 
     fn update_book(&mut self, updated_book: &mut Book) {
-        if let Some(book_index) = self.book_table.selected() {
+        if let Some(book_index) = self.selected_book_index() {
             updated_book.id = self.books[book_index].id;
             info!("Book updated: {:?}", updated_book);
-            let book_id = updated_book.id.clone();
+            let book_id = updated_book.id;
             self.books[book_index] = updated_book.to_owned();
             self.sort_books_by_title();
             self.select_book_by_id(book_id);
@@ -318,36 +925,70 @@ impl Model {
 
     fn delete_book(&mut self, book_index: usize) {
         self.books.remove(book_index);
-        if self.books.is_empty() {
+        let visible_count = self.visible_book_indices().len();
+        if visible_count == 0 {
             self.select_book_by_index(None)
-        } else if book_index == self.books.len() {
+        } else if self.book_table.selected() == Some(visible_count) {
             self.select_previous_book();
         }
         self.update_scrollbar_length();
     }
 
     fn update_scrollbar_length(&mut self) {
-        self.book_table.update_scrollbar_length(self.books.len());
+        self.book_table
+            .update_scrollbar_length(self.visible_book_indices().len());
     }
 
     fn get_table_position_by_id(&self, book_id: Uuid) -> Option<usize> {
-        self.books.iter().position(|b| b.id == book_id)
+        self.visible_book_indices()
+            .iter()
+            .position(|&i| self.books[i].id == book_id)
     }
 
     fn select_book_by_id(&mut self, id: Uuid) {
-        match self.get_table_position_by_id(id) {
-            Some(position) => self.select_book_by_index(Some(position)),
-            None => {}
+        if self.get_table_position_by_id(id).is_none() {
+            // The book is hidden by the type filter, so show everything again.
+            self.book_table.type_filter = None;
+        }
+        if let Some(position) = self.get_table_position_by_id(id) {
+            self.select_book_by_index(Some(position));
         }
     }
 
+    /// An ISBN jumps straight to its book; any other query selects the next
+    /// visible book whose title or authors contain it.
+    fn search(&mut self, query: &str) {
+        if query.trim().is_empty() {
+            return;
+        }
+        if let Some(book) = self.books.iter().find(|b| b.identifiers.has_isbn(query)) {
+            self.select_book_by_id(book.id);
+            return;
+        }
+        let visible = self.visible_book_indices();
+        let start = self.book_table.selected().map_or(0, |row| row + 1);
+        let found = (0..visible.len())
+            .map(|offset| visible[(start + offset) % visible.len()])
+            .find(|&i| self.books[i].matches_query(query));
+        match found {
+            Some(book_index) => self.select_book_by_id(self.books[book_index].id),
+            None => self.status.mode = StatusMode::Error(format!("No book matches '{query}'")),
+        }
+    }
+
+    fn check_duplicate_isbn(&self, book: Book) -> Result<Book, String> {
+        book.check_duplicate_isbn(&self.books)?;
+        Ok(book)
+    }
+
     fn sort_books_by_title(&mut self) {
-        self.books
-            .sort_by(|a, b| a.title_normalized().cmp(&b.title_normalized()));
+        self.books.sort_by_key(|b| b.title_normalized());
     }
 
     fn clear_error(&mut self) {
         self.book_info.form.error = None;
-        self.status.mode = StatusMode::Ok;
+        if let StatusMode::Error(_) = self.status.mode {
+            self.status.mode = StatusMode::Ok;
+        }
     }
 }
@@ -1,6 +1,6 @@
-use crate::model::{book::Book, Model};
+use crate::model::{book::Book, csv_import::CsvPreset, Model};
+use color_eyre::eyre::WrapErr;
 use serde::{Deserialize, Serialize};
-use serde_json;
 use std::{
     fs::{self, File},
     io::Write,
@@ -12,13 +12,16 @@ const SAVED_STATE_PATH: &str = "bookie-state.json";
 pub struct SavedState {
     pub books: Vec<Book>,
     pub selected: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub csv_presets: Vec<CsvPreset>,
 }
 
 impl SavedState {
     pub fn from(model: &Model) -> Self {
         Self {
-            books: model.books.iter().map(|b| b.clone()).collect(),
-            selected: model.book_table.selected(),
+            books: model.books.to_vec(),
+            selected: model.selected_book_index(),
+            csv_presets: model.csv_presets.clone(),
         }
     }
 }
@@ -26,7 +29,8 @@ impl SavedState {
 pub fn load() -> color_eyre::Result<SavedState> {
     match fs::read_to_string(SAVED_STATE_PATH) {
         Ok(data) => {
-            let saved_state = serde_json::from_str(&data).expect("Failed to parse state from JSON");
+            let saved_state = serde_json::from_str(&data)
+                .wrap_err_with(|| format!("Cannot parse {SAVED_STATE_PATH}"))?;
             Ok(saved_state)
         }
         Err(_) => {
@@ -37,14 +41,14 @@ pub fn load() -> color_eyre::Result<SavedState> {
 }
 
 pub fn save_state(model: &Model) -> color_eyre::Result<()> {
-    serialize_saved_state(SavedState::from(&model))
+    save(SavedState::from(model))
 }
 
 fn initial_save_state() -> color_eyre::Result<()> {
-    serialize_saved_state(SavedState::default())
+    save(SavedState::default())
 }
 
-fn serialize_saved_state(state: SavedState) -> color_eyre::Result<()> {
+pub fn save(state: SavedState) -> color_eyre::Result<()> {
     let json = serde_json::to_string_pretty(&state).expect("Failed to save state into JSON");
     let mut file = File::create(SAVED_STATE_PATH)?;
     file.write_all(json.as_bytes())?;
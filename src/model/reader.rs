@@ -0,0 +1,213 @@
+use crate::{library::epub::Chapter, model::book::reading_position::ReadingPosition};
+use unicode_width::UnicodeWidthStr;
+use uuid::Uuid;
+
+/// Used until the first render tells the reader how wide its text area is.
+const DEFAULT_WIDTH: u16 = 80;
+
+pub struct ReaderState {
+    pub book_id: Uuid,
+    pub chapters: Vec<Chapter>,
+    pub chapter: usize,
+    pub paragraph: usize,
+    /// Wrapped line of `paragraph` shown at the top of the page.
+    pub line: usize,
+    /// Size of the text area, updated on every render.
+    pub width: u16,
+    pub height: u16,
+    /// Selected table of contents entry while the contents are shown.
+    pub toc: Option<usize>,
+    pub finish_prompted: bool,
+}
+
+impl ReaderState {
+    pub fn new(book_id: Uuid, chapters: Vec<Chapter>, position: Option<&ReadingPosition>) -> Self {
+        let chapter = position.map_or(0, |p| p.chapter.min(chapters.len() - 1));
+        let paragraph = position.map_or(0, |p| {
+            p.paragraph
+                .min(chapters[chapter].paragraphs.len().saturating_sub(1))
+        });
+        Self {
+            book_id,
+            chapters,
+            chapter,
+            paragraph,
+            line: 0,
+            width: DEFAULT_WIDTH,
+            height: 0,
+            toc: None,
+            finish_prompted: false,
+        }
+    }
+
+    pub fn current_chapter(&self) -> &Chapter {
+        &self.chapters[self.chapter]
+    }
+
+    /// Wrapped lines of a paragraph of the current chapter, plus the blank line after it.
+    pub fn paragraph_lines(&self, paragraph: usize) -> Vec<String> {
+        let mut lines = wrap(&self.current_chapter().paragraphs[paragraph], self.width);
+        lines.push(String::new());
+        lines
+    }
+
+    pub fn scroll_down(&mut self) -> bool {
+        if !self.has_more_below() {
+            return false;
+        }
+        self.line += 1;
+        if self.line >= self.paragraph_lines(self.paragraph).len() {
+            self.paragraph += 1;
+            self.line = 0;
+        }
+        true
+    }
+
+    pub fn scroll_up(&mut self) -> bool {
+        if self.line > 0 {
+            self.line -= 1;
+        } else if self.paragraph > 0 {
+            self.paragraph -= 1;
+            self.line = self.paragraph_lines(self.paragraph).len() - 1;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Turns the page, continuing with the next chapter at the end of this one.
+    pub fn page_down(&mut self) {
+        let mut moved = false;
+        for _ in 0..self.page_step() {
+            moved |= self.scroll_down();
+        }
+        if !moved {
+            self.next_chapter();
+        }
+    }
+
+    pub fn page_up(&mut self) {
+        let mut moved = false;
+        for _ in 0..self.page_step() {
+            moved |= self.scroll_up();
+        }
+        if !moved && self.chapter > 0 {
+            self.previous_chapter();
+        }
+    }
+
+    pub fn next_chapter(&mut self) {
+        if self.chapter + 1 < self.chapters.len() {
+            self.go_to_chapter(self.chapter + 1);
+        }
+    }
+
+    pub fn previous_chapter(&mut self) {
+        self.go_to_chapter(self.chapter.saturating_sub(1));
+    }
+
+    pub fn toggle_toc(&mut self) {
+        self.toc = match self.toc {
+            Some(_) => None,
+            None => Some(self.chapter),
+        };
+    }
+
+    pub fn next_toc_entry(&mut self) {
+        if let Some(selected) = self.toc {
+            self.toc = Some((selected + 1).min(self.chapters.len() - 1));
+        }
+    }
+
+    pub fn previous_toc_entry(&mut self) {
+        if let Some(selected) = self.toc {
+            self.toc = Some(selected.saturating_sub(1));
+        }
+    }
+
+    pub fn jump_to_toc_entry(&mut self) {
+        if let Some(selected) = self.toc.take() {
+            self.go_to_chapter(selected);
+        }
+    }
+
+    /// The last page of the last chapter is on screen.
+    pub fn at_end(&self) -> bool {
+        self.chapter + 1 == self.chapters.len() && !self.has_more_below()
+    }
+
+    pub fn position(&self) -> ReadingPosition {
+        let total: usize = self.chapters.iter().map(|c| c.paragraphs.len()).sum();
+        let before: usize = self.chapters[..self.chapter]
+            .iter()
+            .map(|c| c.paragraphs.len())
+            .sum::<usize>()
+            + self.paragraph;
+        let progress = if self.at_end() || total == 0 {
+            100
+        } else {
+            (before * 100 / total) as u8
+        };
+        ReadingPosition {
+            chapter: self.chapter,
+            paragraph: self.paragraph,
+            progress,
+        }
+    }
+
+    fn go_to_chapter(&mut self, chapter: usize) {
+        self.chapter = chapter;
+        self.paragraph = 0;
+        self.line = 0;
+    }
+
+    fn page_step(&self) -> u16 {
+        self.height.saturating_sub(1).max(1)
+    }
+
+    /// Whether the rest of the chapter is taller than the page.
+    fn has_more_below(&self) -> bool {
+        let mut lines = 0;
+        for paragraph in self.paragraph..self.current_chapter().paragraphs.len() {
+            lines += self.paragraph_lines(paragraph).len();
+            if paragraph == self.paragraph {
+                lines -= self.line;
+            }
+            // The blank line after the last paragraph does not need to be shown.
+            if lines > self.height as usize + 1 {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Greedy word wrap; words wider than the line are split.
+pub fn wrap(text: &str, width: u16) -> Vec<String> {
+    let width = width.max(1) as usize;
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let needed = if line.is_empty() {
+            word.width()
+        } else {
+            line.width() + 1 + word.width()
+        };
+        if needed > width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        for c in word.chars() {
+            if line.width() + c.to_string().width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
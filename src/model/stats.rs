@@ -0,0 +1,122 @@
+use crate::model::book::{item_type::ItemType, reading_status::ReadingStatus, Book};
+use chrono::Datelike;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// The numbers of the stats panel, also given to templates and by the API.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub books_in_db: usize,
+    pub authors_in_db: usize,
+    pub read: usize,
+    pub reading: usize,
+    pub to_read: usize,
+    pub comics: usize,
+    pub magazines: usize,
+    pub papers: usize,
+    pub year: u16,
+    pub books_read_this_year: usize,
+    pub pages_read_this_year: usize,
+    /// Comics, magazines and papers.
+    pub other_items_read_this_year: usize,
+}
+
+/// Counts shown in the stats panel and on the generated site.
+pub struct Stats<'a> {
+    books: &'a [Book],
+}
+
+impl<'a> Stats<'a> {
+    pub fn new(books: &'a [Book]) -> Self {
+        Self { books }
+    }
+
+    pub fn unique_authors(&self) -> usize {
+        self.only_books()
+            .flat_map(|b| b.authors.clone())
+            .collect::<HashSet<String>>()
+            .len()
+    }
+
+    pub fn books_in_db(&self) -> usize {
+        self.only_books().count()
+    }
+
+    pub fn books_read(&self) -> usize {
+        self.only_books()
+            .filter(|b| !b.finished_at.is_empty())
+            .count()
+    }
+
+    pub fn books_reading(&self) -> usize {
+        self.only_books()
+            .filter(|b| b.reading_status == ReadingStatus::Reading)
+            .count()
+    }
+
+    pub fn books_to_read(&self) -> usize {
+        self.only_books()
+            .filter(|b| b.reading_status == ReadingStatus::ToRead)
+            .count()
+    }
+
+    pub fn items_of_type(&self, item_type: ItemType) -> usize {
+        self.books
+            .iter()
+            .filter(|b| b.item_type == item_type)
+            .count()
+    }
+
+    pub fn items_read_in_year(&self, item_type: ItemType, year: u16) -> usize {
+        self.books
+            .iter()
+            .filter(|b| b.item_type == item_type)
+            .map(|b| {
+                b.finished_at
+                    .iter()
+                    .filter(|d| d.year() == (year as i32))
+                    .count()
+            })
+            .sum()
+    }
+
+    pub fn books_read_in_year(&self, year: u16) -> usize {
+        self.items_read_in_year(ItemType::Book, year)
+    }
+
+    pub fn pages_read_in_year(&self, year: u16) -> usize {
+        self.only_books()
+            .map(|b| {
+                (b.pages as usize)
+                    * b.finished_at
+                        .iter()
+                        .filter(|d| d.year() == (year as i32))
+                        .count()
+            })
+            .sum()
+    }
+
+    pub fn summary(&self, year: u16) -> Summary {
+        Summary {
+            books_in_db: self.books_in_db(),
+            authors_in_db: self.unique_authors(),
+            read: self.books_read(),
+            reading: self.books_reading(),
+            to_read: self.books_to_read(),
+            comics: self.items_of_type(ItemType::Comic),
+            magazines: self.items_of_type(ItemType::Magazine),
+            papers: self.items_of_type(ItemType::Paper),
+            year,
+            books_read_this_year: self.books_read_in_year(year),
+            pages_read_this_year: self.pages_read_in_year(year),
+            other_items_read_this_year: [ItemType::Comic, ItemType::Magazine, ItemType::Paper]
+                .into_iter()
+                .map(|t| self.items_read_in_year(t, year))
+                .sum(),
+        }
+    }
+
+    fn only_books(&self) -> impl Iterator<Item = &'a Book> {
+        self.books.iter().filter(|b| b.item_type == ItemType::Book)
+    }
+}
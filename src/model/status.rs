@@ -1,11 +1,17 @@
+use crate::model::book_info::text_input::TextInput;
+
 #[derive(Default)]
 pub struct State {
     pub mode: StatusMode,
+    pub input: TextInput,
 }
 
 impl State {
     pub fn new() -> Self {
-        Self { mode: StatusMode::Ok }
+        Self {
+            mode: StatusMode::Ok,
+            input: TextInput::default(),
+        }
     }
 }
 
@@ -14,5 +20,35 @@ pub enum StatusMode {
     #[default]
     Ok,
     Error(String),
+    Info(String),
     ConfirmDeleteBook,
+    ConfirmFinishBook,
+    ChooseCitationStyle,
+    Prompt(Prompt),
+}
+
+/// What the text typed into the status bar is used for.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Prompt {
+    Search,
+    BarcodeImage,
+    LabelImage,
+    CardPath,
+    ImportFile,
+    PresetName,
+    EditCandidate,
+}
+
+impl Prompt {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Prompt::Search => "Search (title, author or ISBN): ",
+            Prompt::BarcodeImage => "Barcode photo or folder of photos: ",
+            Prompt::LabelImage => "Photo of a QR label: ",
+            Prompt::CardPath => "Save book card as: ",
+            Prompt::ImportFile => "CSV, TSV or plain-text list to import: ",
+            Prompt::PresetName => "Save column mapping as preset: ",
+            Prompt::EditCandidate => "Edit (title by author (year)): ",
+        }
+    }
 }
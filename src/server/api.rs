@@ -0,0 +1,447 @@
+use crate::{
+    model::{
+        book::{reading_status::ReadingStatus, Book},
+        persistance,
+        stats::Stats,
+    },
+    server::{header, Library},
+};
+use chrono::{Datelike, Local};
+use log::info;
+use serde_json::{json, Value};
+use std::io::Read;
+use tiny_http::{Method, Request, Response, ResponseBox};
+use uuid::Uuid;
+
+/// Books are small, so a larger body is a mistake or an attack on memory.
+const MAX_BODY: usize = 1024 * 1024;
+
+/// The fields a client may set. The id, linked file, cover, reading position,
+/// highlights and privacy are kept by bookie itself.
+const EDITABLE_FIELDS: &[&str] = &[
+    "item_type",
+    "title",
+    "authors",
+    "author_sort",
+    "year",
+    "pages",
+    "volume",
+    "issue",
+    "issue_date",
+    "doi",
+    "venue",
+    "identifiers",
+    "language",
+    "series",
+    "series_index",
+    "tags",
+    "publisher",
+    "location",
+    "reading_status",
+    "started_at",
+    "finished_at",
+    "rating",
+];
+
+/// Answers a request for a path under `/api`, checking the token first.
+pub fn handle(request: &mut Request, path: &str, query: &str, library: &Library) -> ResponseBox {
+    let response = respond(request, path, query, library);
+    cors(response, &library.allowed_origin)
+}
+
+fn respond(request: &mut Request, path: &str, query: &str, library: &Library) -> ResponseBox {
+    if request.method() == &Method::Options {
+        // The preflight of a browser, which never carries the token.
+        return Response::empty(204).boxed();
+    }
+    if !authorized(request, &library.token) {
+        return error(401, "Missing or wrong bearer token")
+            .with_header(header("WWW-Authenticate", "Bearer"));
+    }
+    let method = request.method().clone();
+    if method != Method::Get && method != Method::Head {
+        if library.token.is_empty() {
+            return error(403, "Changes need a token, set server.token or --token");
+        }
+        if library.read_only {
+            return error(403, "The server is read-only");
+        }
+    }
+    if request
+        .body_length()
+        .is_some_and(|length| length > MAX_BODY)
+    {
+        return too_large();
+    }
+    // The length header is optional, so the body is cut off as well.
+    let mut body = String::new();
+    if let Err(e) = request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_string(&mut body)
+    {
+        return error(400, &format!("Cannot read the body: {e}"));
+    }
+    if body.len() > MAX_BODY {
+        return too_large();
+    }
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+    // Changes are read, made and saved in one go, so two requests can't
+    // both save over each other's edits.
+    let _lock = library.lock.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = match persistance::load() {
+        Ok(state) => state,
+        Err(e) => return error(500, &format!("Cannot load the library: {e}")),
+    };
+    let (response, changed) = route(&method, &segments, query, &body, &mut state.books);
+    if changed {
+        if let Err(e) = persistance::save(state) {
+            return error(500, &format!("Cannot save the library: {e}"));
+        }
+    }
+    response
+}
+
+/// Answers a request on the books, returning whether they were changed and
+/// need saving.
+fn route(
+    method: &Method,
+    segments: &[&str],
+    query: &str,
+    body: &str,
+    books: &mut Vec<Book>,
+) -> (ResponseBox, bool) {
+    match (method, segments) {
+        (Method::Get | Method::Head, ["api", "books"]) => {
+            let terms = form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "q")
+                .map(|(_, value)| value.to_string());
+            let books = books
+                .iter()
+                .filter(|b| terms.as_ref().is_none_or(|q| b.matches_query(q)))
+                .collect::<Vec<_>>();
+            (ok(200, json!(books)), false)
+        }
+        (Method::Post, ["api", "books"]) => {
+            let book = Book {
+                id: Uuid::new_v4(),
+                ..Book::default()
+            };
+            match merged(&book, body, false) {
+                Ok(mut merged) => {
+                    merged.id = book.id;
+                    save(books, merged, 201)
+                }
+                Err(response) => (response, false),
+            }
+        }
+        (Method::Get | Method::Head, ["api", "books", id]) => match find(books, id) {
+            Some(book) => (ok(200, json!(book)), false),
+            None => (not_found(), false),
+        },
+        (Method::Put | Method::Patch, ["api", "books", id]) => {
+            let Some(existing) = find(books, id).cloned() else {
+                return (not_found(), false);
+            };
+            // PUT replaces the editable fields and PATCH only the ones it sends.
+            match merged(&existing, body, method == &Method::Put) {
+                Ok(mut book) => {
+                    book.id = existing.id;
+                    save(books, book, 200)
+                }
+                Err(response) => (response, false),
+            }
+        }
+        (Method::Delete, ["api", "books", id]) => {
+            let Some(id) = find(books, id).map(|b| b.id) else {
+                return (not_found(), false);
+            };
+            books.retain(|b| b.id != id);
+            (Response::empty(204).boxed(), true)
+        }
+        (Method::Get | Method::Head, ["api", "stats"]) => (
+            ok(
+                200,
+                json!(Stats::new(books).summary(Local::now().year() as u16)),
+            ),
+            false,
+        ),
+        (_, ["api", "books"] | ["api", "books", _] | ["api", "stats"]) => {
+            (error(405, "Method not allowed"), false)
+        }
+        _ => (not_found(), false),
+    }
+}
+
+/// Compares in constant time, so the token can't be guessed a character
+/// at a time from how long the answer takes.
+fn authorized(request: &Request, token: &str) -> bool {
+    if token.is_empty() {
+        return true;
+    }
+    let Some(given) = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn find<'a>(books: &'a [Book], id: &str) -> Option<&'a Book> {
+    let id = Uuid::parse_str(id).ok()?;
+    books.iter().find(|b| b.id == id)
+}
+
+/// The book with the editable fields of the JSON body put over it. When
+/// replacing, the editable fields the body leaves out are emptied.
+fn merged(book: &Book, body: &str, replace: bool) -> Result<Book, ResponseBox> {
+    let fields = match serde_json::from_str::<Value>(body) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => return Err(error(400, "The body must be a JSON object")),
+        Err(e) => return Err(error(400, &format!("Invalid JSON: {e}"))),
+    };
+    if let Some(key) = fields
+        .keys()
+        .find(|k| !EDITABLE_FIELDS.contains(&k.as_str()))
+    {
+        return Err(error(400, &format!("'{key}' is unknown or can't be set")));
+    }
+    let Value::Object(mut value) = json!(book) else {
+        unreachable!("a book serializes to an object");
+    };
+    if replace {
+        let Value::Object(empty) = json!(Book::default()) else {
+            unreachable!("a book serializes to an object");
+        };
+        for key in EDITABLE_FIELDS {
+            match empty.get(*key) {
+                Some(field) => value.insert(key.to_string(), field.clone()),
+                None => value.remove(*key),
+            };
+        }
+    }
+    value.extend(fields);
+    serde_json::from_value(Value::Object(value))
+        .map_err(|e| error(400, &format!("Invalid book: {e}")))
+}
+
+/// Adds the book, or replaces the one with the same id, after the checks the
+/// form makes.
+fn save(books: &mut Vec<Book>, mut book: Book, status: u16) -> (ResponseBox, bool) {
+    if let Err(e) = book
+        .validate()
+        .and_then(|_| book.check_duplicate_isbn(books))
+    {
+        return (error(422, &e), false);
+    }
+    let index = books.iter().position(|b| b.id == book.id);
+    let was_reading = index.is_some_and(|i| books[i].reading_status == ReadingStatus::Reading);
+    if book.reading_status == ReadingStatus::Reading && !was_reading {
+        book.start_reading(Local::now().date_naive());
+    }
+    let id = book.id;
+    match index {
+        Some(index) => {
+            info!("Book updated: {:?}", book);
+            books[index] = book;
+        }
+        None => {
+            if book.cover_path.is_none() {
+                book.cover_path = Some(Book::default_cover_path(&book.title));
+            }
+            info!("Book added: {:?}", book);
+            books.push(book);
+        }
+    }
+    books.sort_by_key(|b| b.title_normalized());
+    let response = ok(status, json!(books.iter().find(|b| b.id == id)));
+    let response = match status {
+        201 => response.with_header(header("Location", &format!("/api/books/{id}"))),
+        _ => response,
+    };
+    (response, true)
+}
+
+/// Lets a dashboard served from the configured origin call the API. Without
+/// one, browsers keep other sites from reading the answers.
+fn cors(response: ResponseBox, origin: &str) -> ResponseBox {
+    if origin.is_empty() {
+        return response;
+    }
+    response
+        .with_header(header("Access-Control-Allow-Origin", origin))
+        .with_header(header(
+            "Access-Control-Allow-Methods",
+            "GET, POST, PUT, PATCH, DELETE, OPTIONS",
+        ))
+        .with_header(header(
+            "Access-Control-Allow-Headers",
+            "Authorization, Content-Type",
+        ))
+}
+
+fn ok(status: u16, value: Value) -> ResponseBox {
+    Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+        .boxed()
+}
+
+fn error(status: u16, message: &str) -> ResponseBox {
+    ok(status, json!({ "error": message }))
+}
+
+fn not_found() -> ResponseBox {
+    error(404, "Not found")
+}
+
+fn too_large() -> ResponseBox {
+    error(413, &format!("The body is larger than {MAX_BODY} bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::sync::Mutex;
+    use tiny_http::TestRequest;
+
+    fn library(token: &str, read_only: bool) -> Library {
+        Library {
+            token: token.to_string(),
+            read_only,
+            allowed_origin: String::new(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn request(method: Method, token: Option<&str>, body: &'static str) -> Request {
+        let mut request = TestRequest::new()
+            .with_method(method)
+            .with_path("/api/books")
+            .with_body(body);
+        if let Some(token) = token {
+            request = request.with_header(header("Authorization", &format!("Bearer {token}")));
+        }
+        request.into()
+    }
+
+    fn status(mut request: Request, library: &Library) -> u16 {
+        handle(&mut request, "/api/books", "", library)
+            .status_code()
+            .0
+    }
+
+    fn dune() -> Book {
+        Book {
+            id: Uuid::new_v4(),
+            title: "Dune".to_string(),
+            authors: vec!["Frank Herbert".to_string()],
+            ..Book::default()
+        }
+    }
+
+    #[rstest]
+    #[case(Method::Get, None)]
+    #[case(Method::Get, Some("wrong"))]
+    #[case(Method::Get, Some("secre"))]
+    #[case(Method::Post, None)]
+    #[case(Method::Delete, Some("secret "))]
+    fn rejects_a_missing_or_wrong_token(#[case] method: Method, #[case] token: Option<&str>) {
+        assert_eq!(
+            status(request(method, token, "{}"), &library("secret", false)),
+            401
+        );
+    }
+
+    #[rstest]
+    #[case(Method::Post)]
+    #[case(Method::Put)]
+    #[case(Method::Patch)]
+    #[case(Method::Delete)]
+    fn refuses_changes_when_read_only(#[case] method: Method) {
+        let library = library("secret", true);
+        assert_eq!(status(request(method, Some("secret"), "{}"), &library), 403);
+    }
+
+    #[rstest]
+    #[case(Method::Post)]
+    #[case(Method::Delete)]
+    fn refuses_changes_without_a_token(#[case] method: Method) {
+        assert_eq!(
+            status(request(method, None, "{}"), &library("", false)),
+            403
+        );
+    }
+
+    #[test]
+    fn refuses_a_body_over_the_limit() {
+        let body = "a".repeat(MAX_BODY + 1).leak();
+        let request = request(Method::Post, Some("secret"), body);
+        assert_eq!(status(request, &library("secret", false)), 413);
+    }
+
+    #[rstest]
+    #[case(r#"{"private": true}"#)]
+    #[case(r#"{"id": "00000000-0000-0000-0000-000000000000"}"#)]
+    #[case(r#"{"rating": 5, "highlights": []}"#)]
+    #[case(r#"{"cover_path": "/etc/passwd"}"#)]
+    fn refuses_to_patch_other_fields(#[case] body: &str) {
+        let book = dune();
+        let id = book.id.to_string();
+        let mut books = vec![book.clone()];
+        let (response, changed) =
+            route(&Method::Patch, &["api", "books", &id], "", body, &mut books);
+        assert_eq!(response.status_code().0, 400);
+        assert!(!changed);
+        assert_eq!(books[0].rating, book.rating);
+        assert_eq!(books[0].cover_path, book.cover_path);
+        assert!(!books[0].private);
+    }
+
+    #[test]
+    fn patches_editable_fields() {
+        let book = dune();
+        let id = book.id.to_string();
+        let mut books = vec![book];
+        let (response, changed) = route(
+            &Method::Patch,
+            &["api", "books", &id],
+            "",
+            r#"{"rating": 4, "tags": ["classic"]}"#,
+            &mut books,
+        );
+        assert_eq!(response.status_code().0, 200);
+        assert!(changed);
+        assert_eq!(books[0].rating, 4);
+        assert_eq!(books[0].tags, ["classic"]);
+        assert_eq!(books[0].title, "Dune");
+    }
+
+    #[test]
+    fn refuses_a_duplicate_isbn() {
+        let mut book = dune();
+        book.identifiers.isbn_13 = Some("9780441172719".to_string());
+        let mut books = vec![book];
+        let (response, changed) = route(
+            &Method::Post,
+            &["api", "books"],
+            "",
+            r#"{"title": "Dune", "authors": ["Frank Herbert"],
+                "identifiers": {"isbn_13": "9780441172719"}}"#,
+            &mut books,
+        );
+        assert_eq!(response.status_code().0, 422);
+        assert!(!changed);
+        assert_eq!(books.len(), 1);
+    }
+}
@@ -0,0 +1,178 @@
+pub mod api;
+pub mod opds;
+
+use crate::{config::ServerConfig, model::persistance};
+use log::{error, info};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use std::{
+    fs::File,
+    io,
+    net::IpAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+};
+use tiny_http::{Header, Request, Response, ResponseBox, Server};
+
+/// Requests are answered by a few threads, so a long download doesn't hold
+/// up browsing on another device.
+const WORKERS: usize = 4;
+
+/// What `bookie serve` offers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// The JSON API under `/api`.
+    Api,
+    /// The OPDS catalog under `/opds`, for e-readers.
+    Opds,
+}
+
+/// The settings of the API and the lock on the state file, shared by the
+/// worker threads.
+pub struct Library {
+    pub token: String,
+    pub read_only: bool,
+    pub allowed_origin: String,
+    pub lock: Mutex<()>,
+}
+
+/// Serves the library until the process is stopped. It is read again for
+/// every request, so changes made in the meantime show up right away.
+pub fn run(address: &str, port: u16, mode: Mode, config: ServerConfig) -> io::Result<()> {
+    if mode == Mode::Api && config.token.is_empty() && !is_loopback(address) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Set a token before serving the API on {address}, other devices can reach it"),
+        ));
+    }
+    let server = Arc::new(Server::http((address, port)).map_err(io::Error::other)?);
+    let library = Arc::new(Library {
+        token: config.token,
+        read_only: config.read_only,
+        allowed_origin: config.allowed_origin,
+        lock: Mutex::new(()),
+    });
+    match mode {
+        Mode::Api => println!(
+            "Serving the API at http://{address}:{port}/api{}, stop with CTRL+C",
+            if library.token.is_empty() {
+                " read-only, as there is no token"
+            } else if library.read_only {
+                " with a token, read-only"
+            } else {
+                " with a token"
+            },
+        ),
+        Mode::Opds => {
+            println!("Serving the OPDS catalog at http://{address}:{port}/opds, stop with CTRL+C")
+        }
+    }
+    let workers = (0..WORKERS)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let library = Arc::clone(&library);
+            thread::spawn(move || loop {
+                match server.recv() {
+                    Ok(request) => answer(request, mode, &library),
+                    Err(e) => error!("Cannot receive a request: {e}"),
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+fn is_loopback(address: &str) -> bool {
+    address == "localhost" || address.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn answer(mut request: Request, mode: Mode, library: &Library) {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let response = match mode {
+        Mode::Api if path == "/api" || path.starts_with("/api/") => {
+            api::handle(&mut request, path, query, library)
+        }
+        Mode::Opds => {
+            let base = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Host"))
+                .map(|h| format!("http://{}", h.value))
+                .unwrap_or_default();
+            let state = {
+                let _lock = library.lock.lock().unwrap_or_else(|e| e.into_inner());
+                persistance::load()
+            };
+            match state {
+                Ok(state) => opds::handle(path, query, &base, &state.books),
+                Err(e) => text(500, &format!("Cannot load the library: {e}")),
+            }
+        }
+        _ => not_found(),
+    };
+    info!("{} {url} {}", request.method(), response.status_code().0);
+    if let Err(e) = request.respond(response) {
+        error!("Cannot answer {url}: {e}");
+    }
+}
+
+pub fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+pub fn text(status: u16, body: &str) -> ResponseBox {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", "text/plain; charset=utf-8"))
+        .boxed()
+}
+
+pub fn not_found() -> ResponseBox {
+    text(404, "Not found")
+}
+
+pub fn xml(content_type: &str, body: String) -> ResponseBox {
+    Response::from_string(body)
+        .with_header(header(
+            "Content-Type",
+            &format!("{content_type}; charset=utf-8"),
+        ))
+        .boxed()
+}
+
+pub fn bytes(content_type: &str, body: Vec<u8>) -> ResponseBox {
+    Response::from_data(body)
+        .with_header(header("Content-Type", content_type))
+        .boxed()
+}
+
+/// Sends an open file as a download under the name of its path.
+pub fn file(file: File, path: &Path, content_type: &str) -> ResponseBox {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Header values are ASCII, so the real name goes in the encoded form.
+    let fallback = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let encoded = utf8_percent_encode(&name, NON_ALPHANUMERIC);
+    Response::from_file(file)
+        .with_header(header("Content-Type", content_type))
+        .with_header(header(
+            "Content-Disposition",
+            &format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}"),
+        ))
+        .boxed()
+}
@@ -0,0 +1,542 @@
+use crate::{
+    export::site::escape,
+    library::EBOOK_EXTENSIONS,
+    model::book::{author_name::AuthorName, linked_file::LinkedFile, Book, COVERS_DIR},
+    server::{bytes, file, not_found, xml},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use image::{imageops::FilterType, ImageFormat};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{Cursor, Seek},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tiny_http::ResponseBox;
+use uuid::Uuid;
+
+const NAVIGATION: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+const ACQUISITION: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+const OPENSEARCH: &str = "application/opensearchdescription+xml";
+const RECENT_ENTRIES: usize = 50;
+const THUMBNAIL_WIDTH: u32 = 200;
+const THUMBNAIL_HEIGHT: u32 = 300;
+
+/// A link to a feed or a section of the catalog.
+struct Section {
+    title: String,
+    href: String,
+    content: String,
+    kind: &'static str,
+}
+
+/// Answers a request for a path under `/opds`. Only books with an EPUB or
+/// PDF that still exists are listed, since the catalog is for downloading them.
+pub fn handle(path: &str, query: &str, base: &str, books: &[Book]) -> ResponseBox {
+    let books = books
+        .iter()
+        .filter(|b| {
+            b.file
+                .as_ref()
+                .is_some_and(|f| is_ebook(&f.path) && f.exists())
+        })
+        .collect::<Vec<_>>();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .map(|s| percent_decode_str(s).decode_utf8_lossy().to_string())
+        .collect::<Vec<_>>();
+    let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+    let href = match query {
+        "" => path.to_string(),
+        query => format!("{path}?{query}"),
+    };
+
+    match segments.as_slice() {
+        [""] | ["opds"] => navigation_feed(&href, "Library", &root_sections(&books)),
+        ["opds", "opensearch.xml"] => xml(OPENSEARCH, opensearch(base)),
+        ["opds", "all"] => {
+            let mut books = books;
+            books.sort_by_key(|b| b.title_normalized());
+            acquisition_feed(&href, "All books", &books)
+        }
+        ["opds", "recent"] => {
+            let mut books = books;
+            books.sort_by_key(|b| std::cmp::Reverse(added(b)));
+            books.truncate(RECENT_ENTRIES);
+            acquisition_feed(&href, "Recently added", &books)
+        }
+        ["opds", "search"] => {
+            let terms = form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "q")
+                .map(|(_, value)| value.to_string())
+                .unwrap_or_default();
+            let mut books = books
+                .into_iter()
+                .filter(|b| !terms.trim().is_empty() && b.matches_query(&terms))
+                .collect::<Vec<_>>();
+            books.sort_by_key(|b| b.title_normalized());
+            acquisition_feed(&href, &format!("Search: {terms}"), &books)
+        }
+        ["opds", "authors"] => {
+            let sections = groups(&books, |b| b.authors.clone(), family_name)
+                .into_iter()
+                .map(|(name, books)| section(&name, "authors", books.len()))
+                .collect::<Vec<_>>();
+            navigation_feed(&href, "By author", &sections)
+        }
+        ["opds", "authors", name] => {
+            let mut books = books
+                .into_iter()
+                .filter(|b| b.authors.iter().any(|a| a == name))
+                .collect::<Vec<_>>();
+            books.sort_by_key(|b| (b.year, b.title_normalized()));
+            acquisition_feed(&href, name, &books)
+        }
+        ["opds", "series"] => {
+            let sections = groups(&books, series, str::to_lowercase)
+                .into_iter()
+                .map(|(name, books)| section(&name, "series", books.len()))
+                .collect::<Vec<_>>();
+            navigation_feed(&href, "By series", &sections)
+        }
+        ["opds", "series", name] => {
+            let mut books = books
+                .into_iter()
+                .filter(|b| b.series.as_deref() == Some(*name))
+                .collect::<Vec<_>>();
+            books.sort_by(|a, b| {
+                let index = |book: &Book| book.series_index.unwrap_or(f32::MAX);
+                index(a).total_cmp(&index(b))
+            });
+            acquisition_feed(&href, name, &books)
+        }
+        ["opds", "tags"] => {
+            let sections = groups(&books, |b| b.tags.clone(), str::to_lowercase)
+                .into_iter()
+                .map(|(name, books)| section(&name, "tags", books.len()))
+                .collect::<Vec<_>>();
+            navigation_feed(&href, "By tag", &sections)
+        }
+        ["opds", "tags", tag] => {
+            let mut books = books
+                .into_iter()
+                .filter(|b| b.tags.iter().any(|t| t == tag))
+                .collect::<Vec<_>>();
+            books.sort_by_key(|b| b.title_normalized());
+            acquisition_feed(&href, tag, &books)
+        }
+        ["opds", "books", id, resource] => {
+            let Some(book) = Uuid::parse_str(id)
+                .ok()
+                .and_then(|id| books.into_iter().find(|b| b.id == id))
+            else {
+                return not_found();
+            };
+            match *resource {
+                "file" => match open_ebook(book) {
+                    Some((ebook, path)) => file(ebook, path, ebook_type(path)),
+                    None => not_found(),
+                },
+                "cover" => match cover(book, Path::new(COVERS_DIR))
+                    .and_then(|p| Some((File::open(&p).ok()?, p)))
+                {
+                    Some((image, path)) => file(image, &path, image_type(&path)),
+                    None => not_found(),
+                },
+                "thumbnail" => match cover(book, Path::new(COVERS_DIR))
+                    .as_deref()
+                    .and_then(thumbnail)
+                {
+                    Some(data) => bytes("image/jpeg", data),
+                    None => not_found(),
+                },
+                _ => not_found(),
+            }
+        }
+        _ => not_found(),
+    }
+}
+
+fn root_sections(books: &[&Book]) -> Vec<Section> {
+    let names = |f: fn(&Book) -> Vec<String>| groups(books, f, str::to_lowercase).len();
+    vec![
+        Section {
+            title: "By author".to_string(),
+            href: "/opds/authors".to_string(),
+            content: count(names(|b| b.authors.clone()), "author", "authors"),
+            kind: NAVIGATION,
+        },
+        Section {
+            title: "By series".to_string(),
+            href: "/opds/series".to_string(),
+            content: count(names(series), "series", "series"),
+            kind: NAVIGATION,
+        },
+        Section {
+            title: "By tag".to_string(),
+            href: "/opds/tags".to_string(),
+            content: count(names(|b| b.tags.clone()), "tag", "tags"),
+            kind: NAVIGATION,
+        },
+        Section {
+            title: "Recently added".to_string(),
+            href: "/opds/recent".to_string(),
+            content: format!("The last {RECENT_ENTRIES} ebooks added to the library"),
+            kind: ACQUISITION,
+        },
+        Section {
+            title: "All books".to_string(),
+            href: "/opds/all".to_string(),
+            content: count(books.len(), "ebook", "ebooks"),
+            kind: ACQUISITION,
+        },
+    ]
+}
+
+fn section(name: &str, path: &str, books: usize) -> Section {
+    Section {
+        title: name.to_string(),
+        href: format!(
+            "/opds/{path}/{}",
+            utf8_percent_encode(name, NON_ALPHANUMERIC)
+        ),
+        content: count(books, "book", "books"),
+        kind: ACQUISITION,
+    }
+}
+
+/// Books by each of the names the key gives, in the order of the sort key.
+fn groups<'a>(
+    books: &[&'a Book],
+    key: impl Fn(&Book) -> Vec<String>,
+    sort_key: fn(&str) -> String,
+) -> Vec<(String, Vec<&'a Book>)> {
+    let mut groups = BTreeMap::<(String, String), Vec<&Book>>::new();
+    for book in books {
+        for name in key(book) {
+            groups
+                .entry((sort_key(&name), name))
+                .or_default()
+                .push(book);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|((_, name), books)| (name, books))
+        .collect()
+}
+
+fn count(number: usize, one: &str, many: &str) -> String {
+    format!("{number} {}", if number == 1 { one } else { many })
+}
+
+fn family_name(name: &str) -> String {
+    AuthorName::parse(name).family.to_lowercase()
+}
+
+fn series(book: &Book) -> Vec<String> {
+    book.series.clone().into_iter().collect()
+}
+
+fn feed_head(href: &str, title: &str, kind: &str) -> Vec<String> {
+    vec![
+        r#"<?xml version="1.0" encoding="utf-8"?>"#.to_string(),
+        r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/" xmlns:opds="http://opds-spec.org/2010/catalog">"#.to_string(),
+        format!("  <id>urn:bookie:{}</id>", escape(href)),
+        format!("  <title>{}</title>", escape(title)),
+        format!("  <updated>{}</updated>", timestamp(Utc::now())),
+        "  <author><name>bookie</name></author>".to_string(),
+        format!(r#"  <link rel="start" href="/opds" type="{NAVIGATION}"/>"#),
+        format!(r#"  <link rel="self" href="{}" type="{kind}"/>"#, escape(href)),
+        format!(r#"  <link rel="search" href="/opds/opensearch.xml" type="{OPENSEARCH}"/>"#),
+    ]
+}
+
+fn navigation_feed(href: &str, title: &str, sections: &[Section]) -> ResponseBox {
+    let mut lines = feed_head(href, title, NAVIGATION);
+    let updated = timestamp(Utc::now());
+    for section in sections {
+        lines.extend([
+            "  <entry>".to_string(),
+            format!("    <title>{}</title>", escape(&section.title)),
+            format!("    <id>urn:bookie:{}</id>", escape(&section.href)),
+            format!("    <updated>{updated}</updated>"),
+            format!(
+                r#"    <content type="text">{}</content>"#,
+                escape(&section.content)
+            ),
+            format!(
+                r#"    <link rel="subsection" href="{}" type="{}"/>"#,
+                escape(&section.href),
+                section.kind
+            ),
+            "  </entry>".to_string(),
+        ]);
+    }
+    lines.push("</feed>\n".to_string());
+    xml(NAVIGATION, lines.join("\n"))
+}
+
+fn acquisition_feed(href: &str, title: &str, books: &[&Book]) -> ResponseBox {
+    let mut lines = feed_head(href, title, ACQUISITION);
+    for book in books {
+        lines.extend(entry(book));
+    }
+    lines.push("</feed>\n".to_string());
+    xml(ACQUISITION, lines.join("\n"))
+}
+
+fn entry(book: &Book) -> Vec<String> {
+    let href = format!("/opds/books/{}", book.id);
+    let mut lines = vec![
+        "  <entry>".to_string(),
+        format!("    <title>{}</title>", escape(&book.title)),
+        format!("    <id>urn:uuid:{}</id>", book.id),
+        format!("    <updated>{}</updated>", timestamp(added(book))),
+    ];
+    for author in &book.authors {
+        lines.push(format!(
+            "    <author><name>{}</name></author>",
+            escape(author)
+        ));
+    }
+    if let Some(language) = &book.language {
+        lines.push(format!(
+            "    <dc:language>{}</dc:language>",
+            escape(language)
+        ));
+    }
+    if let Some(publisher) = &book.publisher {
+        lines.push(format!(
+            "    <dc:publisher>{}</dc:publisher>",
+            escape(publisher)
+        ));
+    }
+    if book.year > 0 {
+        lines.push(format!("    <dc:issued>{}</dc:issued>", book.year));
+    }
+    if let Some(isbn) = book.identifiers.isbn() {
+        lines.push(format!(
+            "    <dc:identifier>urn:isbn:{}</dc:identifier>",
+            escape(isbn)
+        ));
+    }
+    for tag in &book.tags {
+        lines.push(format!(
+            r#"    <category term="{0}" label="{0}"/>"#,
+            escape(tag)
+        ));
+    }
+    let mut summary = vec![];
+    if let Some(series) = &book.series {
+        summary.push(match book.series_index {
+            Some(index) => format!("{series} #{index}"),
+            None => series.clone(),
+        });
+    }
+    if book.pages > 0 {
+        summary.push(format!("{} pages", book.pages));
+    }
+    if !summary.is_empty() {
+        lines.push(format!(
+            r#"    <summary type="text">{}</summary>"#,
+            escape(&summary.join(", "))
+        ));
+    }
+    if let Some(path) = cover(book, Path::new(COVERS_DIR)) {
+        lines.push(format!(
+            r#"    <link rel="http://opds-spec.org/image" href="{href}/cover" type="{}"/>"#,
+            image_type(&path)
+        ));
+        lines.push(format!(
+            r#"    <link rel="http://opds-spec.org/image/thumbnail" href="{href}/thumbnail" type="image/jpeg"/>"#
+        ));
+    }
+    if let Some(linked) = &book.file {
+        lines.push(format!(
+            r#"    <link rel="http://opds-spec.org/acquisition" href="{href}/file" type="{}" length="{}"/>"#,
+            ebook_type(&linked.path),
+            linked.size
+        ));
+    }
+    lines.push("  </entry>".to_string());
+    lines
+}
+
+fn opensearch(base: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>bookie</ShortName>
+  <Description>Search the ebooks by title, author or ISBN</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <OutputEncoding>UTF-8</OutputEncoding>
+  <Url type="{ACQUISITION}" template="{}/opds/search?q={{searchTerms}}"/>
+</OpenSearchDescription>
+"#,
+        escape(base)
+    )
+}
+
+/// When the ebook file was put in the library, as far as the file system
+/// remembers.
+fn added(book: &Book) -> DateTime<Utc> {
+    book.file
+        .as_ref()
+        .and_then(|f| fs::metadata(&f.path).ok())
+        .and_then(|m| m.modified().ok())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .into()
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// The cover, as long as it is a file in the `covers` folder; the stored path
+/// is never served as it is.
+fn cover(book: &Book, covers: &Path) -> Option<PathBuf> {
+    let covers = fs::canonicalize(covers).ok()?;
+    let path = fs::canonicalize(book.cover_path.as_ref()?).ok()?;
+    (path.starts_with(covers) && path.is_file()).then_some(path)
+}
+
+/// The book's ebook, opened only when it is still the file that was linked,
+/// so a path changed in the state file can't hand out anything else.
+fn open_ebook(book: &Book) -> Option<(File, &Path)> {
+    let linked = book.file.as_ref().filter(|f| is_ebook(&f.path))?;
+    let mut ebook = File::open(&linked.path).ok()?;
+    let current = LinkedFile::from_file(&linked.path, &ebook).ok()?;
+    if !current.same_content(linked) {
+        return None;
+    }
+    ebook.rewind().ok()?;
+    Some((ebook, &linked.path))
+}
+
+fn is_ebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| EBOOK_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+}
+
+fn thumbnail(path: &Path) -> Option<Vec<u8>> {
+    let image = image::open(path).ok()?;
+    let mut data = Cursor::new(vec![]);
+    image
+        .resize(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT, FilterType::Triangle)
+        .to_rgb8()
+        .write_to(&mut data, ImageFormat::Jpeg)
+        .ok()?;
+    Some(data.into_inner())
+}
+
+fn ebook_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "epub" => "application/epub+zip",
+        _ => "application/pdf",
+    }
+}
+
+fn image_type(path: &Path) -> &'static str {
+    ImageFormat::from_path(path)
+        .map(|f| f.to_mime_type())
+        .unwrap_or("image/jpeg")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, os::unix::fs::symlink};
+
+    /// A folder with `covers/`, a cover in it and files next to it.
+    struct Folder(PathBuf);
+
+    impl Folder {
+        fn new() -> Self {
+            let dir = env::temp_dir().join(format!("bookie-opds-{}", Uuid::new_v4()));
+            fs::create_dir_all(dir.join("covers")).unwrap();
+            fs::write(dir.join("covers/Dune.jpg"), "cover").unwrap();
+            fs::write(dir.join("secret.jpg"), "secret").unwrap();
+            fs::write(dir.join("Dune.epub"), "ebook").unwrap();
+            fs::write(dir.join("other.epub"), "other").unwrap();
+            fs::write(dir.join("notes.txt"), "ebook").unwrap();
+            Self(dir)
+        }
+
+        fn covers(&self) -> PathBuf {
+            self.0.join("covers")
+        }
+    }
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    fn book(cover_path: Option<PathBuf>, file: Option<LinkedFile>) -> Book {
+        Book {
+            id: Uuid::new_v4(),
+            title: "Dune".to_string(),
+            cover_path,
+            file,
+            ..Book::default()
+        }
+    }
+
+    #[test]
+    fn serves_covers_from_the_covers_folder() {
+        let folder = Folder::new();
+        let book = book(Some(folder.covers().join("Dune.jpg")), None);
+        assert!(cover(&book, &folder.covers()).is_some());
+    }
+
+    #[test]
+    fn refuses_covers_outside_the_covers_folder() {
+        let folder = Folder::new();
+        symlink(
+            folder.0.join("secret.jpg"),
+            folder.covers().join("link.jpg"),
+        )
+        .unwrap();
+        for path in [
+            folder.0.join("secret.jpg"),
+            folder.covers().join("../secret.jpg"),
+            folder.covers().join("link.jpg"),
+            folder.covers(),
+        ] {
+            let book = book(Some(path.clone()), None);
+            assert_eq!(cover(&book, &folder.covers()), None, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn serves_the_linked_ebook() {
+        let folder = Folder::new();
+        let file = LinkedFile::read(&folder.0.join("Dune.epub")).unwrap();
+        assert!(open_ebook(&book(None, Some(file))).is_some());
+    }
+
+    #[test]
+    fn refuses_a_changed_path_or_another_file_type() {
+        let folder = Folder::new();
+        let linked = LinkedFile::read(&folder.0.join("Dune.epub")).unwrap();
+        for path in [folder.0.join("other.epub"), folder.0.join("notes.txt")] {
+            let file = LinkedFile {
+                path: path.clone(),
+                ..linked.clone()
+            };
+            let book = book(None, Some(file));
+            assert!(open_ebook(&book).is_none(), "{}", path.display());
+            let response = handle(&format!("/opds/books/{}/file", book.id), "", "", &[book]);
+            assert_eq!(response.status_code().0, 404);
+        }
+    }
+}
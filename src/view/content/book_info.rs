@@ -1,10 +1,13 @@
 use crate::{
+    metadata::Metadata,
     model::{
-        book::reading_status::ReadingStatus,
+        book::{
+            identifiers::Identifiers, item_type::ItemType, reading_status::ReadingStatus, Book,
+        },
         book_info::{form_field::FormField, text_input::TextInput, CoverStatus},
         Model,
     },
-    view::{content::max_label_width, with_panel, STAR},
+    view::{content::max_label_width, render_text_line, with_panel, STAR},
 };
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -14,17 +17,6 @@ use ratatui::{
     Frame,
 };
 use ratatui_image::StatefulImage;
-use unicode_segmentation::UnicodeSegmentation;
-
-const LABELS: &[&str] = &[
-    " Title: ",
-    " Authors: ",
-    " Year: ",
-    " Pages: ",
-    " Status: ",
-    " Finished on: ",
-    " Rating: ",
-];
 
 pub fn render_book_info(model: &mut Model, frame: &mut Frame, area: Rect) {
     with_panel(frame, area, "Info", |frame, area| {
@@ -33,52 +25,146 @@ pub fn render_book_info(model: &mut Model, frame: &mut Frame, area: Rect) {
             return;
         };
 
-        let values = vec![
-            static_line(&book.title),
-            static_line(book.authors.join(", ")),
-            static_line(book.year.to_string()),
-            static_line(book.pages.to_string()),
-            reading_status_line(&book.reading_status, true),
-            static_line(
-                book.finished_at
-                    .last()
-                    .map(|d| d.to_string())
-                    .unwrap_or("".to_string()),
-            ),
-            Line::styled(
-                STAR.repeat(book.rating as usize),
-                Style::default().fg(Color::LightYellow),
-            ),
-        ];
-
-        render_book_info_content(LABELS, values, model, frame, area);
+        let fields = FormField::order(book.item_type);
+        let values = fields
+            .iter()
+            .map(|field| match field {
+                FormField::ItemType => item_type_line(book.item_type, true),
+                FormField::Title => {
+                    let mut line = static_line(&book.title);
+                    if book.private {
+                        line.spans.push(Span::styled(
+                            "  (private)",
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    line
+                }
+                FormField::Authors => static_line(book.authors.join(", ")),
+                FormField::Year => static_line(book.year.to_string()),
+                FormField::Pages => static_line(book.pages.to_string()),
+                FormField::Volume => static_line(optional_text(book.volume)),
+                FormField::Issue => static_line(optional_text(book.issue)),
+                FormField::IssueDate => static_line(optional_text(book.issue_date)),
+                FormField::Doi => static_line(book.doi.clone().unwrap_or_default()),
+                FormField::Venue => static_line(book.venue.clone().unwrap_or_default()),
+                FormField::Isbn => static_line(isbn_text(&book.identifiers)),
+                FormField::Asin => static_line(optional_text(book.identifiers.asin.as_ref())),
+                FormField::OpenLibraryId => {
+                    static_line(optional_text(book.identifiers.openlibrary_id.as_ref()))
+                }
+                FormField::GoodreadsId => {
+                    static_line(optional_text(book.identifiers.goodreads_id.as_ref()))
+                }
+                FormField::Location => static_line(optional_text(book.location.as_ref())),
+                FormField::ReadingStatus => {
+                    let mut line = reading_status_line(&book.reading_status, true);
+                    if let (ReadingStatus::Reading, Some(position)) =
+                        (&book.reading_status, &book.position)
+                    {
+                        line.spans.push(Span::styled(
+                            format!("  ({}% read)", position.progress),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    if let Some(activity) = device_activity(book) {
+                        line.spans.push(Span::styled(
+                            format!("  {activity}"),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    line
+                }
+                FormField::FinishedAt => static_line(
+                    book.finished_at
+                        .last()
+                        .map(|d| d.to_string())
+                        .unwrap_or("".to_string()),
+                ),
+                FormField::Rating => Line::styled(
+                    STAR.repeat(book.rating as usize),
+                    Style::default().fg(Color::LightYellow),
+                ),
+            })
+            .collect();
+
+        render_book_info_content(&labels(fields), values, model, frame, area);
     });
 }
 
 pub fn render_book_form(model: &mut Model, frame: &mut Frame, area: Rect) {
-    with_panel(frame, area, "Info", |frame, area| {
+    let title = model
+        .book_info
+        .review_queue
+        .describe()
+        .unwrap_or("Info".to_string());
+    with_panel(frame, area, &title, |frame, area| {
         let form = &model.book_info.form;
-        let rating_stars = STAR.repeat(form.rating as usize);
-        let values = vec![
-            render_text_line(&form.title, form.active == FormField::Title),
-            render_text_line(&form.authors, form.active == FormField::Authors),
-            render_text_line(&form.year, form.active == FormField::Year),
-            render_text_line(&form.pages, form.active == FormField::Pages),
-            reading_status_line(
-                &form.reading_status,
-                form.active == FormField::ReadingStatus,
-            ),
-            render_text_line(&form.finished_at, form.active == FormField::FinishedAt),
-            render_text_line(
-                &TextInput::new(rating_stars),
-                form.active == FormField::Rating,
-            ),
-        ];
-
-        render_book_info_content(LABELS, values, model, frame, area);
+        let fields = form.fields();
+        let values = fields
+            .iter()
+            .map(|&field| {
+                let active = form.active == field;
+                match field {
+                    FormField::ItemType => item_type_line(form.item_type, active),
+                    FormField::ReadingStatus => reading_status_line(&form.reading_status, active),
+                    FormField::Rating => {
+                        render_text_line(&TextInput::new(STAR.repeat(form.rating as usize)), active)
+                    }
+                    FormField::Title if active => {
+                        let mut line = render_text_line(&form.title, active);
+                        if let Some(suggestion) = form.suggestions.get(form.selected_suggestion) {
+                            line.spans.push(Span::styled(
+                                format!(
+                                    "  ↳ {} [{}/{}]",
+                                    describe_suggestion(suggestion),
+                                    form.selected_suggestion + 1,
+                                    form.suggestions.len()
+                                ),
+                                Style::default().fg(Color::DarkGray),
+                            ));
+                        }
+                        line
+                    }
+                    _ => match form.text_input(field) {
+                        Some(input) => render_text_line(input, active),
+                        None => Line::default(),
+                    },
+                }
+            })
+            .collect();
+
+        render_book_info_content(&labels(fields), values, model, frame, area);
     });
 }
 
+fn labels(fields: &[FormField]) -> Vec<&'static str> {
+    fields.iter().map(|f| label(*f)).collect()
+}
+
+fn label(field: FormField) -> &'static str {
+    match field {
+        FormField::ItemType => " Type: ",
+        FormField::Title => " Title: ",
+        FormField::Authors => " Authors: ",
+        FormField::Year => " Year: ",
+        FormField::Pages => " Pages: ",
+        FormField::Volume => " Volume: ",
+        FormField::Issue => " Issue: ",
+        FormField::IssueDate => " Issue date: ",
+        FormField::Doi => " DOI: ",
+        FormField::Venue => " Venue: ",
+        FormField::Isbn => " ISBN: ",
+        FormField::Asin => " ASIN: ",
+        FormField::OpenLibraryId => " OpenLibrary: ",
+        FormField::GoodreadsId => " Goodreads: ",
+        FormField::Location => " Location: ",
+        FormField::ReadingStatus => " Status: ",
+        FormField::FinishedAt => " Finished on: ",
+        FormField::Rating => " Rating: ",
+    }
+}
+
 fn render_book_info_content(
     labels: &[&str],
     values: Vec<Line<'static>>,
@@ -136,7 +222,7 @@ fn render_book_cover(model: &mut Model, frame: &mut Frame, area: Rect) {
     match &mut model.book_info.cover {
         CoverStatus::Ready(image_state) => {
             let image = StatefulImage::default().resize(ratatui_image::Resize::Scale(None));
-            frame.render_stateful_widget(image, padded, image_state);
+            frame.render_stateful_widget(image, padded, image_state.as_mut());
         }
         CoverStatus::Loading => {
             render_cover_placeholder(frame, padded, "LOADING");
@@ -208,42 +294,69 @@ fn reading_status_line(status: &ReadingStatus, active: bool) -> Line<'static> {
     ])
 }
 
-/* ---------- shared text rendering ---------- */
-
-fn render_text_line(input: &TextInput, active: bool) -> Line<'static> {
-    text_with_cursor(&input.text, input.cursor, active)
+/// Reading time and highlights imported from an e-reader, e.g. "3h 20m · 5 highlights".
+fn device_activity(book: &Book) -> Option<String> {
+    let mut parts = vec![];
+    if book.reading_seconds >= 60 {
+        let minutes = book.reading_seconds / 60;
+        parts.push(format!("{}h {}m", minutes / 60, minutes % 60));
+    }
+    match book.highlights.len() {
+        0 => {}
+        1 => parts.push("1 highlight".to_string()),
+        n => parts.push(format!("{n} highlights")),
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
 }
 
-fn text_with_cursor(text: &str, cursor: usize, active: bool) -> Line<'static> {
-    let graphemes: Vec<&str> = UnicodeSegmentation::graphemes(text, true).collect();
-
-    let base_style = if active {
-        Style::default().fg(Color::LightYellow)
-    } else {
-        Style::default()
-    };
-
-    let cursor_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+fn item_type_line(item_type: ItemType, active: bool) -> Line<'static> {
+    let selected = Style::default()
+        .fg(if active {
+            Color::LightYellow
+        } else {
+            Color::DarkGray
+        })
+        .add_modifier(Modifier::BOLD);
 
-    let mut spans = Vec::with_capacity(graphemes.len() + 1);
+    let normal = Style::default().fg(Color::DarkGray);
 
-    // Highlight the character under the cursor
-    for (i, c) in graphemes.iter().enumerate() {
-        if active && i == cursor {
-            spans.push(Span::styled((*c).to_string(), cursor_style).to_owned());
-        } else {
-            spans.push(Span::styled((*c).to_string(), base_style).to_owned());
+    let mut spans = vec![];
+    for (i, t) in ItemType::ALL.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" | "));
         }
+        spans.push(Span::styled(
+            t.label(),
+            if *t == item_type { selected } else { normal },
+        ));
     }
-
-    // Cursor at end of text: render a block cursor
-    if active && cursor == graphemes.len() {
-        spans.push(Span::styled(" ".to_string(), cursor_style).to_owned());
-    }
-
     Line::from(spans)
 }
 
 fn static_line(text: impl Into<String>) -> Line<'static> {
     Line::raw(text.into())
 }
+
+fn describe_suggestion(suggestion: &Metadata) -> String {
+    let mut text = suggestion.title.clone().unwrap_or_default();
+    if !suggestion.authors.is_empty() {
+        text.push_str(&format!(" — {}", suggestion.authors.join(", ")));
+    }
+    if let Some(year) = suggestion.year {
+        text.push_str(&format!(" ({year})"));
+    }
+    text
+}
+
+fn isbn_text(identifiers: &Identifiers) -> String {
+    match (&identifiers.isbn_13, &identifiers.isbn_10) {
+        (Some(isbn_13), Some(isbn_10)) => format!("{isbn_13} ({isbn_10})"),
+        (Some(isbn_13), None) => isbn_13.clone(),
+        (None, Some(isbn_10)) => isbn_10.clone(),
+        (None, None) => String::new(),
+    }
+}
+
+fn optional_text<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
@@ -1,4 +1,8 @@
-use crate::{model::Model, view::with_panel};
+use crate::{
+    model::{stats::Summary, Model},
+    view::with_panel,
+};
+use chrono::{Datelike, Local};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     widgets::Paragraph,
@@ -8,7 +12,7 @@ use unicode_width::UnicodeWidthStr;
 
 pub fn render_book_stats(model: &Model, frame: &mut Frame, area: Rect) {
     with_panel(frame, area, "Stats", |frame, area| {
-        let rows = build_stats(model, 2026);
+        let rows = build_stats(&model.stats().summary(Local::now().year() as u16));
 
         let labels: Vec<String> = rows.iter().map(|r| r.label.to_string()).collect();
         let values: Vec<String> = rows
@@ -33,24 +37,27 @@ pub fn render_book_stats(model: &Model, frame: &mut Frame, area: Rect) {
     });
 }
 
-fn build_stats(model: &Model, year: i32) -> Vec<StatLine> {
+fn build_stats(summary: &Summary) -> Vec<StatLine> {
     vec![
         StatLine::header("Global:"),
-        StatLine::xxxnew("├ Books in DB:", model.books.len().to_string()),
-        StatLine::xxxnew("├ Authors in DB:", model.unique_authors().to_string()),
+        StatLine::xxxnew("├ Books in DB:", summary.books_in_db.to_string()),
+        StatLine::xxxnew("├ Authors in DB:", summary.authors_in_db.to_string()),
         StatLine::header("└ Status:"),
-        StatLine::xxxnew("  ├ Read:", model.books_read().to_string()),
-        StatLine::xxxnew("  ├ Reading:", model.books_reading().to_string()),
-        StatLine::xxxnew("  └ To read:", model.books_to_read().to_string()),
+        StatLine::xxxnew("  ├ Read:", summary.read.to_string()),
+        StatLine::xxxnew("  ├ Reading:", summary.reading.to_string()),
+        StatLine::xxxnew("  └ To read:", summary.to_read.to_string()),
         StatLine::empty(),
-        StatLine::header(format!("{}:", year)),
-        StatLine::xxxnew(
-            "├ Books:",
-            model.books_read_in_year(year as u16).to_string(),
-        ),
+        StatLine::header("Other items:"),
+        StatLine::xxxnew("├ Comics:", summary.comics.to_string()),
+        StatLine::xxxnew("├ Magazines:", summary.magazines.to_string()),
+        StatLine::xxxnew("└ Papers:", summary.papers.to_string()),
+        StatLine::empty(),
+        StatLine::header(format!("{}:", summary.year)),
+        StatLine::xxxnew("├ Books:", summary.books_read_this_year.to_string()),
+        StatLine::xxxnew("├ Pages:", summary.pages_read_this_year.to_string()),
         StatLine::xxxnew(
-            "└ Pages:",
-            model.pages_read_in_year(year as u16).to_string(),
+            "└ Other items:",
+            summary.other_items_read_this_year.to_string(),
         ),
     ]
 }
@@ -83,6 +90,6 @@ impl StatLine {
     }
 }
 
-fn max_label_width(labels: &Vec<String>) -> u16 {
+fn max_label_width(labels: &[String]) -> u16 {
     labels.iter().map(|l| l.width() as u16).max().unwrap_or(0)
 }
@@ -10,7 +10,11 @@ use ratatui::{
 };
 
 pub fn render_book_table(model: &mut Model, frame: &mut Frame, area: Rect) {
-    with_book_table_panel(frame, area, "Books", |frame, area| {
+    let title = match model.book_table.type_filter {
+        Some(item_type) => item_type.plural(),
+        None => "All items",
+    };
+    with_book_table_panel(frame, area, title, |frame, area| {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Fill(1), Constraint::Length(1)])
@@ -18,7 +22,11 @@ pub fn render_book_table(model: &mut Model, frame: &mut Frame, area: Rect) {
 
         frame.render_stateful_widget(
             create_book_table(
-                &model.books,
+                &model
+                    .visible_book_indices()
+                    .iter()
+                    .map(|&i| &model.books[i])
+                    .collect::<Vec<&Book>>(),
                 model.focus == Focus::Table,
                 model.book_table.table_state.selected(),
             ),
@@ -65,10 +73,10 @@ where
 }
 
 fn create_book_table(
-    books: &Vec<Book>,
+    books: &[&Book],
     is_table_focused: bool,
     selected_row: Option<usize>,
-) -> Table<'_> {
+) -> Table<'static> {
     let rows = books.iter().enumerate().map(|(i, b)| {
         let is_row_active = selected_row.is_some() && selected_row.unwrap() == i;
         Row::new(vec![
@@ -80,7 +88,7 @@ fn create_book_table(
             Cell::from(
                 b.authors
                     .iter()
-                    .map(Book::author_with_initials)
+                    .map(|a| Book::author_with_initials(a))
                     .collect::<Vec<String>>()
                     .join(", "),
             )
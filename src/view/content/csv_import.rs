@@ -0,0 +1,147 @@
+use crate::{
+    model::{
+        csv_import::{CsvImportState, ImportField, RowOutcome},
+        Model,
+    },
+    view::{with_panel, BLUE},
+};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Cell, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+const COLUMN_WIDTH: u16 = 18;
+const PREVIEW_ROWS: usize = 5;
+const HINTS: &str = "←/→: column  ↑/↓: field  D: delimiter  F: date format  S: list separator  P: preset  W: save preset  PgUp/PgDn: problems  ENTER: import  Q: cancel";
+
+pub fn render_csv_import(model: &Model, frame: &mut Frame, area: Rect) {
+    let Some(import) = &model.csv_import else {
+        return;
+    };
+    let file_name = import
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let title = format!("Import {file_name}");
+    with_panel(frame, area, &title, |frame, area| {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Length(PREVIEW_ROWS as u16 + 3),
+                Constraint::Length(2),
+                Constraint::Fill(1),
+                Constraint::Length(2),
+            ])
+            .split(area);
+        frame.render_widget(Paragraph::new(settings_line(import)), rows[0]);
+        frame.render_widget(preview_table(import, area.width), rows[1]);
+        frame.render_widget(Paragraph::new(summary_line(import)), rows[2]);
+        frame.render_widget(Paragraph::new(problem_lines(import)), rows[3]);
+        frame.render_widget(
+            Paragraph::new(HINTS)
+                .style(Style::default().fg(Color::DarkGray))
+                .wrap(Wrap { trim: true }),
+            rows[4],
+        );
+    });
+}
+
+fn settings_line(import: &CsvImportState) -> Line<'static> {
+    let delimiter = match import.delimiter {
+        '\t' => "tab".to_string(),
+        d => format!("'{d}'"),
+    };
+    let date_format = import
+        .date_format
+        .replace("%Y", "YYYY")
+        .replace("%m", "MM")
+        .replace("%d", "DD");
+    let preset = import.preset.as_deref().unwrap_or("none");
+    Line::raw(format!(
+        "Delimiter: {delimiter}   Dates: {date_format}   List separator: '{}'   Preset: {preset}",
+        import.list_separator
+    ))
+}
+
+/// Headers, their fields and the first rows, scrolled so the selected column is visible.
+fn preview_table(import: &CsvImportState, width: u16) -> Table<'static> {
+    let visible = (width / (COLUMN_WIDTH + 1)).max(1) as usize;
+    let first = import.selected_column.saturating_sub(visible - 1);
+    let columns = (first..import.headers.len()).take(visible);
+
+    let header_style = |column: usize| {
+        if column == import.selected_column {
+            Style::default().fg(BLUE).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        }
+    };
+    let header = Row::new(
+        columns
+            .clone()
+            .map(|c| Cell::new(import.headers[c].clone()).style(header_style(c))),
+    );
+    let mapping = Row::new(columns.clone().map(|c| {
+        let field = import.mapping[c];
+        let style = match field {
+            ImportField::Ignore => Style::default().fg(Color::DarkGray),
+            _ => Style::default().fg(Color::LightYellow),
+        };
+        Cell::new(format!("→ {}", field.label())).style(style)
+    }));
+    let preview = import
+        .rows
+        .iter()
+        .filter_map(|(_, cells)| cells.as_ref().ok())
+        .take(PREVIEW_ROWS)
+        .map(|cells| {
+            Row::new(
+                columns
+                    .clone()
+                    .map(|c| Cell::new(cells.get(c).cloned().unwrap_or_default())),
+            )
+        });
+
+    Table::new(
+        std::iter::once(mapping).chain(preview),
+        vec![Constraint::Length(COLUMN_WIDTH); visible],
+    )
+    .header(header)
+}
+
+fn summary_line(import: &CsvImportState) -> Line<'static> {
+    let new = import.count(|o| matches!(o, RowOutcome::New(_)));
+    let duplicates = import.count(|o| matches!(o, RowOutcome::Duplicate(_)));
+    let invalid = import.count(|o| matches!(o, RowOutcome::Invalid(_)));
+    let style = if new == 0 {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default().fg(Color::LightGreen)
+    };
+    Line::styled(
+        format!(
+            "{} rows: {new} to import, {duplicates} already in the library, {invalid} invalid",
+            import.rows.len()
+        ),
+        style,
+    )
+}
+
+fn problem_lines(import: &CsvImportState) -> Vec<Line<'static>> {
+    import
+        .problems()
+        .into_iter()
+        .skip(import.problem_scroll)
+        .map(|(line, message)| {
+            Line::styled(
+                format!("Line {line}: {message}"),
+                Style::default().fg(Color::Red),
+            )
+        })
+        .collect()
+}
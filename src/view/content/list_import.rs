@@ -0,0 +1,86 @@
+use crate::{
+    model::{
+        list_import::{Decision, ListImportState},
+        Model,
+    },
+    view::{with_panel, BLUE},
+};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+const HINTS: &str =
+    "↑/↓: choose line  A: accept  X: reject  SPACE: toggle  E: edit  ENTER: add accepted  Q: cancel";
+
+pub fn render_list_import(model: &Model, frame: &mut Frame, area: Rect) {
+    let Some(import) = &model.list_import else {
+        return;
+    };
+    let file_name = import
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let title = format!(
+        "Add from {file_name} · {} of {} accepted",
+        import.accepted(),
+        import.candidates.len()
+    );
+    with_panel(frame, area, &title, |frame, area| {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        let mut state = TableState::default().with_selected(Some(import.selected));
+        frame.render_stateful_widget(candidate_table(import), rows[0], &mut state);
+        if let Some(candidate) = import.selected_candidate() {
+            frame.render_widget(
+                Paragraph::new(format!("Line {}: {}", candidate.line, candidate.text))
+                    .style(Style::default().fg(Color::DarkGray)),
+                rows[1],
+            );
+        }
+        frame.render_widget(
+            Paragraph::new(HINTS).style(Style::default().fg(Color::DarkGray)),
+            rows[2],
+        );
+    });
+}
+
+fn candidate_table(import: &ListImportState) -> Table<'static> {
+    let header = Row::new(["", "Title", "Authors", "Year", "Problem"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = import.candidates.iter().map(|candidate| {
+        let (mark, style) = match candidate.decision {
+            Decision::Accept => ("✓", Style::default().fg(Color::LightGreen)),
+            Decision::Reject => ("✗", Style::default().fg(Color::DarkGray)),
+        };
+        let problem = candidate.book.as_ref().err().cloned().unwrap_or_default();
+        Row::new([
+            Cell::new(mark).style(style),
+            Cell::new(candidate.title.clone()),
+            Cell::new(candidate.authors.join(", ")),
+            Cell::new(candidate.year.map(|y| y.to_string()).unwrap_or_default()),
+            Cell::new(problem).style(Style::default().fg(Color::Red)),
+        ])
+    });
+    Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Length(4),
+            Constraint::Fill(2),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::default().fg(BLUE).add_modifier(Modifier::BOLD))
+}
@@ -1,13 +1,22 @@
 mod book_info;
 mod book_stats;
 mod book_table;
+mod csv_import;
+mod list_import;
+mod reader;
 
 use crate::{
-    model::{book_info::BookInfoMode, Model},
+    model::{
+        book_info::{form_field::FormField, BookInfoMode},
+        Model,
+    },
     view::content::{
         book_info::{render_book_form, render_book_info},
         book_stats::render_book_stats,
         book_table::render_book_table,
+        csv_import::render_csv_import,
+        list_import::render_list_import,
+        reader::render_reader,
     },
 };
 use ratatui::{
@@ -17,13 +26,28 @@ use ratatui::{
 use unicode_width::UnicodeWidthStr;
 
 pub fn render_content(model: &mut Model, frame: &mut Frame, area: Rect) {
+    if model.reader.is_some() {
+        render_reader(model, frame, area);
+        return;
+    }
+    if model.csv_import.is_some() {
+        render_csv_import(model, frame, area);
+        return;
+    }
+    if model.list_import.is_some() {
+        render_list_import(model, frame, area);
+        return;
+    }
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Fill(1), Constraint::Length(30)])
         .split(area);
     let table_details_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Fill(1), Constraint::Length(13)])
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(info_panel_height(model)),
+        ])
         .split(chunks[0]);
 
     render_book_table(model, frame, table_details_chunks[0]);
@@ -36,6 +60,17 @@ pub fn render_content(model: &mut Model, frame: &mut Frame, area: Rect) {
     render_book_stats(model, frame, chunks[1]);
 }
 
+fn info_panel_height(model: &Model) -> u16 {
+    let field_count = match model.book_info.mode {
+        BookInfoMode::View => model
+            .get_selected_book()
+            .map_or(0, |book| FormField::order(book.item_type).len()),
+        BookInfoMode::Add | BookInfoMode::Edit => model.book_info.form.fields().len(),
+    };
+    // Two lines for the panel border, never shorter than the cover placeholder.
+    (field_count as u16 + 2).max(13)
+}
+
 pub fn max_label_width(labels: &[&str]) -> u16 {
     labels.iter().map(|l| l.width() as u16).max().unwrap_or(0)
 }
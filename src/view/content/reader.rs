@@ -0,0 +1,95 @@
+use crate::{
+    model::{reader::ReaderState, Model},
+    view::{with_panel, BLUE},
+};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::Paragraph,
+    Frame,
+};
+
+const TOC_WIDTH: u16 = 32;
+const HINTS: &str = "↑/↓: scroll  PgUp/PgDn/Space: page  ←/→: chapter  T: contents  Q: close";
+
+pub fn render_reader(model: &mut Model, frame: &mut Frame, area: Rect) {
+    let book_title = model
+        .get_reader_book()
+        .map(|b| b.title.clone())
+        .unwrap_or_default();
+    let Some(reader) = &mut model.reader else {
+        return;
+    };
+    let title = format!(
+        "{book_title} · {} ({}%)",
+        reader.current_chapter().title,
+        reader.position().progress
+    );
+    with_panel(frame, area, &title, |frame, area| {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .split(area);
+        let text_area = match reader.toc {
+            Some(selected) => {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(TOC_WIDTH), Constraint::Fill(1)])
+                    .split(rows[0]);
+                render_toc(reader, selected, frame, columns[0]);
+                columns[1]
+            }
+            None => rows[0],
+        };
+        reader.width = text_area.width;
+        reader.height = text_area.height;
+        frame.render_widget(Paragraph::new(page_lines(reader)), text_area);
+        frame.render_widget(
+            Paragraph::new(HINTS).style(Style::default().fg(Color::DarkGray)),
+            rows[1],
+        );
+    });
+}
+
+fn page_lines(reader: &ReaderState) -> Vec<Line<'static>> {
+    let height = reader.height as usize;
+    let mut lines = vec![];
+    let paragraphs = reader.current_chapter().paragraphs.len();
+    for paragraph in reader.paragraph..paragraphs {
+        let skip = if paragraph == reader.paragraph {
+            reader.line
+        } else {
+            0
+        };
+        for line in reader.paragraph_lines(paragraph).into_iter().skip(skip) {
+            if lines.len() >= height {
+                return lines;
+            }
+            lines.push(Line::raw(line));
+        }
+    }
+    lines
+}
+
+fn render_toc(reader: &ReaderState, selected: usize, frame: &mut Frame, area: Rect) {
+    // Keep the selected entry on screen in long tables of contents.
+    let first = selected.saturating_sub(area.height.saturating_sub(1) as usize);
+    let lines = reader
+        .chapters
+        .iter()
+        .enumerate()
+        .skip(first)
+        .map(|(i, chapter)| {
+            let style = if i == selected {
+                Style::default().fg(BLUE).add_modifier(Modifier::BOLD)
+            } else if i == reader.chapter {
+                Style::default().fg(Color::LightYellow)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::styled(chapter.title.clone(), style)
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(Paragraph::new(lines), area);
+}
@@ -1,4 +1,7 @@
-use crate::model::{status::StatusMode, Model};
+use crate::{
+    model::{status::StatusMode, Model},
+    view::render_text_line,
+};
 use ratatui::{
     prelude::Rect,
     style::{Color, Style},
@@ -16,6 +19,34 @@ pub fn render_status(model: &Model, frame: &mut Frame, area: Rect) {
         StatusMode::Error(error) => {
             Line::styled(format!("Error: {}", error), Style::default().fg(Color::Red))
         }
+        StatusMode::Info(info) => {
+            Line::styled(info.clone(), Style::default().fg(Color::LightYellow))
+        }
+        StatusMode::Prompt(prompt) => {
+            let mut line = render_text_line(&model.status.input, true);
+            line.spans.insert(0, prompt.label().into());
+            line
+        }
+        StatusMode::ChooseCitationStyle => match model.get_selected_book() {
+            Some(book) => Line::styled(
+                format!(
+                    "Copy '{}' as [a] APA, [m] MLA, [c] Chicago or [s] short citation? [Esc] cancel",
+                    book.title
+                ),
+                Style::default().fg(Color::LightYellow),
+            ),
+            None => Line::default(),
+        },
+        StatusMode::ConfirmFinishBook => match model.get_reader_book() {
+            Some(book) => Line::styled(
+                format!(
+                    "You reached the end of '{}'. Record today as its finish date? [y/n]",
+                    book.title
+                ),
+                Style::default().fg(Color::LightYellow),
+            ),
+            None => Line::default(),
+        },
         StatusMode::ConfirmDeleteBook => match model.get_selected_book() {
             Some(book) => Line::styled(
                 format!(
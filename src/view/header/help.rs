@@ -6,9 +6,21 @@ use ratatui::{
 };
 
 const HELP_1: &[&str] = &["A: add", "E: edit", "D: delete"];
-const HELP_2: &[&str] = &["←/→: choose item in edit", "Tab/Shift+Tab: switch field"];
-const HELP_3: &[&str] = &[""];
-const HELP_4: &[&str] = &["ENTER: submit form"];
+const HELP_2: &[&str] = &[
+    "←/→: choose item in edit",
+    "Tab/Shift+Tab: switch field",
+    "R: read  P: private  O: QR",
+];
+const HELP_3: &[&str] = &[
+    "T: filter by type  S: card",
+    "/: search  C: cite",
+    "↑/↓/→: pick suggestion",
+];
+const HELP_4: &[&str] = &[
+    "ENTER: submit form",
+    "CTRL+L: look up book",
+    "B: barcodes  I: CSV",
+];
 
 pub fn render_help_1(frame: &mut Frame, area: Rect) {
     with_help_panel(frame, area, |frame, inner| {
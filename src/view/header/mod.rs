@@ -20,7 +20,7 @@ pub fn render_header(_: &Model, frame: &mut Frame, area: Rect) {
             Constraint::Length(13),
             Constraint::Length(29),
             Constraint::Fill(1),
-            Constraint::Length(19),
+            Constraint::Length(28),
             Constraint::Length(23),
         ])
         .split(area);
@@ -3,7 +3,7 @@ mod footer;
 mod header;
 
 use crate::{
-    model::Model,
+    model::{book_info::text_input::TextInput, Model},
     view::{content::render_content, footer::render_footer, header::render_header},
 };
 use ratatui::{
@@ -11,6 +11,7 @@ use ratatui::{
     widgets::{Block, Borders, Padding},
     Frame,
 };
+use unicode_segmentation::UnicodeSegmentation;
 
 pub const BLUE: Color = Color::Rgb(0, 170, 223);
 
@@ -49,3 +50,39 @@ where
     let inner = block.inner(area);
     render(frame, inner);
 }
+
+/* ---------- shared text rendering ---------- */
+
+pub fn render_text_line(input: &TextInput, active: bool) -> Line<'static> {
+    text_with_cursor(&input.text, input.cursor, active)
+}
+
+fn text_with_cursor(text: &str, cursor: usize, active: bool) -> Line<'static> {
+    let graphemes: Vec<&str> = UnicodeSegmentation::graphemes(text, true).collect();
+
+    let base_style = if active {
+        Style::default().fg(Color::LightYellow)
+    } else {
+        Style::default()
+    };
+
+    let cursor_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+
+    let mut spans = Vec::with_capacity(graphemes.len() + 1);
+
+    // Highlight the character under the cursor
+    for (i, c) in graphemes.iter().enumerate() {
+        if active && i == cursor {
+            spans.push(Span::styled((*c).to_string(), cursor_style).to_owned());
+        } else {
+            spans.push(Span::styled((*c).to_string(), base_style).to_owned());
+        }
+    }
+
+    // Cursor at end of text: render a block cursor
+    if active && cursor == graphemes.len() {
+        spans.push(Span::styled(" ".to_string(), cursor_style).to_owned());
+    }
+
+    Line::from(spans)
+}